@@ -0,0 +1,161 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::Local;
+use rsfarkle::game::Game;
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct AutoSave {
+    pub saved_at: String,
+    pub transcript: String,
+}
+
+pub struct AutoSaveThread {
+    handle: JoinHandle<()>,
+    running: Arc<AtomicBool>,
+}
+
+impl AutoSave {
+    pub fn path() -> PathBuf {
+        std::env::temp_dir().join("rsfarkle_autosave.json")
+    }
+
+    pub fn load() -> io::Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn remove() -> io::Result<()> {
+        std::fs::remove_file(Self::path())
+    }
+
+    fn save_to(path: &Path, transcript: String) -> io::Result<()> {
+        let save = AutoSave {
+            saved_at: Local::now().format("%F %T").to_string(),
+            transcript,
+        };
+        let partial = path.with_extension("json.tmp");
+        serde_json::to_writer(std::fs::File::create(&partial)?, &save)?;
+        std::fs::rename(partial, path)
+    }
+}
+
+impl AutoSaveThread {
+    pub fn start(state: Arc<Mutex<Option<Game>>>) -> Self {
+        Self::start_with_interval(state, AUTOSAVE_INTERVAL)
+    }
+
+    pub fn start_with_interval(state: Arc<Mutex<Option<Game>>>, interval: Duration) -> Self {
+        Self::spawn(state, interval, AutoSave::path())
+    }
+
+    fn spawn(state: Arc<Mutex<Option<Game>>>, interval: Duration, path: PathBuf) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let handle = thread::spawn(move || loop {
+            thread::park_timeout(interval);
+            let transcript = state.lock().unwrap().as_ref().map(Game::transcript);
+            if let Some(transcript) = transcript {
+                let _ = AutoSave::save_to(&path, transcript);
+            }
+            if !flag.load(Ordering::Relaxed) {
+                break;
+            }
+        });
+        AutoSaveThread { handle, running }
+    }
+
+    pub fn stop(self) {
+        self.running.store(false, Ordering::Relaxed);
+        self.handle.thread().unpark();
+        self.handle.join().expect("Auto-save thread panicked");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsfarkle::farkle::GameAction;
+    use rsfarkle::game::GameConfig;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rsfarkle_{}_{}.json", name, std::process::id()))
+    }
+
+    fn game() -> Game {
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 5,
+            seed: Some(3),
+            ..Default::default()
+        });
+        game.apply(GameAction::Roll).unwrap();
+        game
+    }
+
+    #[test]
+    fn save_round_trips_the_transcript() {
+        let path = scratch_path("save");
+        let game = game();
+        AutoSave::save_to(&path, game.transcript()).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let save = AutoSave::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(save.transcript, game.transcript());
+        let (resumed, _) = Game::from_transcript(&save.transcript).unwrap();
+        assert_eq!(resumed.roll().dice_values(), game.roll().dice_values());
+    }
+
+    #[test]
+    fn thread_saves_the_shared_game() {
+        let path = scratch_path("thread");
+        let game = game();
+        let state = Arc::new(Mutex::new(Some(game.clone())));
+        let thread = AutoSaveThread::spawn(state, Duration::from_millis(10), path.clone());
+        thread::sleep(Duration::from_millis(50));
+        thread.stop();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let save: AutoSave = serde_json::from_str(&contents).unwrap();
+        assert_eq!(save.transcript, game.transcript());
+    }
+
+    #[test]
+    fn thread_skips_saving_without_a_game() {
+        let path = scratch_path("idle");
+        let thread = AutoSaveThread::spawn(
+            Arc::new(Mutex::new(None)),
+            Duration::from_millis(10),
+            path.clone(),
+        );
+        thread::sleep(Duration::from_millis(30));
+        thread.stop();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::Local;
+#[cfg(feature = "onekey")]
+use std::io::Read;
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+pub struct Console {
+    log: Option<File>,
+    captured: Option<Vec<u8>>,
+}
+
+macro_rules! out {
+    ($con:expr, $($arg:tt)*) => {
+        write!($con, $($arg)*).expect("Failed to write output")
+    };
+}
+
+macro_rules! outln {
+    ($con:expr) => {
+        writeln!($con).expect("Failed to write output")
+    };
+    ($con:expr, $($arg:tt)*) => {
+        writeln!($con, $($arg)*).expect("Failed to write output")
+    };
+}
+
+pub(crate) use out;
+pub(crate) use outln;
+
+impl Console {
+    pub fn new(log_path: Option<&str>) -> io::Result<Self> {
+        let log = match log_path {
+            Some(path) => {
+                let mut file = File::create(path)?;
+                writeln!(file, "Session started {}", Local::now().format("%F %T"))?;
+                Some(file)
+            }
+            None => None,
+        };
+        Ok(Console {
+            log,
+            captured: None,
+        })
+    }
+
+    // Collects the output instead of printing it so tests can check it
+    #[cfg(test)]
+    pub fn capturing() -> Self {
+        Console {
+            log: None,
+            captured: Some(vec![]),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn captured(&self) -> String {
+        String::from_utf8_lossy(self.captured.as_deref().unwrap_or_default()).into_owned()
+    }
+
+    pub fn log(&mut self, text: &str) -> io::Result<()> {
+        if let Some(log) = &mut self.log {
+            log.write_all(text.as_bytes())?;
+            log.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn stamp(&mut self) -> io::Result<()> {
+        self.log(&format!("[{}] ", Local::now().format("%T")))
+    }
+
+    pub fn read_line(&mut self) -> io::Result<String> {
+        self.flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        self.log(&input)?;
+        Ok(input)
+    }
+
+    #[cfg(feature = "onekey")]
+    pub fn read_key(&mut self) -> io::Result<char> {
+        self.flush()?;
+        let mut buffer = [0; 1];
+        io::stdin().read_exact(&mut buffer)?;
+        let key = buffer[0] as char;
+        self.log(&key.to_string())?;
+        Ok(key)
+    }
+}
+
+impl Write for Console {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.captured {
+            Some(captured) => captured.extend_from_slice(buf),
+            None => io::stdout().write_all(buf)?,
+        }
+        if let Some(log) = &mut self.log {
+            log.write_all(buf)?;
+            if buf.contains(&b'\n') {
+                log.flush()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()?;
+        if let Some(log) = &mut self.log {
+            log.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+
+use itertools::Itertools;
+use rsfarkle::farkle::{Player, Roll, RollType};
+use rsfarkle::game::GameSummary;
+
+use crate::console::{outln, Console};
+
+pub trait GameDisplay {
+    fn roll_text(&self, roll: &Roll, pickable: Option<[bool; 6]>) -> String;
+
+    fn show_roll(&self, con: &mut Console, roll: &Roll, pickable: Option<[bool; 6]>) {
+        outln!(con, "{}", self.roll_text(roll, pickable));
+    }
+
+    fn show_scoring_message(&self, con: &mut Console, roll_type: RollType, value: u32) {
+        match roll_type {
+            RollType::Farkle => outln!(con, "Farkle!"),
+            RollType::Straight | RollType::TriplePair => outln!(
+                con,
+                "{}!\nSelected {} points' worth of dice.",
+                roll_type,
+                value
+            ),
+            RollType::Simple => outln!(con, "Simple roll — pick your scoring dice."),
+        }
+    }
+
+    fn show_player_turn(&self, con: &mut Console, player: &Player, turn: u32, max_turns: u32) {
+        outln!(
+            con,
+            "{}'s turn {} of {}. Current score: {}.",
+            player.name(),
+            turn,
+            max_turns,
+            player.score()
+        );
+    }
+
+    fn show_game_over(&self, con: &mut Console, _summary: &GameSummary) {
+        outln!(con, "Game over");
+    }
+}
+
+pub fn farkle_banner() -> String {
+    let text = "F A R K L E !";
+    let rule = "=".repeat(text.len() + 4);
+    format!("{}\n  {}\n{}", rule, text, rule)
+}
+
+fn marker_row(pickable: [bool; 6], width: usize) -> String {
+    pickable
+        .iter()
+        .map(|&can_pick| format!("{:^1$}", if can_pick { "^" } else { "" }, width))
+        .join(" ")
+}
+
+pub struct PlainTextDisplay;
+
+impl GameDisplay for PlainTextDisplay {
+    fn roll_text(&self, roll: &Roll, pickable: Option<[bool; 6]>) -> String {
+        let mut text = (1..=6).join(" ");
+        text += "\n------------\n";
+        text += &roll
+            .dice()
+            .iter()
+            .map(|die| match die.picked() {
+                true => "-".to_string(),
+                false => die.value().to_string(),
+            })
+            .join(" ");
+        if let Some(pickable) = pickable {
+            text += "\n";
+            text += &marker_row(pickable, 1);
+        }
+        text.trim_end().to_string()
+    }
+}
+
+pub struct AsciiArtDisplay;
+
+impl AsciiArtDisplay {
+    const WIDTH: usize = 7;
+}
+
+impl GameDisplay for AsciiArtDisplay {
+    fn roll_text(&self, roll: &Roll, pickable: Option<[bool; 6]>) -> String {
+        let edge = |left, right| {
+            roll.dice()
+                .iter()
+                .map(|_| format!("{}─────{}", left, right))
+                .join(" ")
+        };
+        let faces: Vec<_> = roll.dice().iter().map(|die| die.pips()).collect();
+        let mut lines = vec![(1..=6).map(|i| format!("{:^1$}", i, Self::WIDTH)).join(" ")];
+        lines.push(edge('┌', '┐'));
+        for row in 0..3 {
+            lines.push(
+                faces
+                    .iter()
+                    .map(|face| format!("│ {} │", face[row].replace('o', "●")))
+                    .join(" "),
+            );
+        }
+        lines.push(edge('└', '┘'));
+        if let Some(pickable) = pickable {
+            lines.push(marker_row(pickable, Self::WIDTH));
+        }
+        lines.iter().map(|line| line.trim_end()).join("\n")
+    }
+
+    fn show_game_over(&self, con: &mut Console, _summary: &GameSummary) {
+        outln!(con, "┌───────────┐");
+        outln!(con, "│ Game over │");
+        outln!(con, "└───────────┘");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(display: &dyn GameDisplay, roll: &Roll, pickable: Option<[bool; 6]>) -> String {
+        let mut con = Console::capturing();
+        display.show_roll(&mut con, roll, pickable);
+        con.captured()
+    }
+
+    #[test]
+    fn plain_rolls_mark_the_pickable_dice() {
+        let mut roll = Roll::from_values([1, 5, 2, 3, 4, 6]);
+        roll.toggle_die(0);
+        let pickable = Some([false, true, false, false, false, false]);
+        assert_eq!(
+            shown(&PlainTextDisplay, &roll, pickable),
+            "1 2 3 4 5 6\n------------\n- 5 2 3 4 6\n  ^\n"
+        );
+        assert_eq!(
+            shown(&PlainTextDisplay, &roll, None),
+            "1 2 3 4 5 6\n------------\n- 5 2 3 4 6\n"
+        );
+    }
+
+    #[test]
+    fn ascii_rolls_draw_each_die() {
+        let roll = Roll::from_values([1, 2, 3, 4, 5, 6]);
+        let pickable = Some([true, false, false, false, true, false]);
+        let rows = [
+            "   1       2       3       4       5       6",
+            "┌─────┐ ┌─────┐ ┌─────┐ ┌─────┐ ┌─────┐ ┌─────┐",
+            "│     │ │ ●   │ │ ●   │ │ ● ● │ │ ● ● │ │ ● ● │",
+            "│  ●  │ │     │ │  ●  │ │     │ │  ●  │ │ ● ● │",
+            "│     │ │   ● │ │   ● │ │ ● ● │ │ ● ● │ │ ● ● │",
+            "└─────┘ └─────┘ └─────┘ └─────┘ └─────┘ └─────┘",
+            "   ^                               ^",
+        ];
+        assert_eq!(
+            shown(&AsciiArtDisplay, &roll, pickable),
+            rows.join("\n") + "\n"
+        );
+        let unmarked = shown(&AsciiArtDisplay, &roll, None);
+        assert_eq!(unmarked.lines().collect::<Vec<_>>(), rows[..6]);
+    }
+
+    #[test]
+    fn banner_frames_the_title() {
+        assert_eq!(
+            farkle_banner(),
+            "=================\n  F A R K L E !\n================="
+        );
+    }
+}
@@ -0,0 +1,1681 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+#[cfg(feature = "persistence")]
+mod autosave;
+mod console;
+mod display;
+mod prompt;
+mod server;
+
+use chrono::Local;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, Write},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(feature = "persistence")]
+use autosave::{AutoSave, AutoSaveThread};
+use console::{out, outln, Console};
+use display::{farkle_banner, AsciiArtDisplay, GameDisplay, PlainTextDisplay};
+use prompt::{Prompter, ScriptedPrompter, TerminalPrompter};
+use server::udp::{self, UdpGameTransport};
+
+use itertools::Itertools;
+
+use rsfarkle::bot::{simulate_n_turns, Difficulty};
+use rsfarkle::farkle::*;
+use rsfarkle::game::*;
+use rsfarkle::history::GameHistory;
+use rsfarkle::notation::RollNotation;
+use rsfarkle::order::{RollOff, RoundOrder, TurnOrder};
+use rsfarkle::rules::*;
+use rsfarkle::team::{Team, TeamScoring};
+
+use clap::{Args, Parser};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use termios::{tcsetattr, Termios, ICANON, TCSANOW};
+
+#[derive(Debug, Parser)]
+#[command(name = "rsfarkle", version, about = "Command line Farkle game")]
+struct Options {
+    #[command(subcommand)]
+    command: Subcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Subcommand {
+    #[command(about = "Play a game")]
+    Play(Box<PlayOptions>),
+    #[command(about = "Simulate turns played by a bot")]
+    Simulate(SimulateOptions),
+    #[command(about = "Replay a game transcript")]
+    Replay(ReplayOptions),
+    #[command(about = "Show the saved session history")]
+    Stats,
+    #[command(about = "Evaluate a position without playing a game")]
+    Analyze(AnalyzeOptions),
+}
+
+#[derive(Debug, Args)]
+struct PlayOptions {
+    #[arg(
+        short = 'p',
+        long = "players",
+        help = "Player count (default FARKLE_PLAYERS or 2)",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    player_count: Option<usize>,
+    #[arg(
+        short = 't',
+        long = "turns",
+        help = "Turn count (default FARKLE_TURNS or 10)"
+    )]
+    turn_count: Option<u32>,
+    #[arg(long = "stats-report", help = "Print player statistics after the game")]
+    stats_report: bool,
+    #[arg(
+        long = "pot",
+        help = "Play with a shared pot that grows by this amount on each farkle"
+    )]
+    pot: Option<u32>,
+    #[arg(
+        long = "faces",
+        help = "Dice faces and single-scoring faces, e.g. \"8 1:100 8:50\" (default \"6 1:100 5:50\")"
+    )]
+    faces: Option<DiceFaces>,
+    #[arg(
+        long = "preset",
+        help = "Start from a named ruleset: standard, hasbro, doubling, pot, blind or d8",
+        value_parser = parse_preset
+    )]
+    preset: Option<Ruleset>,
+    #[arg(
+        long = "session-log",
+        help = "Write a human-readable log of the session to a file"
+    )]
+    session_log: Option<String>,
+    #[arg(long = "seed", help = "Seed for the dice")]
+    seed: Option<u64>,
+    #[arg(
+        long = "roll-off",
+        help = "Decide the turn order by having each player roll a die"
+    )]
+    roll_off: bool,
+    #[arg(
+        long = "order",
+        help = "Turn order from round to round: fixed, rotate (first player moves to the end) or shuffle"
+    )]
+    order: Option<RoundOrder>,
+    #[arg(
+        long = "turn-order",
+        help = "Who goes first: sequential, random or handicap (biggest handicap first)"
+    )]
+    turn_order: Option<TurnOrder>,
+    #[arg(
+        long = "roll-seed",
+        help = "Derive each roll from this seed and its roll number instead of a running RNG"
+    )]
+    roll_seed: Option<u64>,
+    #[arg(
+        long = "auto-advance",
+        help = "Automatically make the move when it is the only safe one, like the first roll of a turn"
+    )]
+    auto_advance: bool,
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        help = "Explain why dice in a roll cannot be picked and whether to bank after each pick"
+    )]
+    verbose: bool,
+    #[arg(
+        long = "ascii",
+        alias = "ascii-dice",
+        help = "Draw the dice as boxes with pips"
+    )]
+    ascii: bool,
+    #[arg(
+        long = "bell",
+        help = "Ring the terminal bell and print a banner on a farkle"
+    )]
+    bell: bool,
+    #[arg(
+        long = "farkle-pause-ms",
+        help = "How long to pause after the --bell banner",
+        default_value_t = 800
+    )]
+    farkle_pause_ms: u64,
+    #[arg(
+        long = "bank-rounding",
+        help = "Round each bank to a multiple of this many points"
+    )]
+    bank_rounding: Option<u32>,
+    #[arg(
+        long = "rounding",
+        help = "Direction for --bank-rounding: nearest, up or down"
+    )]
+    rounding: Option<Rounding>,
+    #[arg(
+        long = "no-hot-dice",
+        help = "Make players bank once all six dice have scored instead of rolling them again"
+    )]
+    no_hot_dice: bool,
+    #[arg(
+        long = "roll-limit",
+        help = "Allow at most this many rolls per turn before the player must bank"
+    )]
+    roll_limit: Option<NonZeroU32>,
+    #[arg(
+        long = "sudden-death",
+        help = "Break a tie for first with extra one-turn rounds between the tied players"
+    )]
+    sudden_death: bool,
+    #[arg(
+        long = "hard-ones",
+        help = "Score 1s only as singles, so three 1s are worth 300 instead of 1000"
+    )]
+    hard_ones: bool,
+    #[arg(
+        long = "hard-fives",
+        help = "Score 5s only as singles, so three 5s are worth 150 instead of 500"
+    )]
+    hard_fives: bool,
+    #[arg(
+        long = "zilch-wipe",
+        help = "Rolling three 1s at once wipes the roller's score instead of scoring 1000"
+    )]
+    zilch_wipe: bool,
+    #[arg(
+        long = "handicap",
+        help = "Starting score for each player in order, e.g. \"0,2000\"",
+        value_delimiter = ','
+    )]
+    handicaps: Vec<u32>,
+    #[arg(
+        long = "team",
+        help = "Put players on a team that can pass the dice between them, e.g. \"Red:1,3\" (repeatable)"
+    )]
+    teams: Vec<Team>,
+    #[arg(
+        long = "team-scoring",
+        help = "How teams win: shared (the team total counts) or separate (each player's own score counts)"
+    )]
+    team_scoring: Option<TeamScoring>,
+    #[arg(
+        long = "p2p-host",
+        help = "Host a two-player game over UDP on this port, configured from FARKLE_* variables only",
+        conflicts_with = "p2p_connect"
+    )]
+    p2p_host: Option<u16>,
+    #[arg(
+        long = "p2p-connect",
+        help = "Join a two-player game hosted at this address, e.g. 192.168.1.5:4000"
+    )]
+    p2p_connect: Option<String>,
+    #[arg(
+        long = "input-script",
+        help = "Read player names and moves from this file, one per line, instead of the terminal"
+    )]
+    input_script: Option<String>,
+    #[command(flatten)]
+    scores: ScoresOutput,
+}
+
+#[derive(Debug, Args)]
+struct SimulateOptions {
+    #[arg(
+        short = 'n',
+        long = "turns",
+        help = "Number of turns to simulate",
+        default_value_t = 1000
+    )]
+    turns: usize,
+    #[arg(
+        long = "bot-strategy",
+        help = "Bot difficulty: easy, medium or hard",
+        default_value = "hard"
+    )]
+    difficulty: Difficulty,
+    #[arg(
+        long = "threshold",
+        help = "Bank once the hand is worth this many points, overriding the difficulty"
+    )]
+    threshold: Option<u32>,
+    #[arg(
+        long = "preset",
+        help = "Named ruleset: standard, hasbro, doubling, pot, blind or d8",
+        default_value = "standard",
+        value_parser = parse_preset
+    )]
+    preset: Ruleset,
+    #[arg(long = "seed", help = "Seed for the dice")]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct ReplayOptions {
+    #[arg(help = "Transcript file")]
+    transcript: String,
+    #[arg(
+        long = "replay-speed",
+        help = "Watch the game move by move, waiting this many milliseconds between moves"
+    )]
+    replay_speed: Option<u64>,
+    #[command(flatten)]
+    scores: ScoresOutput,
+}
+
+#[derive(Debug, Args)]
+struct AnalyzeOptions {
+    #[arg(
+        help = "Position such as \"[11]3556 hand:200\": the dice with kept ones in brackets, then the points in hand",
+        required = true
+    )]
+    position: Vec<String>,
+    #[arg(
+        long = "preset",
+        help = "Named ruleset: standard, hasbro, doubling, pot, blind or d8",
+        default_value = "standard",
+        value_parser = parse_preset
+    )]
+    preset: Ruleset,
+}
+
+#[derive(Debug, Args)]
+struct ScoresOutput {
+    #[arg(
+        long = "scores-file",
+        help = "Write the final scores to this file without asking"
+    )]
+    file: Option<String>,
+    #[arg(
+        long = "no-save-prompt",
+        help = "Print the final scores instead of asking for a file"
+    )]
+    no_prompt: bool,
+    #[arg(
+        long = "scores-append",
+        help = "Append to an existing scores file instead of picking a new numbered name"
+    )]
+    append: bool,
+}
+
+fn parse_preset(name: &str) -> Result<Ruleset, String> {
+    Ruleset::preset(name).ok_or_else(|| format!("Unknown preset {}", name))
+}
+
+struct Peer {
+    transport: UdpGameTransport,
+    local: usize,
+    lost: Option<io::Error>,
+}
+
+#[derive(Clone, Copy)]
+struct RollView<'a> {
+    verbose: bool,
+    display: &'a dyn GameDisplay,
+    bell: bool,
+    farkle_pause: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Action(GameAction),
+    Help,
+    View,
+    Pick,
+    Hand,
+    Stats,
+    Leaderboard,
+    Join(String),
+    Leave(usize),
+    Exit,
+}
+
+impl FromStr for Command {
+    type Err = UnknownAction;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "help" => Ok(Command::Help),
+            "view" => Ok(Command::View),
+            "pick" => Ok(Command::Pick),
+            "hand" => Ok(Command::Hand),
+            "stats" => Ok(Command::Stats),
+            "leaderboard" | "lb" => Ok(Command::Leaderboard),
+            "exit" => Ok(Command::Exit),
+            _ => match s.split_once(' ') {
+                Some(("join", name)) if !name.trim().is_empty() => {
+                    Ok(Command::Join(name.trim().to_string()))
+                }
+                Some(("leave", player)) => match player.trim().parse::<usize>() {
+                    Ok(player) if player > 0 => Ok(Command::Leave(player - 1)),
+                    _ => Err(UnknownAction),
+                },
+                _ => s.parse().map(Command::Action),
+            },
+        }
+    }
+}
+
+impl TryFrom<char> for Command {
+    type Error = UnknownAction;
+
+    fn try_from(key: char) -> Result<Self, Self::Error> {
+        match key {
+            '?' => Ok(Command::Help),
+            'v' => Ok(Command::View),
+            'p' => Ok(Command::Pick),
+            'h' => Ok(Command::Hand),
+            's' => Ok(Command::Stats),
+            'l' => Ok(Command::Leaderboard),
+            'e' => Ok(Command::Exit),
+            _ => GameAction::try_from(key).map(Command::Action),
+        }
+    }
+}
+
+fn print_help(con: &mut Console) {
+    outln!(
+        con,
+        concat!(
+            "help - show this help text\n",
+            "roll - roll die pool\n",
+            "view - view the current roll\n",
+            "pick - pick dice from the die pool\n",
+            "take <value> - pick all dice showing a value\n",
+            "unpick - reset the die selection\n",
+            "hand - show your current hand\n",
+            "stats - show the odds of rolling the remaining dice\n",
+            "leaderboard, lb - show the current standings\n",
+            "join <name> - add a player starting next round\n",
+            "leave <n> - remove player n, keeping their banked score\n",
+            "bank - bank all points currently in hand\n",
+            "forfeit - give up the turn and lose the points in hand\n",
+            "pass <n> - hand your points and remaining dice to teammate n\n",
+            "exit - immediately exit the game"
+        )
+    )
+}
+
+fn explain_unpickable(con: &mut Console, roll: &Roll, pickable: [bool; 6]) {
+    let counts = roll.count_values();
+    let stuck = roll
+        .dice()
+        .iter()
+        .zip(pickable)
+        .filter(|(die, can_pick)| !die.picked() && !can_pick)
+        .map(|(die, _)| die.value())
+        .unique();
+    for value in stuck {
+        let times = match counts[value - 1] {
+            1 => "once".to_string(),
+            2 => "twice".to_string(),
+            n => format!("{} times", n),
+        };
+        outln!(con, "{} appears only {}; you need three.", value, times);
+    }
+}
+
+fn view_roll(con: &mut Console, game: &Game, view: RollView) {
+    let roll = game.roll();
+    let pickable = (game.state() == GameState::Picking).then(|| roll.determine_pickable(None));
+    if view.verbose {
+        outln!(con, "Roll {}", game.rolls_this_turn());
+    }
+    outln!(con, "Your roll:");
+    view.display.show_roll(con, roll, pickable);
+    if let (Some(pickable), true) = (pickable, view.verbose) {
+        explain_unpickable(con, roll, pickable);
+    }
+    let kept = game.current_player().kept_values_this_turn();
+    if !kept.is_empty() {
+        outln!(con, "Kept: {}", kept.iter().join(" "));
+    }
+}
+
+fn print_leaderboard(con: &mut Console, game: &Game) {
+    outln!(con, "Standings:");
+    for (rank, standing) in game.standings().iter().enumerate() {
+        let marker = if standing.player == game.current_player_index() {
+            "->"
+        } else {
+            "  "
+        };
+        out!(
+            con,
+            "{} {}. {} - {}",
+            marker,
+            rank + 1,
+            game.players()[standing.player].name(),
+            standing.score
+        );
+        if standing.behind > 0 {
+            out!(con, " ({} behind)", standing.behind);
+        }
+        if game.has_left(standing.player) {
+            out!(con, " (left)");
+        }
+        outln!(con);
+    }
+    print_team_standings(con, game);
+}
+
+fn print_team_standings(con: &mut Console, game: &Game) {
+    if game.teams().is_empty() {
+        return;
+    }
+    outln!(con, "Teams ({} scoring):", game.config().team_scoring);
+    for (rank, (team, score)) in game.team_standings().into_iter().enumerate() {
+        let members = game.teams()[team]
+            .members
+            .iter()
+            .map(|&member| game.players()[member].name())
+            .join(", ");
+        outln!(
+            con,
+            "   {}. {} - {} ({})",
+            rank + 1,
+            game.teams()[team].name,
+            score,
+            members
+        );
+    }
+}
+
+fn recommend(con: &mut Console, game: &Game, view: RollView) {
+    if let (Some(recommendation), true) = (game.recommend(), view.verbose) {
+        outln!(con, "Recommendation: {}", recommendation);
+    }
+}
+
+fn warn_if_stuck(con: &mut Console, game: &Game) {
+    let roll = game.roll();
+    if game.roll_limit_reached() {
+        outln!(con, "{}", GameError::RollLimitReached);
+    } else if !roll.can_continue(&game.config().rules) {
+        if roll.is_exhausted() {
+            outln!(con, "{}", GameError::NoDiceLeft);
+        } else {
+            outln!(
+                con,
+                "No more scoring dice in this roll. Bank or roll again."
+            );
+        }
+    } else if game.state() == GameState::HotDice {
+        outln!(con, "Hot dice! Roll again or bank? (roll/bank)");
+    }
+}
+
+fn ring_farkle_bell(con: &mut Console, view: RollView) {
+    out!(con, "\x07");
+    outln!(con, "{}", farkle_banner());
+    con.flush().expect("Failed to write output");
+    if let Some(pause) = view.farkle_pause {
+        std::thread::sleep(pause);
+    }
+}
+
+fn print_roll_off(con: &mut Console, game: &Game, roll_off: &RollOff) {
+    let names = &game.config().player_names;
+    outln!(con, "Rolling for turn order...");
+    for (round, rolls) in roll_off.rounds().iter().enumerate() {
+        if round > 0 {
+            let tied = rolls.iter().map(|&(player, _)| &names[player]).join(", ");
+            outln!(con, "Tie between {}! Rolling again.", tied);
+        }
+        for &(player, value) in rolls {
+            outln!(con, "{} rolled {}.", names[player], value);
+        }
+    }
+    let order = game.players().iter().map(Player::name).join(", ");
+    outln!(con, "Turn order: {}", order);
+}
+
+fn show_score_wipe(con: &mut Console, game: &Game, player: usize, lost: u32) {
+    let name = game.players()[player].name();
+    outln!(con, "{}", "!".repeat(40));
+    outln!(
+        con,
+        "  THREE 1s! {}'s score is wiped out!",
+        name.to_uppercase()
+    );
+    outln!(con, "{}", "!".repeat(40));
+    outln!(con, "{} loses {} banked points and the turn.", name, lost);
+}
+
+fn apply(
+    game: &mut Game,
+    peer: Option<&mut Peer>,
+    action: GameAction,
+) -> Result<GameEvent, GameError> {
+    let event = game.apply(action)?;
+    if let Some(peer) = peer.filter(|peer| peer.lost.is_none()) {
+        if let Err(e) = peer.transport.send_move(action, game) {
+            peer.lost = Some(e);
+        }
+    }
+    Ok(event)
+}
+
+fn connection_lost(con: &mut Console, peer: Option<&mut Peer>) -> bool {
+    match peer.and_then(|peer| peer.lost.take()) {
+        Some(e) => {
+            outln!(con, "Connection lost: {}", e);
+            true
+        }
+        None => false,
+    }
+}
+
+fn play_game(
+    con: &mut Console,
+    prompter: &mut dyn Prompter,
+    game: &mut Game,
+    auto_advance: bool,
+    view: RollView,
+    mut peer: Option<&mut Peer>,
+    autosave: Option<&Mutex<Option<Game>>>,
+) -> bool {
+    let mut round = 0;
+    while !game.is_over() {
+        if game.current_turn() != round {
+            round = game.current_turn();
+            if let Some(overtime) = game.overtime_round() {
+                let names = game
+                    .contenders()
+                    .iter()
+                    .map(|&player| game.players()[player].name())
+                    .join(", ");
+                outln!(con, "Sudden death round {}: {}", overtime, names);
+            } else if game.config().round_order != RoundOrder::Fixed {
+                let order = game
+                    .seating()
+                    .iter()
+                    .filter(|&&player| !game.has_left(player))
+                    .map(|&player| game.players()[player].name())
+                    .join(", ");
+                outln!(con, "Round {} order: {}", round, order);
+            }
+        }
+        let player = game.current_player();
+        con.stamp().expect("Failed to write output");
+        view.display
+            .show_player_turn(con, player, game.current_turn(), game.total_turns());
+        if let Some(pot) = game.pot() {
+            outln!(con, "The pot is at {} points.", pot);
+        }
+        while game.state() != GameState::TurnEnded {
+            let player_no = game.current_player_index();
+            if let Some(autosave) = autosave {
+                *autosave.lock().unwrap() = Some(game.clone());
+            }
+            if connection_lost(con, peer.as_deref_mut()) {
+                return true;
+            }
+            if let Some(peer) = peer.as_deref_mut().filter(|peer| peer.local != player_no) {
+                match peer.transport.recv_move(game) {
+                    Ok(action) => {
+                        let text = udp::action_text(action).to_lowercase();
+                        outln!(con, "{}> {}", player_no, text);
+                        if action == GameAction::Roll {
+                            view_roll(con, game, view);
+                        }
+                        game.drain_events();
+                    }
+                    Err(e) => {
+                        outln!(con, "Connection lost: {}", e);
+                        return true;
+                    }
+                }
+                continue;
+            }
+            let safe_moves: Vec<_> = game
+                .legal_moves()
+                .into_iter()
+                .filter(|mov| !mov.is_destructive())
+                .collect();
+            let cmd = if auto_advance && safe_moves == [GameAction::Roll] {
+                outln!(con, "{}> roll (auto)", player_no);
+                Some(Command::Action(GameAction::Roll))
+            } else {
+                prompter.get_move(con, player_no)
+            };
+            match cmd {
+                Some(cmd) => match cmd {
+                    Command::Action(GameAction::Roll) => {
+                        match apply(game, peer.as_deref_mut(), GameAction::Roll) {
+                            Ok(GameEvent::Rolled { roll_type, value }) => {
+                                view_roll(con, game, view);
+                                let mut wiped = None;
+                                for event in game.drain_events() {
+                                    match event {
+                                        GameEvent::PotClaimed(pot) => {
+                                            outln!(con, "Claimed the pot of {} points!", pot)
+                                        }
+                                        GameEvent::Special(special) => outln!(con, "{}", special),
+                                        GameEvent::Farkled { lost } if lost > 0 => {
+                                            outln!(con, "You lose {} unbanked points.", lost)
+                                        }
+                                        GameEvent::ScoreWiped { player, lost } => {
+                                            wiped = Some((player, lost))
+                                        }
+                                        _ => (),
+                                    }
+                                }
+                                if let Some((player, lost)) = wiped {
+                                    show_score_wipe(con, game, player, lost);
+                                } else if view.bell && roll_type == RollType::Farkle {
+                                    ring_farkle_bell(con, view);
+                                } else {
+                                    view.display.show_scoring_message(con, roll_type, value);
+                                }
+                                if wiped.is_none()
+                                    && matches!(
+                                        roll_type,
+                                        RollType::Straight | RollType::TriplePair
+                                    )
+                                {
+                                    warn_if_stuck(con, game);
+                                    recommend(con, game, view);
+                                }
+                            }
+                            Err(e @ (GameError::RollLimitReached | GameError::NoDiceLeft)) => {
+                                outln!(con, "{}", e)
+                            }
+                            _ => outln!(
+                                con,
+                                "You have already rolled. Use 'pick' to pick from the die pool."
+                            ),
+                        }
+                    }
+                    Command::Action(GameAction::Bank) => {
+                        match apply(game, peer.as_deref_mut(), GameAction::Bank) {
+                            Ok(GameEvent::Banked(points)) => {
+                                outln!(con, "Banked {} points.", points)
+                            }
+                            Err(e @ GameError::InvalidSelection(_)) => outln!(con, "{}", e),
+                            _ => outln!(con, "You must pick from the die pool before banking."),
+                        }
+                    }
+                    Command::Action(GameAction::ForfeitTurn) => {
+                        let prompt = format!(
+                            "Give up this turn and lose {} points in hand?",
+                            game.current_player().hand_value()
+                        );
+                        if !prompter.confirm(con, &prompt) {
+                            continue;
+                        }
+                        match apply(game, peer.as_deref_mut(), GameAction::ForfeitTurn) {
+                            Ok(_) => outln!(con, "Gave up the turn."),
+                            Err(e) => outln!(con, "{}", e),
+                        }
+                    }
+                    Command::View => view_roll(con, game, view),
+                    Command::Pick => match game.state() {
+                        GameState::Rolling => outln!(
+                            con,
+                            "You have already picked dice. Use 'unpick' to reset your selection."
+                        ),
+                        GameState::HotDice => warn_if_stuck(con, game),
+                        GameState::FirstRoll => {
+                            outln!(con, "You have not rolled yet. Use 'roll' to roll.")
+                        }
+                        _ => {
+                            outln!(con, "Enter a die index to toggle selecting. Any invalid input to stop picking.");
+                            while let Some(idx) = prompter.get_pick(con) {
+                                match apply(
+                                    game,
+                                    peer.as_deref_mut(),
+                                    GameAction::ToggleDie(idx - 1),
+                                ) {
+                                    Ok(GameEvent::Toggled(ToggleResult::Picked)) => {
+                                        outln!(con, "Picked die {}.", idx)
+                                    }
+                                    Ok(GameEvent::Toggled(ToggleResult::Unpicked)) => {
+                                        outln!(con, "Unpicked die {}.", idx)
+                                    }
+                                    Ok(GameEvent::Toggled(ToggleResult::NotUnpickable)) => {
+                                        outln!(con, "You cannot unpick this die.")
+                                    }
+                                    _ => outln!(con, "You cannot pick this die."),
+                                }
+                            }
+                            match apply(game, peer.as_deref_mut(), GameAction::ConfirmSelection) {
+                                Ok(GameEvent::Selected(value)) => {
+                                    outln!(con, "Selected {} points' worth of dice.", value);
+                                    warn_if_stuck(con, game);
+                                    recommend(con, game, view);
+                                }
+                                Err(e) => outln!(con, "{}", e),
+                                _ => (),
+                            }
+                        }
+                    },
+                    Command::Action(GameAction::TakeValue(value)) => {
+                        match apply(game, peer.as_deref_mut(), GameAction::TakeValue(value)) {
+                            Ok(GameEvent::Taken(0)) => {
+                                outln!(con, "There are no pickable dice showing {}.", value)
+                            }
+                            Ok(_) => {
+                                match apply(game, peer.as_deref_mut(), GameAction::ConfirmSelection)
+                                {
+                                    Ok(GameEvent::Selected(value)) => {
+                                        outln!(con, "Selected {} points' worth of dice.", value);
+                                        warn_if_stuck(con, game);
+                                        recommend(con, game, view);
+                                    }
+                                    Err(e) => outln!(con, "{}", e),
+                                    _ => (),
+                                }
+                            }
+                            Err(e @ GameError::NoSuchFace(_)) => outln!(con, "{}", e),
+                            Err(_) => outln!(con, "You can only take dice after rolling."),
+                        }
+                    }
+                    Command::Help => print_help(con),
+                    Command::Hand => {
+                        let player = game.current_player();
+                        outln!(con, "Your selections:");
+                        for sel in player.selections() {
+                            outln!(con, "{}", sel);
+                        }
+                        outln!(con, "{} points in hand.", player.hand_value());
+                    }
+                    Command::Stats => {
+                        let roll = game.roll();
+                        let rules = &game.config().rules;
+                        let hand = game.current_player().hand_value();
+                        outln!(con, "{} dice left to roll.", roll.remaining_count());
+                        outln!(
+                            con,
+                            "Chance of a farkle: {:.1}%",
+                            roll.farkle_probability(rules) * 100.
+                        );
+                        outln!(
+                            con,
+                            "Expected value of rolling with {} points in hand: {:+.0} points",
+                            hand,
+                            roll.expected_reroll_value(hand, rules)
+                        );
+                        if let Some(recommendation) = game.recommend() {
+                            outln!(con, "Recommendation: {}", recommendation);
+                        }
+                    }
+                    Command::Leaderboard => print_leaderboard(con, game),
+                    Command::Action(GameAction::UndoSelection) => {
+                        if apply(game, peer.as_deref_mut(), GameAction::UndoSelection).is_err() {
+                            outln!(con, "Cannot unpick dice at this time.");
+                            continue;
+                        }
+                        outln!(con, "Reset die selection.");
+                        view_roll(con, game, view);
+                    }
+                    Command::Join(_) | Command::Leave(_) if peer.is_some() => {
+                        outln!(con, "Players cannot join or leave a network game.")
+                    }
+                    Command::Join(name) => match game.add_player(Player::new(name.clone())) {
+                        Ok(()) => outln!(con, "{} will join next round.", name),
+                        Err(e) => outln!(con, "{}", e),
+                    },
+                    Command::Leave(idx) => match game.remove_player(idx) {
+                        Ok(()) => outln!(
+                            con,
+                            "{} left the game with {} points.",
+                            game.players()[idx].name(),
+                            game.players()[idx].score()
+                        ),
+                        Err(e) => outln!(con, "{}", e),
+                    },
+                    Command::Action(GameAction::PassToTeammate(idx)) => {
+                        match apply(game, peer.as_deref_mut(), GameAction::PassToTeammate(idx)) {
+                            Ok(_) => {
+                                let player = game.current_player();
+                                outln!(
+                                    con,
+                                    "Passed the dice to {}, who has {} points in hand.",
+                                    player.name(),
+                                    player.hand_value()
+                                );
+                                view_roll(con, game, view);
+                            }
+                            Err(
+                                e @ (GameError::NotATeammate(_)
+                                | GameError::NoSuchPlayer(_)
+                                | GameError::AlreadyPassed),
+                            ) => {
+                                outln!(con, "{}", e)
+                            }
+                            Err(_) => outln!(con, "Set aside some scoring dice before passing."),
+                        }
+                    }
+                    Command::Exit => return true,
+                    Command::Action(_) => panic!("Unreachable state"),
+                },
+                None => {
+                    outln!(
+                        con,
+                        "Invalid command. Type 'help' to see a list of commands."
+                    )
+                }
+            }
+        }
+        if connection_lost(con, peer.as_deref_mut()) {
+            return true;
+        }
+        if let Some(summary) = game.last_turn_summary() {
+            outln!(con, "{}", summary);
+        }
+        game.next_turn();
+    }
+    view.display.show_game_over(con, &game.summary());
+    print_team_standings(con, game);
+    false
+}
+
+fn open_scores_file(path: &str, append: bool) -> io::Result<(File, PathBuf)> {
+    let mut target = PathBuf::from(path);
+    if append {
+        let file = OpenOptions::new().create(true).append(true).open(&target)?;
+        return Ok((file, target));
+    }
+    let original = Path::new(path);
+    let stem = original.file_stem().unwrap_or_default().to_string_lossy();
+    let mut suffix = 1;
+    while target.exists() {
+        let name = match original.extension() {
+            Some(ext) => format!("{}.{}.{}", stem, suffix, ext.to_string_lossy()),
+            None => format!("{}.{}", stem, suffix),
+        };
+        target = original.with_file_name(name);
+        suffix += 1;
+    }
+    Ok((File::create(&target)?, target))
+}
+
+fn save_scores(
+    con: &mut Console,
+    prompter: &mut dyn Prompter,
+    game: &Game,
+    start: chrono::DateTime<Local>,
+    output: &ScoresOutput,
+) -> io::Result<()> {
+    let filename = match &output.file {
+        Some(path) => path.clone(),
+        None if !output.no_prompt && prompter.is_interactive() => prompter.get_filename(con),
+        None => String::new(),
+    };
+
+    let mut players: Vec<_> = (0..game.players().len()).collect();
+    players.sort_by_key(|&idx| &game.players()[idx]);
+
+    fn dump(
+        out: &mut impl Write,
+        start: &chrono::DateTime<Local>,
+        game: &Game,
+        players: &[usize],
+    ) -> io::Result<()> {
+        let now = Local::now();
+        writeln!(
+            out,
+            "{}-{} ({} turns)",
+            start.format("%F: %T"),
+            now.format("%T"),
+            game.total_turns()
+        )?;
+        for &idx in players {
+            let player = &game.players()[idx];
+            if game.roll_off().is_some() {
+                writeln!(
+                    out,
+                    "{} - {} (entry #{})",
+                    player.name(),
+                    player.score(),
+                    game.entry_position(idx) + 1
+                )?;
+            } else {
+                writeln!(out, "{} - {}", player.name(), player.score())?;
+            }
+        }
+        if game.winners().is_empty() {
+            writeln!(out, "No points scored — no winner")?;
+        }
+
+        Ok(())
+    }
+
+    if filename.is_empty() {
+        dump(con, &start, game, &players)?;
+    } else {
+        let (mut file, path) = open_scores_file(&filename, output.append)?;
+        dump(&mut file, &start, game, &players)?;
+        outln!(con, "Scores written to {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn play(options: PlayOptions) -> io::Result<()> {
+    let PlayOptions {
+        player_count,
+        turn_count,
+        stats_report,
+        pot,
+        faces,
+        preset,
+        session_log,
+        seed,
+        roll_off,
+        order,
+        turn_order,
+        roll_seed,
+        auto_advance,
+        verbose,
+        ascii,
+        bell,
+        farkle_pause_ms,
+        bank_rounding,
+        rounding,
+        no_hot_dice,
+        roll_limit,
+        sudden_death,
+        hard_ones,
+        hard_fives,
+        zilch_wipe,
+        handicaps,
+        teams,
+        team_scoring,
+        p2p_host,
+        p2p_connect,
+        input_script,
+        scores,
+    } = options;
+    let mut con = Console::new(session_log.as_deref())?;
+
+    let env = match GameConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            outln!(con, "{}", e);
+            return Ok(());
+        }
+    };
+    let networked = p2p_host.is_some() || p2p_connect.is_some();
+    let player_count = player_count.unwrap_or(env.player_names.len());
+    let preset = preset.unwrap_or_else(|| env.rules.clone());
+
+    let mut player_names = Vec::with_capacity(player_count);
+
+    let mut terminal = TerminalPrompter::new();
+    let one_key = terminal.is_one_key();
+    let mut scripted = match &input_script {
+        Some(path) => Some(ScriptedPrompter::from_script(&std::fs::read_to_string(
+            path,
+        )?)),
+        None => None,
+    };
+    let prompter: &mut dyn Prompter = match &mut scripted {
+        Some(scripted) => scripted,
+        None => &mut terminal,
+    };
+
+    let resumed = if networked || input_script.is_some() {
+        None
+    } else {
+        resume_autosave(&mut con, prompter)
+    };
+
+    if networked {
+        player_names.push(prompter.get_name(&mut con, None));
+    } else if resumed.is_none() {
+        for i in 0..player_count {
+            player_names.push(prompter.get_name(&mut con, Some(i)));
+        }
+    }
+
+    let config = GameConfig {
+        player_names,
+        turns: turn_count.unwrap_or(env.turns),
+        seed: seed.or(env.seed),
+        rules: Ruleset {
+            pot: pot.map(PotRule::with_contribution).or(preset.pot),
+            faces: faces.unwrap_or(preset.faces.clone()),
+            bank_rounding: bank_rounding.or(preset.bank_rounding),
+            rounding: rounding.unwrap_or(preset.rounding),
+            hot_dice: preset.hot_dice && !no_hot_dice,
+            max_rolls_per_turn: roll_limit.or(preset.max_rolls_per_turn),
+            sudden_death: sudden_death || preset.sudden_death,
+            ones_set_bonus: preset.ones_set_bonus && !hard_ones,
+            fives_set_bonus: preset.fives_set_bonus && !hard_fives,
+            zilch_wipe: zilch_wipe || preset.zilch_wipe,
+            ..preset
+        },
+        roll_off: roll_off || env.roll_off,
+        round_order: order.unwrap_or(env.round_order),
+        turn_order: turn_order.unwrap_or(env.turn_order),
+        teams,
+        team_scoring: team_scoring.unwrap_or(env.team_scoring),
+        roll_seed: roll_seed.or(env.roll_seed),
+        handicaps: if handicaps.is_empty() {
+            env.handicaps.clone()
+        } else {
+            handicaps
+        },
+    };
+
+    let (mut game, mut peer) = if let Some(game) = resumed {
+        (game, None)
+    } else if let Some(port) = p2p_host {
+        outln!(con, "Waiting for the other player on port {}...", port);
+        // A hosted game takes its settings from the environment alone, like any server deployment
+        let config = GameConfig {
+            player_names: config.player_names,
+            ..env
+        };
+        let name = config.player_names[0].clone();
+        let (transport, game) = UdpGameTransport::host(port, &name, config)?;
+        let local = (0..2)
+            .find(|&idx| game.entry_position(idx) == 0)
+            .unwrap_or(0);
+        (
+            game,
+            Some(Peer {
+                transport,
+                local,
+                lost: None,
+            }),
+        )
+    } else if let Some(addr) = p2p_connect {
+        outln!(con, "Connecting to {}...", addr);
+        let (transport, game) = UdpGameTransport::connect(addr.as_str(), &config.player_names[0])?;
+        let local = (0..2)
+            .find(|&idx| game.entry_position(idx) == 1)
+            .unwrap_or(1);
+        (
+            game,
+            Some(Peer {
+                transport,
+                local,
+                lost: None,
+            }),
+        )
+    } else {
+        (Game::new(config), None)
+    };
+    if peer.is_some() {
+        let names = game.players().iter().map(Player::name).join(" vs ");
+        outln!(con, "Connected: {}", names);
+    }
+
+    con.log(&game.transcript())?;
+
+    if let Some(result) = game.roll_off() {
+        print_roll_off(&mut con, &game, result);
+    } else if game.players().len() > 1 {
+        let order = game.players().iter().map(Player::name).join(", ");
+        outln!(con, "Turn order: {}", order);
+    }
+
+    let stdin = 0;
+    let old = (one_key && input_script.is_none()).then(|| Termios::from_fd(stdin).unwrap());
+
+    if let Some(old) = old {
+        let mut new = old;
+        new.c_lflag &= !ICANON;
+        tcsetattr(stdin, TCSANOW, &new).unwrap();
+    }
+
+    let start = Local::now();
+
+    let shared = Arc::new(Mutex::new(None));
+    let autosaving = cfg!(feature = "persistence") && peer.is_none();
+    #[cfg(feature = "persistence")]
+    let autosave = autosaving.then(|| AutoSaveThread::start(shared.clone()));
+
+    let mut history = GameHistory::default();
+    let exited = play_game(
+        &mut con,
+        prompter,
+        &mut game,
+        auto_advance,
+        RollView {
+            verbose,
+            display: if ascii {
+                &AsciiArtDisplay
+            } else {
+                &PlainTextDisplay
+            },
+            bell: bell && io::stdout().is_terminal(),
+            farkle_pause: (input_script.is_none() && io::stdin().is_terminal())
+                .then(|| Duration::from_millis(farkle_pause_ms)),
+        },
+        peer.as_mut(),
+        autosaving.then_some(&*shared),
+    );
+    if let Some(peer) = &peer {
+        peer.transport.close();
+    }
+    #[cfg(feature = "persistence")]
+    if let Some(autosave) = autosave {
+        autosave.stop();
+        if game.is_over() {
+            let _ = AutoSave::remove();
+        }
+    }
+    history.push(game.summary());
+    #[cfg(feature = "persistence")]
+    if let Some(path) = GameHistory::default_path() {
+        if let Err(e) = history.save(&path) {
+            outln!(con, "Failed to save session history: {}", e);
+        }
+    }
+
+    if let Some(old) = old {
+        tcsetattr(stdin, TCSANOW, &old).unwrap();
+    }
+
+    if verbose {
+        outln!(con, "{}", game.roll_stats());
+    }
+
+    if stats_report {
+        for player in game.players() {
+            outln!(con, "{}", player.stats().to_report(player.name()));
+        }
+    }
+
+    #[cfg(feature = "turn-timer")]
+    for player in game.players() {
+        if let Some(average) = player.average_turn_time() {
+            outln!(con, "{}: avg {}s/turn", player.name(), average.as_secs());
+        }
+    }
+
+    if exited {
+        outln!(con, "{}", history.to_report());
+    }
+
+    save_scores(&mut con, prompter, &game, start, &scores)?;
+
+    Ok(())
+}
+
+fn watch(
+    con: &mut Console,
+    game: &mut Game,
+    playback: &mut Playback,
+    delay: Duration,
+) -> Result<(), TranscriptError> {
+    let mut turn = None;
+    while !playback.is_finished() {
+        let Some(line) = playback.step(game)? else {
+            break;
+        };
+        let current = (game.current_player_index(), game.current_turn());
+        if turn != Some(current) {
+            turn = Some(current);
+            PlainTextDisplay.show_player_turn(
+                con,
+                game.current_player(),
+                game.current_turn(),
+                game.total_turns(),
+            );
+        }
+        outln!(
+            con,
+            "{}> {}",
+            game.current_player_index(),
+            line.to_lowercase()
+        );
+        if line.starts_with("ROLL") {
+            outln!(con, "{}", PlainTextDisplay.roll_text(game.roll(), None));
+        }
+        std::thread::sleep(delay);
+    }
+    playback.finish(game)
+}
+
+fn replay(options: ReplayOptions) -> io::Result<()> {
+    let mut con = Console::new(None)?;
+    let moves = std::fs::read_to_string(&options.transcript)?;
+    let result = Playback::start(&moves).and_then(|(mut game, mut playback)| {
+        match options.replay_speed {
+            Some(ms) => watch(
+                &mut con,
+                &mut game,
+                &mut playback,
+                Duration::from_millis(ms),
+            )?,
+            None => playback.finish(&mut game)?,
+        }
+        Ok(game)
+    });
+    match result {
+        Ok(game) => {
+            let summary = game.summary();
+            outln!(con, "Replayed {} turns", summary.turns());
+            for (name, score) in summary.standings() {
+                outln!(con, "{} - {}", name, score);
+            }
+            save_scores(
+                &mut con,
+                &mut TerminalPrompter::new(),
+                &game,
+                Local::now(),
+                &options.scores,
+            )
+        }
+        Err(e) => {
+            outln!(con, "Failed to replay transcript: {}", e);
+            Ok(())
+        }
+    }
+}
+
+fn simulate(options: SimulateOptions) -> io::Result<()> {
+    let mut con = Console::new(None)?;
+    let mut strategy = options.difficulty.strategy();
+    if let Some(threshold) = options.threshold {
+        strategy.bank_threshold = threshold;
+    }
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let results = simulate_n_turns(
+        options.turns,
+        &strategy,
+        &Hand::default(),
+        &options.preset,
+        &mut rng,
+    );
+    let turns = results.len().max(1) as f64;
+    let banked: u32 = results.iter().map(|result| result.banked).sum();
+    let farkles = results.iter().filter(|result| result.farkled).count();
+    let rolls: u32 = results.iter().map(|result| result.rolls_taken).sum();
+    outln!(
+        con,
+        "Simulated {} turns with the {} bot (banks at {} points, seed {})",
+        results.len(),
+        options.difficulty,
+        strategy.bank_threshold,
+        seed
+    );
+    outln!(con, "Average banked: {:.1}", banked as f64 / turns);
+    outln!(con, "Farkle rate: {:.1}%", farkles as f64 / turns * 100.);
+    outln!(con, "Average rolls per turn: {:.2}", rolls as f64 / turns);
+    Ok(())
+}
+
+fn parse_position(tokens: &[&str], rules: &Ruleset) -> Result<(Roll, u32), (usize, String)> {
+    let mut roll = None;
+    let mut hand = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        let fail = |reason: String| (idx, reason);
+        match token.split_once(':') {
+            Some(("hand", points)) => {
+                hand = points
+                    .parse()
+                    .map_err(|_| fail(format!("Bad hand value \"{}\"", points)))?
+            }
+            Some(_) => return Err(fail("Expected hand:<points>".to_string())),
+            None if roll.is_some() => return Err(fail("The roll was already given".to_string())),
+            None => {
+                let parsed = token
+                    .parse::<RollNotation>()
+                    .and_then(|notation| notation.to_roll(rules.faces.clone()));
+                roll = Some(parsed.map_err(|e| fail(e.to_string()))?);
+            }
+        }
+    }
+    roll.map(|roll| (roll, hand))
+        .ok_or((tokens.len(), "Expected a roll such as [11]3556".to_string()))
+}
+
+fn analyze(options: AnalyzeOptions) -> io::Result<()> {
+    let mut con = Console::new(None)?;
+    let tokens: Vec<&str> = options
+        .position
+        .iter()
+        .flat_map(|arg| arg.split_whitespace())
+        .collect();
+    let (roll, hand) = match parse_position(&tokens, &options.preset) {
+        Ok(position) => position,
+        Err((idx, reason)) => {
+            let offset: usize = tokens[..idx].iter().map(|token| token.len() + 1).sum();
+            let width = tokens.get(idx).map_or(1, |token| token.len());
+            outln!(con, "{}", tokens.join(" "));
+            outln!(con, "{}{}", " ".repeat(offset), "^".repeat(width));
+            outln!(con, "{}", reason);
+            return Ok(());
+        }
+    };
+
+    let analysis = roll.analyze(hand, &options.preset);
+    outln!(con, "{}", PlainTextDisplay.roll_text(&roll, None));
+    outln!(con, "Points in hand: {}", hand);
+    let best = match analysis.best() {
+        Some(best) => best,
+        None => {
+            outln!(con, "Farkle! None of the dice score.");
+            return Ok(());
+        }
+    };
+    let dice = |n: usize| match n {
+        1 => "1 die".to_string(),
+        n => format!("{} dice", n),
+    };
+    outln!(con, "Legal selections:");
+    for option in &analysis.options {
+        outln!(
+            con,
+            "  {} - {} points, {} left",
+            option.values.iter().join(" "),
+            option.value,
+            dice(option.dice_left)
+        );
+    }
+    outln!(
+        con,
+        "Best pick: {} ({} points)",
+        best.values.iter().join(" "),
+        best.value
+    );
+    outln!(con, "Farkle chance on the next roll:");
+    for &(left, chance) in &analysis.farkle_chances {
+        let rolling = match left {
+            0 => "6 fresh dice".to_string(),
+            n => dice(n),
+        };
+        outln!(con, "  Rolling {}: {:.1}%", rolling, chance * 100.);
+    }
+    if let Some(recommendation) = analysis.recommendation() {
+        outln!(con, "Recommendation: {}", recommendation);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "persistence")]
+fn resume_autosave(con: &mut Console, prompter: &mut dyn Prompter) -> Option<Game> {
+    let save = AutoSave::load().ok()?;
+    let prompt = format!("Resume the unfinished game saved at {}?", save.saved_at);
+    if !prompter.confirm(con, &prompt) {
+        let _ = AutoSave::remove();
+        return None;
+    }
+    let resumed = Playback::start(&save.transcript).and_then(|(mut game, mut playback)| {
+        playback.finish(&mut game)?;
+        Ok(game)
+    });
+    match resumed {
+        Ok(game) => Some(game),
+        Err(e) => {
+            outln!(con, "Could not resume the saved game: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "persistence"))]
+fn resume_autosave(_con: &mut Console, _prompter: &mut dyn Prompter) -> Option<Game> {
+    None
+}
+
+#[cfg(feature = "persistence")]
+fn show_stats() -> io::Result<()> {
+    let mut con = Console::new(None)?;
+    let history = match GameHistory::default_path() {
+        Some(path) if path.exists() => GameHistory::load(&path)?,
+        _ => GameHistory::default(),
+    };
+    if history.is_empty() {
+        outln!(con, "No games recorded yet.");
+    } else {
+        outln!(con, "{}", history.to_report());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "persistence"))]
+fn show_stats() -> io::Result<()> {
+    let mut con = Console::new(None)?;
+    outln!(con, "This build does not save session history.");
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    match Options::parse().command {
+        Subcommand::Play(options) => play(*options),
+        Subcommand::Simulate(options) => simulate(options),
+        Subcommand::Replay(options) => replay(options),
+        Subcommand::Stats => show_stats(),
+        Subcommand::Analyze(options) => analyze(options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+    fn play_options(args: &[&str]) -> Result<PlayOptions, clap::Error> {
+        let args = ["rsfarkle", "play"].iter().chain(args);
+        match Options::try_parse_from(args)?.command {
+            Subcommand::Play(options) => Ok(*options),
+            command => panic!("Expected to play, got {:?}", command),
+        }
+    }
+
+    fn parse_error(args: &[&str]) -> ErrorKind {
+        play_options(args).unwrap_err().kind()
+    }
+
+    #[test]
+    fn play_flags_parse() {
+        let options = play_options(&["-p", "2", "-t", "5"]).unwrap();
+        assert_eq!(
+            (options.player_count, options.turn_count),
+            (Some(2), Some(5))
+        );
+        assert!(options.preset.is_none() && !options.no_hot_dice && !options.sudden_death);
+        assert!(options.handicaps.is_empty());
+
+        let options = play_options(&[
+            "--preset",
+            "zilch",
+            "--hard-ones",
+            "--no-hot-dice",
+            "--bank-rounding",
+            "50",
+            "--rounding",
+            "down",
+            "--handicap",
+            "0,500",
+            "--team",
+            "Red:1,3",
+            "--team-scoring",
+            "separate",
+            "--ascii-dice",
+        ])
+        .unwrap();
+        assert!(options.preset.unwrap().zilch_wipe);
+        assert!(options.hard_ones && options.no_hot_dice && options.ascii);
+        assert_eq!(
+            (options.bank_rounding, options.rounding),
+            (Some(50), Some(Rounding::Down))
+        );
+        assert_eq!(options.handicaps, [0, 500]);
+        assert_eq!(options.teams, [Team::new("Red", vec![0, 2])]);
+        assert_eq!(options.team_scoring, Some(TeamScoring::Separate));
+        assert_eq!(options.farkle_pause_ms, 800);
+
+        let options = play_options(&["--p2p-host", "4000", "--scores-file", "out.txt"]).unwrap();
+        assert_eq!(options.p2p_host, Some(4000));
+        assert_eq!(options.scores.file.as_deref(), Some("out.txt"));
+    }
+
+    #[test]
+    fn invalid_play_flags_fail() {
+        assert_eq!(
+            parse_error(&["--p2p-host", "4000", "--p2p-connect", "localhost:4000"]),
+            ErrorKind::ArgumentConflict
+        );
+        assert_eq!(parse_error(&["-p", "two"]), ErrorKind::ValueValidation);
+        assert_eq!(parse_error(&["-p", "0"]), ErrorKind::ValueValidation);
+        assert_eq!(
+            parse_error(&["--roll-limit", "0"]),
+            ErrorKind::ValueValidation
+        );
+        assert_eq!(
+            parse_error(&["--preset", "chaos"]),
+            ErrorKind::ValueValidation
+        );
+        assert_eq!(
+            parse_error(&["--rounding", "sideways"]),
+            ErrorKind::ValueValidation
+        );
+        assert_eq!(
+            parse_error(&["--p2p-host", "70000"]),
+            ErrorKind::ValueValidation
+        );
+        assert_eq!(parse_error(&["--team", "Red"]), ErrorKind::ValueValidation);
+        assert_eq!(parse_error(&["--turns"]), ErrorKind::InvalidValue);
+        assert_eq!(
+            parse_error(&["--players-count", "2"]),
+            ErrorKind::UnknownArgument
+        );
+    }
+
+    #[test]
+    fn subcommands_parse() {
+        let options = Options::try_parse_from(["rsfarkle", "simulate", "-n", "50", "--seed", "3"]);
+        let Subcommand::Simulate(options) = options.unwrap().command else {
+            panic!("Expected to simulate");
+        };
+        assert_eq!((options.turns, options.seed), (50, Some(3)));
+        assert_eq!(options.difficulty, Difficulty::Hard);
+
+        let error = Options::try_parse_from(["rsfarkle", "analyze"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+        let error = Options::try_parse_from(["rsfarkle"]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+        );
+    }
+
+    #[test]
+    fn analyze_positions_point_at_the_bad_token() {
+        let rules = Ruleset::default();
+        let (roll, hand) = parse_position(&["[11]3556", "hand:200"], &rules).unwrap();
+        assert_eq!((roll.picked_values(), hand), (vec![1, 1], 200));
+
+        let bad_token = |tokens: &[&str]| parse_position(tokens, &rules).unwrap_err().0;
+        assert_eq!(bad_token(&["[11]3556", "hand:lots"]), 1);
+        assert_eq!(bad_token(&["hand:200", "113559"]), 1);
+        assert_eq!(bad_token(&["113556", "155234"]), 1);
+        assert_eq!(bad_token(&["points:200", "113556"]), 0);
+        assert_eq!(
+            parse_position(&["hand:200"], &rules).unwrap_err(),
+            (1, "Expected a roll such as [11]3556".to_string())
+        );
+    }
+
+    fn play_script(script: &str) -> (Game, String, bool) {
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 2,
+            seed: Some(5),
+            ..Default::default()
+        });
+        let mut con = Console::capturing();
+        let mut prompter = ScriptedPrompter::from_script(script);
+        let view = RollView {
+            verbose: false,
+            display: &PlainTextDisplay,
+            bell: false,
+            farkle_pause: None,
+        };
+        let exited = play_game(&mut con, &mut prompter, &mut game, false, view, None, None);
+        (game, con.captured(), exited)
+    }
+
+    #[cfg(not(feature = "debug-dice"))]
+    #[test]
+    fn scripted_games_play_to_the_end() {
+        // One line per turn: Ann, Bob, Ann, Bob
+        let script = [
+            "roll\npick\n2\n4\ndone\nbank",
+            "roll\ntake 5\nbank",
+            "roll\ntake 1\nbank",
+            "roll\ntake 1\nbank",
+        ]
+        .join("\n");
+        let (game, output, exited) = play_script(&script);
+        assert!(!exited && game.is_over());
+        let scores: Vec<_> = game.players().iter().map(Player::score).collect();
+        assert_eq!(scores, [350, 1500]);
+        assert!(output.contains("Picking> 2\nPicked die 2.\nPicking> 4\nPicked die 4."));
+        assert!(output.contains("0> bank\nBanked 150 points."));
+        assert!(output.contains("1> bank\nBanked 1000 points."));
+        assert!(output.ends_with("Game over\n"), "{}", output);
+    }
+
+    #[test]
+    fn scripted_games_stop_at_exit() {
+        let (game, output, exited) = play_script("roll\nexit\nbank");
+        assert!(exited && !game.is_over());
+        assert_eq!(game.rolls_this_turn(), 1);
+        assert!(output.ends_with("0> exit\n"));
+
+        let (game, _, exited) = play_script("roll");
+        assert!(exited && !game.is_over());
+    }
+
+    #[test]
+    fn scores_print_without_a_terminal() {
+        let (game, _, _) = play_script("");
+        let output = ScoresOutput {
+            file: None,
+            no_prompt: false,
+            append: false,
+        };
+        let mut con = Console::capturing();
+        let mut prompter = ScriptedPrompter::from_script("");
+        save_scores(&mut con, &mut prompter, &game, Local::now(), &output).unwrap();
+        let printed = con.captured();
+        let lines: Vec<_> = printed.lines().collect();
+        assert!(lines[0].ends_with("(2 turns)"), "{}", printed);
+        assert_eq!(
+            lines[1..],
+            ["Ann - 0", "Bob - 0", "No points scored — no winner"]
+        );
+
+        // A script with lines left would answer the filename prompt
+        let output = ScoresOutput {
+            no_prompt: true,
+            ..output
+        };
+        let mut con = Console::capturing();
+        let mut prompter = ScriptedPrompter::from_script("scores.txt");
+        save_scores(&mut con, &mut prompter, &game, Local::now(), &output).unwrap();
+        assert!(con
+            .captured()
+            .ends_with("Bob - 0\nNo points scored — no winner\n"));
+        assert_eq!(prompter.0.len(), 1);
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Write};
+
+use crate::console::{out, outln, Console};
+use crate::Command;
+
+pub trait Prompter {
+    fn get_move(&mut self, con: &mut Console, player_no: usize) -> Option<Command>;
+    fn get_pick(&mut self, con: &mut Console) -> Option<usize>;
+    fn get_name(&mut self, con: &mut Console, player: Option<usize>) -> String;
+    fn get_filename(&mut self, con: &mut Console) -> String;
+    fn confirm(&mut self, con: &mut Console, prompt: &str) -> bool;
+    fn is_interactive(&self) -> bool;
+}
+
+pub struct TerminalPrompter {
+    one_key: bool,
+}
+
+pub struct ScriptedPrompter(pub VecDeque<String>);
+
+fn parse_pick(input: &str) -> Option<usize> {
+    input
+        .trim()
+        .parse()
+        .ok()
+        .filter(|val| (1..=6).contains(val))
+}
+
+fn name_prompt(con: &mut Console, player: Option<usize>) {
+    match player {
+        Some(idx) => out!(con, "Enter name for player {}: ", idx + 1),
+        None => out!(con, "Enter your name: "),
+    }
+}
+
+impl TerminalPrompter {
+    pub fn new() -> Self {
+        TerminalPrompter {
+            one_key: cfg!(feature = "onekey") && io::stdin().is_terminal(),
+        }
+    }
+
+    pub fn is_one_key(&self) -> bool {
+        self.one_key
+    }
+
+    fn read_line(con: &mut Console) -> String {
+        con.read_line().expect("Failed to read").trim().to_string()
+    }
+
+    #[cfg(feature = "onekey")]
+    fn read_key(&self, con: &mut Console) -> Option<char> {
+        if !self.one_key {
+            return None;
+        }
+        let key = con.read_key().unwrap();
+        outln!(con);
+        Some(key)
+    }
+
+    #[cfg(not(feature = "onekey"))]
+    fn read_key(&self, _con: &mut Console) -> Option<char> {
+        None
+    }
+}
+
+impl Prompter for TerminalPrompter {
+    fn get_move(&mut self, con: &mut Console, player_no: usize) -> Option<Command> {
+        out!(con, "{}> ", player_no);
+        match self.read_key(con) {
+            Some(key) => Command::try_from(key).ok(),
+            None => Self::read_line(con).parse().ok(),
+        }
+    }
+
+    fn get_pick(&mut self, con: &mut Console) -> Option<usize> {
+        out!(con, "Picking> ");
+        match self.read_key(con) {
+            Some(key) => "qwerty".find(key).map(|idx| idx + 1),
+            None => parse_pick(&Self::read_line(con)),
+        }
+    }
+
+    fn get_name(&mut self, con: &mut Console, player: Option<usize>) -> String {
+        name_prompt(con, player);
+        Self::read_line(con)
+    }
+
+    fn get_filename(&mut self, con: &mut Console) -> String {
+        out!(con, "Enter filename for scores: ");
+        Self::read_line(con)
+    }
+
+    fn confirm(&mut self, con: &mut Console, prompt: &str) -> bool {
+        out!(con, "{} (y/n) ", prompt);
+        match self.read_key(con) {
+            Some(key) => key.eq_ignore_ascii_case(&'y'),
+            None => Self::read_line(con).eq_ignore_ascii_case("y"),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+}
+
+impl ScriptedPrompter {
+    pub fn from_script(script: &str) -> Self {
+        ScriptedPrompter(script.lines().map(|line| line.trim().to_string()).collect())
+    }
+
+    fn answer(&mut self, con: &mut Console) -> Option<String> {
+        let answer = self.0.pop_front();
+        outln!(con, "{}", answer.as_deref().unwrap_or_default());
+        answer
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn get_move(&mut self, con: &mut Console, player_no: usize) -> Option<Command> {
+        out!(con, "{}> ", player_no);
+        match self.answer(con) {
+            Some(answer) => answer.parse().ok(),
+            None => Some(Command::Exit),
+        }
+    }
+
+    fn get_pick(&mut self, con: &mut Console) -> Option<usize> {
+        out!(con, "Picking> ");
+        self.answer(con).as_deref().and_then(parse_pick)
+    }
+
+    fn get_name(&mut self, con: &mut Console, player: Option<usize>) -> String {
+        name_prompt(con, player);
+        self.answer(con)
+            .unwrap_or_else(|| format!("Player {}", player.unwrap_or(0) + 1))
+    }
+
+    fn get_filename(&mut self, con: &mut Console) -> String {
+        out!(con, "Enter filename for scores: ");
+        self.answer(con).unwrap_or_default()
+    }
+
+    fn confirm(&mut self, con: &mut Console, prompt: &str) -> bool {
+        out!(con, "{} (y/n) ", prompt);
+        self.answer(con)
+            .is_some_and(|answer| answer.eq_ignore_ascii_case("y"))
+    }
+
+    fn is_interactive(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
@@ -12,7 +12,4 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-pub mod farkle;
-
-#[cfg(test)]
-mod tests {}
+pub mod udp;
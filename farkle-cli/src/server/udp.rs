@@ -0,0 +1,561 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use rsfarkle::farkle::{GameAction, GameState};
+use rsfarkle::game::{Game, GameConfig};
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_RETRIES: u32 = 20;
+// How long to wait for the other player's next move before giving up on them
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_DATAGRAM: usize = 65507;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveMessage {
+    pub seq: u32,
+    pub game_id: u128,
+    pub action: GameAction,
+    pub checksum: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Message {
+    Join(String),
+    State {
+        game_id: u128,
+        seq: u32,
+        transcript: String,
+    },
+    Move(MoveMessage),
+    Ack(u32),
+    Resync(u128),
+    Bye,
+}
+
+#[derive(Debug)]
+pub struct UdpGameTransport {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    game_id: u128,
+    sent: u32,
+    received: u32,
+    idle_timeout: Duration,
+}
+
+pub fn state_checksum(game: &Game) -> u32 {
+    let snapshot = game.snapshot();
+    let mut bytes = vec![];
+    let mut number = |n: u64| bytes.extend(n.to_le_bytes());
+    for die in &snapshot.dice {
+        number(die.value as u64);
+        number(die.picked as u64 | (die.picked_this_roll as u64) << 1);
+    }
+    for player in &snapshot.players {
+        number(player.name.len() as u64);
+        number(player.score as u64);
+        number(player.hand as u64);
+    }
+    number(snapshot.current_player as u64);
+    number(snapshot.current_turn as u64);
+    number(snapshot.turns as u64);
+    number(match snapshot.state {
+        GameState::FirstRoll => 0,
+        GameState::Rolling => 1,
+        GameState::HotDice => 2,
+        GameState::Picking => 3,
+        GameState::TurnEnded => 4,
+    });
+    number(snapshot.pot.map_or(0, |pot| pot as u64 + 1));
+    number(snapshot.rolls_this_turn as u64);
+    for player in &snapshot.players {
+        bytes.extend(player.name.bytes());
+    }
+    bytes.iter().fold(0x811c9dc5, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+pub fn action_text(action: GameAction) -> String {
+    match action {
+        GameAction::Roll => "ROLL".to_string(),
+        GameAction::ToggleDie(idx) => format!("TOGGLE {}", idx + 1),
+        GameAction::TakeValue(value) => format!("TAKE {}", value),
+        GameAction::ConfirmSelection => "CONFIRM".to_string(),
+        GameAction::UndoSelection => "UNPICK".to_string(),
+        GameAction::Bank => "BANK".to_string(),
+        GameAction::ForfeitTurn => "FORFEIT".to_string(),
+        GameAction::PassToTeammate(idx) => format!("PASS {}", idx + 1),
+    }
+}
+
+fn parse_action(text: &str) -> Option<GameAction> {
+    let mut words = text.split_whitespace();
+    let keyword = words.next()?;
+    let mut number = || match words.next()?.parse::<usize>() {
+        Ok(n) if n > 0 => Some(n),
+        _ => None,
+    };
+    let action = match keyword {
+        "ROLL" => GameAction::Roll,
+        "TOGGLE" => GameAction::ToggleDie(number()? - 1),
+        "TAKE" => GameAction::TakeValue(number()?),
+        "CONFIRM" => GameAction::ConfirmSelection,
+        "UNPICK" => GameAction::UndoSelection,
+        "BANK" => GameAction::Bank,
+        "FORFEIT" => GameAction::ForfeitTurn,
+        "PASS" => GameAction::PassToTeammate(number()? - 1),
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn invalid(reason: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
+
+impl Message {
+    fn encode(&self) -> String {
+        match self {
+            Message::Join(name) => format!("JOIN {}", name),
+            Message::State {
+                game_id,
+                seq,
+                transcript,
+            } => format!("STATE {:x} {}\n{}", game_id, seq, transcript),
+            Message::Move(mov) => format!(
+                "MOVE {} {:x} {}\n{}",
+                mov.seq,
+                mov.game_id,
+                mov.checksum,
+                action_text(mov.action)
+            ),
+            Message::Ack(seq) => format!("ACK {}", seq),
+            Message::Resync(game_id) => format!("RESYNC {:x}", game_id),
+            Message::Bye => "BYE".to_string(),
+        }
+    }
+
+    fn decode(text: &str) -> Option<Message> {
+        let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+        let mut words = header.split_whitespace();
+        let keyword = words.next()?;
+        let mut next = || words.next();
+        let message = match keyword {
+            "JOIN" => Message::Join(header["JOIN".len()..].trim().to_string()),
+            "STATE" => Message::State {
+                game_id: u128::from_str_radix(next()?, 16).ok()?,
+                seq: next()?.parse().ok()?,
+                transcript: body.to_string(),
+            },
+            "MOVE" => Message::Move(MoveMessage {
+                seq: next()?.parse().ok()?,
+                game_id: u128::from_str_radix(next()?, 16).ok()?,
+                checksum: next()?.parse().ok()?,
+                action: parse_action(body)?,
+            }),
+            "ACK" => Message::Ack(next()?.parse().ok()?),
+            "RESYNC" => Message::Resync(u128::from_str_radix(next()?, 16).ok()?),
+            "BYE" => Message::Bye,
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+impl UdpGameTransport {
+    pub fn host(port: u16, name: &str, config: GameConfig) -> io::Result<(Self, Game)> {
+        Self::host_on(UdpSocket::bind(("0.0.0.0", port))?, name, config)
+    }
+
+    pub fn host_on(
+        socket: UdpSocket,
+        name: &str,
+        mut config: GameConfig,
+    ) -> io::Result<(Self, Game)> {
+        let mut buf = vec![0; MAX_DATAGRAM];
+        let (peer, guest) = loop {
+            let (len, from) = socket.recv_from(&mut buf)?;
+            if let Some(Message::Join(guest)) =
+                Message::decode(&String::from_utf8_lossy(&buf[..len]))
+            {
+                break (from, guest);
+            }
+        };
+        config.player_names = vec![name.to_string(), guest];
+        let game = Game::new(config);
+        socket.set_read_timeout(Some(RETRY_INTERVAL))?;
+        let transport = UdpGameTransport {
+            socket,
+            peer,
+            game_id: rand::thread_rng().gen(),
+            sent: 0,
+            received: 0,
+            idle_timeout: IDLE_TIMEOUT,
+        };
+        transport.send_state(&game)?;
+        Ok((transport, game))
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> io::Result<(Self, Game)> {
+        let peer = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| invalid("No address to connect to"))?;
+        let local = if peer.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let mut transport = UdpGameTransport {
+            socket: UdpSocket::bind(local)?,
+            peer,
+            game_id: 0,
+            sent: 0,
+            received: 0,
+            idle_timeout: IDLE_TIMEOUT,
+        };
+        transport.socket.set_read_timeout(Some(RETRY_INTERVAL))?;
+        for _ in 0..MAX_RETRIES {
+            transport.send(&Message::Join(name.to_string()))?;
+            let deadline = Instant::now() + RETRY_INTERVAL;
+            while let Some(message) = transport.recv_until(deadline)? {
+                if let Message::State {
+                    game_id,
+                    seq,
+                    transcript,
+                } = message
+                {
+                    transport.game_id = game_id;
+                    let game = transport.load_state(seq, &transcript)?;
+                    return Ok((transport, game));
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "The host did not answer",
+        ))
+    }
+
+    pub fn send_move(&mut self, action: GameAction, game: &Game) -> io::Result<()> {
+        self.sent += 1;
+        let message = Message::Move(MoveMessage {
+            seq: self.sent,
+            game_id: self.game_id,
+            action,
+            checksum: state_checksum(game),
+        });
+        let mut peer_left = false;
+        for _ in 0..MAX_RETRIES {
+            self.send(&message)?;
+            let deadline = Instant::now() + RETRY_INTERVAL;
+            while let Some(reply) = self.recv_until(deadline)? {
+                match reply {
+                    Message::Ack(seq) if seq == self.sent => return Ok(()),
+                    Message::Bye => peer_left = true,
+                    other => self.answer(other, game)?,
+                }
+            }
+        }
+        if peer_left {
+            Err(Self::peer_left())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "The other player stopped answering",
+            ))
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_idle_timeout(&mut self, timeout: Duration) {
+        self.idle_timeout = timeout;
+    }
+
+    pub fn recv_move(&mut self, game: &mut Game) -> io::Result<GameAction> {
+        let retries = (self.idle_timeout.as_millis() / RETRY_INTERVAL.as_millis()).max(1);
+        let mut idle = 0;
+        loop {
+            let Some(message) = self.recv_until(Instant::now() + RETRY_INTERVAL)? else {
+                idle += 1;
+                if idle >= retries {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "The other player stopped answering",
+                    ));
+                }
+                continue;
+            };
+            idle = 0;
+            let mov = match message {
+                Message::Move(mov) if mov.game_id == self.game_id => mov,
+                other => {
+                    self.answer(other, game)?;
+                    continue;
+                }
+            };
+            if mov.seq <= self.received {
+                self.send(&Message::Ack(mov.seq))?;
+                continue;
+            }
+            // A rejected action is fine as long as it was rejected on both sides
+            let _ = game.apply(mov.action);
+            if state_checksum(game) == mov.checksum {
+                self.received = mov.seq;
+            } else {
+                *game = self.resync()?;
+            }
+            self.send(&Message::Ack(mov.seq))?;
+            return Ok(mov.action);
+        }
+    }
+
+    pub fn close(&self) {
+        let _ = self.send(&Message::Bye);
+        let deadline = Instant::now() + RETRY_INTERVAL * 4;
+        while Instant::now() < deadline {
+            if let Ok(Some(Message::Move(mov))) = self.recv_until(deadline) {
+                if mov.seq <= self.received {
+                    let _ = self.send(&Message::Ack(mov.seq));
+                }
+            }
+        }
+    }
+
+    fn resync(&mut self) -> io::Result<Game> {
+        for _ in 0..MAX_RETRIES {
+            self.send(&Message::Resync(self.game_id))?;
+            let deadline = Instant::now() + RETRY_INTERVAL;
+            while let Some(message) = self.recv_until(deadline)? {
+                if let Message::State {
+                    game_id,
+                    seq,
+                    transcript,
+                } = message
+                {
+                    if game_id == self.game_id {
+                        return self.load_state(seq, &transcript);
+                    }
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "The other player did not send the game state",
+        ))
+    }
+
+    fn load_state(&mut self, seq: u32, transcript: &str) -> io::Result<Game> {
+        let (game, _) = Game::from_transcript(transcript).map_err(invalid)?;
+        self.received = seq;
+        Ok(game)
+    }
+
+    fn answer(&self, message: Message, game: &Game) -> io::Result<()> {
+        match message {
+            Message::Join(_) | Message::Resync(_) => self.send_state(game),
+            Message::Move(mov) if mov.seq <= self.received => self.send(&Message::Ack(mov.seq)),
+            Message::Bye => Err(Self::peer_left()),
+            _ => Ok(()),
+        }
+    }
+
+    fn peer_left() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::ConnectionAborted,
+            "The other player left the game",
+        )
+    }
+
+    fn send_state(&self, game: &Game) -> io::Result<()> {
+        self.send(&Message::State {
+            game_id: self.game_id,
+            seq: self.sent,
+            transcript: game.transcript(),
+        })
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        self.socket
+            .send_to(message.encode().as_bytes(), self.peer)?;
+        Ok(())
+    }
+
+    fn recv_until(&self, deadline: Instant) -> io::Result<Option<Message>> {
+        let mut buf = vec![0; MAX_DATAGRAM];
+        while Instant::now() < deadline {
+            match self.socket.recv_from(&mut buf) {
+                Ok((len, from)) if from == self.peer => {
+                    if let Some(message) = Message::decode(&String::from_utf8_lossy(&buf[..len])) {
+                        return Ok(Some(message));
+                    }
+                }
+                Ok(_) => (),
+                Err(e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn config(seed: u64) -> GameConfig {
+        GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 50,
+            seed: Some(seed),
+            ..Default::default()
+        }
+    }
+
+    fn pair(seed: u64) -> ((UdpGameTransport, Game), (UdpGameTransport, Game)) {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        let host = thread::spawn(move || UdpGameTransport::host_on(socket, "Ann", config(seed)));
+        let guest = UdpGameTransport::connect(addr, "Bob").unwrap();
+        (host.join().unwrap().unwrap(), guest)
+    }
+
+    fn next_action(game: &Game) -> GameAction {
+        match game.state() {
+            GameState::Picking if game.roll().dice().iter().any(|die| die.picked_this_roll()) => {
+                GameAction::ConfirmSelection
+            }
+            GameState::Picking => {
+                let unpicked = game.roll().unpicked_values();
+                (1..=6)
+                    .find(|&value| {
+                        let count = unpicked.iter().filter(|&&die| die == value).count();
+                        count >= 3 || (count > 0 && (value == 1 || value == 5))
+                    })
+                    .map(GameAction::TakeValue)
+                    .unwrap()
+            }
+            GameState::Rolling | GameState::HotDice
+                if game.current_player().hand_value() >= 300 =>
+            {
+                GameAction::Bank
+            }
+            _ => GameAction::Roll,
+        }
+    }
+
+    fn play(transport: &mut UdpGameTransport, game: &mut Game, local: usize, moves: usize) {
+        for _ in 0..moves {
+            if game.state() == GameState::TurnEnded {
+                game.next_turn();
+            }
+            if game.current_player_index() == local {
+                let action = next_action(game);
+                game.apply(action).unwrap();
+                transport.send_move(action, game).unwrap();
+            } else {
+                transport.recv_move(game).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn moves_round_trip_as_text() {
+        for action in [
+            GameAction::Roll,
+            GameAction::ToggleDie(0),
+            GameAction::TakeValue(5),
+            GameAction::ConfirmSelection,
+            GameAction::UndoSelection,
+            GameAction::Bank,
+            GameAction::ForfeitTurn,
+            GameAction::PassToTeammate(3),
+        ] {
+            let message = Message::Move(MoveMessage {
+                seq: 7,
+                game_id: 0xfa4c1e,
+                action,
+                checksum: 42,
+            });
+            assert_eq!(Message::decode(&message.encode()), Some(message));
+        }
+        assert_eq!(parse_action("TOGGLE 0"), None);
+        assert_eq!(parse_action("SHOUT"), None);
+    }
+
+    #[test]
+    fn checksum_tracks_the_game_state() {
+        let game = Game::new(config(1));
+        let mut rolled = Game::new(config(1));
+        assert_eq!(state_checksum(&game), state_checksum(&rolled));
+        rolled.apply(GameAction::Roll).unwrap();
+        assert_ne!(state_checksum(&game), state_checksum(&rolled));
+    }
+
+    #[test]
+    fn peers_stay_in_step_over_loopback() {
+        let ((mut host, mut hosted), (mut guest, mut joined)) = pair(5);
+        let peer = thread::spawn(move || {
+            play(&mut guest, &mut joined, 1, 20);
+            joined
+        });
+        play(&mut host, &mut hosted, 0, 20);
+        let joined = peer.join().unwrap();
+        assert_eq!(hosted.snapshot(), joined.snapshot());
+        assert_eq!(hosted.transcript(), joined.transcript());
+    }
+
+    // Debug dice always roll a straight, which leaves nothing to pick
+    #[cfg(not(feature = "debug-dice"))]
+    #[test]
+    fn dropped_move_forces_a_resync() {
+        let ((mut host, mut hosted), (mut guest, mut joined)) = pair(5);
+        let peer = thread::spawn(move || {
+            guest.recv_move(&mut joined).unwrap();
+            guest.recv_move(&mut joined).unwrap();
+            joined
+        });
+        hosted.apply(GameAction::Roll).unwrap();
+        host.send_move(GameAction::Roll, &hosted).unwrap();
+        assert_eq!(hosted.state(), GameState::Picking);
+        // The pick never reaches the guest, so the confirmation cannot match
+        let take = next_action(&hosted);
+        hosted.apply(take).unwrap();
+        hosted.apply(GameAction::ConfirmSelection).unwrap();
+        host.send_move(GameAction::ConfirmSelection, &hosted)
+            .unwrap();
+        let joined = peer.join().unwrap();
+        assert_eq!(hosted.snapshot(), joined.snapshot());
+    }
+
+    #[test]
+    fn silent_peer_times_out() {
+        let ((_host, _), (mut guest, mut joined)) = pair(5);
+        guest.set_idle_timeout(Duration::ZERO);
+        let err = guest.recv_move(&mut joined).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, SeedableRng};
+use rsfarkle::bot::{simulate_n_turns, GreedyBot};
+use rsfarkle::farkle::Hand;
+use rsfarkle::rules::StandardRules;
+
+fn simulate_turns(c: &mut Criterion) {
+    c.bench_function("simulate 10k turns", |b| {
+        b.iter(|| {
+            let mut rng = StdRng::seed_from_u64(7);
+            simulate_n_turns(
+                10_000,
+                &GreedyBot::default(),
+                &Hand::default(),
+                &StandardRules,
+                &mut rng,
+            )
+        })
+    });
+}
+
+criterion_group!(benches, simulate_turns);
+criterion_main!(benches);
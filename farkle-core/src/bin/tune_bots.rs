@@ -0,0 +1,47 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use rsfarkle::rules::StandardRules;
+use rsfarkle::tuning::{rank_difficulties, tune_difficulties, TournamentConfig};
+
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(0);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let config = TournamentConfig::default();
+    println!(
+        "Tuning against the default bot ({} games of {} turns, seed {})",
+        config.games, config.turns, seed
+    );
+    for (difficulty, tuned) in tune_difficulties(config, &StandardRules, &mut rng) {
+        match tuned {
+            Some(tuned) => println!(
+                "{}: bank at {} ({:.1}% wins, target {:.0}%)",
+                difficulty,
+                tuned.bank_threshold,
+                tuned.win_rate * 100.,
+                difficulty.target_win_rate() * 100.
+            ),
+            None => println!("{}: no candidates", difficulty),
+        }
+    }
+    println!("Round robin between the difficulties:");
+    for (difficulty, win_rate) in rank_difficulties(config, &StandardRules, &mut rng) {
+        println!("{}: {:.1}% wins", difficulty, win_rate * 100.);
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use rand::Rng;
+
+use crate::farkle::{Hand, Roll, RollType, Selection, ToggleResult};
+use crate::rules::ScoringRules;
+
+const DEFAULT_BANK_THRESHOLD: u32 = 300;
+// Picked with the tune-bots binary against the default bot
+const EASY_BANK_THRESHOLD: u32 = 1600;
+const MEDIUM_BANK_THRESHOLD: u32 = 850;
+const HARD_BANK_THRESHOLD: u32 = 350;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BotMove {
+    Pick(Vec<usize>),
+    Roll,
+    Bank,
+}
+
+pub trait BotStrategy {
+    fn choose_move(&self, roll: &Roll, hand: &Hand) -> BotMove;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GreedyBot {
+    pub bank_threshold: u32,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct TurnSimResult {
+    pub banked: u32,
+    pub farkled: bool,
+    pub rolls_taken: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct RollScoreCache {
+    selections: HashMap<Roll, Vec<Selection>>,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn target_win_rate(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.25,
+            Difficulty::Medium => 0.4,
+            Difficulty::Hard => 0.5,
+        }
+    }
+
+    pub fn strategy(&self) -> GreedyBot {
+        let bank_threshold = match self {
+            Difficulty::Easy => EASY_BANK_THRESHOLD,
+            Difficulty::Medium => MEDIUM_BANK_THRESHOLD,
+            Difficulty::Hard => HARD_BANK_THRESHOLD,
+        };
+        GreedyBot { bank_threshold }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err("Expected easy, medium or hard"),
+        }
+    }
+}
+
+impl Default for GreedyBot {
+    fn default() -> Self {
+        GreedyBot {
+            bank_threshold: DEFAULT_BANK_THRESHOLD,
+        }
+    }
+}
+
+impl BotStrategy for GreedyBot {
+    fn choose_move(&self, roll: &Roll, hand: &Hand) -> BotMove {
+        let pickable: Vec<usize> = roll
+            .determine_pickable(None)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &allowed)| allowed.then_some(i))
+            .collect();
+        if !pickable.is_empty() {
+            return BotMove::Pick(pickable);
+        }
+        let total: u32 = hand.iter().map(|sel| sel.value()).sum();
+        let hot_dice = roll.dice().iter().all(|die| die.picked());
+        if total >= self.bank_threshold && !hot_dice {
+            BotMove::Bank
+        } else {
+            BotMove::Roll
+        }
+    }
+}
+
+impl RollScoreCache {
+    fn key(roll: &Roll) -> Roll {
+        let mut pool: Vec<_> = roll
+            .dice()
+            .iter()
+            .filter(|die| !die.picked() || die.picked_this_roll())
+            .map(|die| die.value())
+            .collect();
+        pool.sort_unstable();
+        let mut key = Roll::with_faces(roll.faces().clone());
+        for die in pool.len()..key.dice().len() {
+            key.set_aside(die);
+        }
+        key.new_roll_from_values(&pool)
+            .expect("the key keeps one value per unpicked die");
+        key
+    }
+
+    pub fn selections(&mut self, roll: &Roll, rules: &dyn ScoringRules) -> &[Selection] {
+        let key = RollScoreCache::key(roll);
+        if self.selections.contains_key(&key) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            let selections = key
+                .all_valid_selections(rules)
+                .into_iter()
+                .map(|(_, selection)| selection)
+                .collect();
+            self.selections.insert(key.clone(), selections);
+        }
+        &self.selections[&key]
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+impl Roll {
+    pub fn simulate_turn(
+        &mut self,
+        strategy: &dyn BotStrategy,
+        hand: &Hand,
+        rules: &dyn ScoringRules,
+    ) -> TurnSimResult {
+        self.simulate_turn_with_rng(strategy, hand, rules, &mut rand::thread_rng())
+    }
+
+    pub fn simulate_turn_with_rng(
+        &mut self,
+        strategy: &dyn BotStrategy,
+        hand: &Hand,
+        rules: &dyn ScoringRules,
+        rng: &mut impl Rng,
+    ) -> TurnSimResult {
+        let mut hand = hand.clone();
+        let mut result = TurnSimResult::default();
+        loop {
+            if self.is_exhausted() {
+                self.release_all();
+            }
+            self.new_roll_with_rng(rng);
+            result.rolls_taken += 1;
+            let (selection, roll_type) = self.determine_type();
+            match roll_type {
+                RollType::Farkle => {
+                    result.farkled = true;
+                    return result;
+                }
+                RollType::Straight | RollType::TriplePair => hand.push(selection),
+                RollType::Simple => {
+                    let picks = match strategy.choose_move(self, &hand) {
+                        BotMove::Pick(picks) => picks,
+                        _ => return result,
+                    };
+                    for die in picks {
+                        if die >= self.dice().len() || self.toggle_die(die) != ToggleResult::Picked
+                        {
+                            return result;
+                        }
+                    }
+                    match self.construct_selection_with(rules) {
+                        Ok(selection) => hand.push(selection),
+                        Err(_) => return result,
+                    }
+                }
+            }
+            match strategy.choose_move(self, &hand) {
+                BotMove::Roll => (),
+                BotMove::Bank | BotMove::Pick(_) => {
+                    result.banked = hand.iter().map(|sel| sel.value()).sum();
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+pub fn simulate_n_turns(
+    n: usize,
+    strategy: &dyn BotStrategy,
+    hand: &Hand,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> Vec<TurnSimResult> {
+    (0..n)
+        .map(|_| Roll::with_faces(rules.faces()).simulate_turn_with_rng(strategy, hand, rules, rng))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::rules::StandardRules;
+
+    fn seeded_turns(n: usize) -> Vec<TurnSimResult> {
+        let mut rng = StdRng::seed_from_u64(7);
+        simulate_n_turns(
+            n,
+            &GreedyBot::default(),
+            &Hand::default(),
+            &StandardRules,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn simulated_turns_mostly_bank() {
+        let results = seeded_turns(1000);
+        assert_eq!(results, seeded_turns(1000));
+        let banked = results.iter().filter(|result| result.banked > 0).count();
+        assert!(banked > 600, "only {} of 1000 turns banked", banked);
+        for result in &results {
+            assert!(result.rolls_taken > 0);
+            assert_eq!(result.farkled, result.banked == 0);
+            if !result.farkled {
+                assert!(result.banked >= DEFAULT_BANK_THRESHOLD);
+            }
+        }
+    }
+
+    fn hash_of(roll: &Roll) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        roll.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn cache_keys_ignore_dice_order() {
+        let roll = Roll::from_values([1, 1, 1, 5, 5, 5]);
+        let hash = hash_of(&roll);
+        assert!((0..1000).all(|_| hash_of(&Roll::from_values([1, 1, 1, 5, 5, 5])) == hash));
+
+        let mut cache = RollScoreCache::default();
+        let selections = cache.selections(&roll, &StandardRules).to_vec();
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+        for values in [[5, 1, 5, 1, 5, 1], [5, 5, 5, 1, 1, 1], [1, 5, 1, 5, 1, 5]] {
+            let permuted = cache.selections(&Roll::from_values(values), &StandardRules);
+            assert_eq!(permuted, selections);
+        }
+        assert_eq!((cache.hits(), cache.misses()), (3, 1));
+        cache.selections(&Roll::from_values([1, 1, 1, 5, 5, 2]), &StandardRules);
+        assert_eq!(cache.misses(), 2);
+    }
+
+    #[test]
+    fn cache_hits_repeated_rolls() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut cache = RollScoreCache::default();
+        assert_eq!(cache.hit_rate(), 0.);
+        let mut roll = Roll::default();
+        for _ in 0..1000 {
+            roll.new_roll_with_rng(&mut rng);
+            cache.selections(&roll, &StandardRules);
+        }
+        assert_eq!(cache.hits() + cache.misses(), 1000);
+        assert!(cache.hit_rate() >= 0.3, "hit rate {}", cache.hit_rate());
+    }
+}
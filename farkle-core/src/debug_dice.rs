@@ -0,0 +1,64 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+// Developer-only: replaces random rolls with a fixed, cycling sequence so
+// scoring paths can be reproduced by hand. Never enable this for real games.
+// Release builds ignore the sequence and roll normally.
+use std::sync::OnceLock;
+
+use crate::farkle::{DieValue, Roll};
+
+const DEBUG_DICE_VAR: &str = "FARKLE_DEBUG_DICE";
+const STRAIGHT: [DieValue; 6] = [1, 2, 3, 4, 5, 6];
+
+static SEQUENCE: OnceLock<Vec<DieValue>> = OnceLock::new();
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DebugDice {
+    cursor: usize,
+}
+
+fn sequence() -> &'static [DieValue] {
+    SEQUENCE.get_or_init(|| {
+        let values: Vec<DieValue> = std::env::var(DEBUG_DICE_VAR)
+            .unwrap_or_default()
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(|value| value.parse().ok())
+            .filter(|&value| value > 0)
+            .collect();
+        if values.is_empty() {
+            STRAIGHT.to_vec()
+        } else {
+            values
+        }
+    })
+}
+
+impl DebugDice {
+    // Each game walks the sequence from its own start
+    pub(crate) fn roll(&mut self, roll: &mut Roll) -> bool {
+        if !cfg!(debug_assertions) {
+            return false;
+        }
+        let sequence = sequence();
+        let faces = roll.faces().count;
+        let values: Vec<_> = (0..roll.unpicked_values().len())
+            .map(|offset| sequence[(self.cursor + offset) % sequence.len()].min(faces))
+            .collect();
+        self.cursor += values.len();
+        roll.new_roll_from_values(&values)
+            .expect("one value per unpicked die");
+        true
+    }
+}
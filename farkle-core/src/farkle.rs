@@ -0,0 +1,1983 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::{Add, AddAssign},
+    str::FromStr,
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::game::GameError;
+use crate::rules::{DiceFaces, FourKindRule, Ruleset, ScoringRules, StandardRules};
+use crate::stats::PlayerStats;
+
+pub const STRAIGHT_VALUE: u32 = 3000;
+pub const TRIPLE_PAIR_VALUE: u32 = 2000;
+
+pub const ONE_VALUE: u32 = 100;
+pub const FIVE_VALUE: u32 = 50;
+
+pub const SET_SCALE_VALUE: u32 = 100;
+const SINGLE_SET_MULTIPLIER: u32 = 10;
+
+pub const FALLBACK_TURN_AVERAGE: u32 = 400;
+const MIN_TURNS_FOR_AVERAGE: u32 = 3;
+
+const GOLDEN_RATIO_U64: u64 = 0x9E37_79B9_7F4A_7C15;
+const STANDARD_FACES: [DieValue; 6] = [1, 2, 3, 4, 5, 6];
+
+const PIP_CELLS: [&[usize]; 9] = [
+    &[4],
+    &[0, 8],
+    &[0, 4, 8],
+    &[0, 2, 6, 8],
+    &[0, 2, 4, 6, 8],
+    &[0, 2, 3, 5, 6, 8],
+    &[0, 2, 3, 4, 5, 6, 8],
+    &[0, 1, 2, 3, 5, 6, 7, 8],
+    &[0, 1, 2, 3, 4, 5, 6, 7, 8],
+];
+
+#[derive(Default, Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize))]
+pub enum GameState {
+    #[default]
+    FirstRoll,
+    Rolling,
+    HotDice,
+    Picking,
+    TurnEnded,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RollType {
+    Farkle,
+    Simple,
+    TriplePair,
+    Straight,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ToggleResult {
+    Picked,
+    Unpicked,
+    NotPickable,
+    NotUnpickable,
+}
+
+pub type DieValue = usize;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Die {
+    value: DieValue,
+    picked: bool,
+    picked_this_roll: bool,
+    faces: Arc<[DieValue]>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Roll {
+    dice: [Die; 6],
+    faces: DiceFaces,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoreComponent {
+    Straight,
+    TriplePair([DieValue; 3]),
+    Set {
+        face: DieValue,
+        count: usize,
+        base: u32,
+    },
+    FourOfAKind {
+        face: DieValue,
+        points: u32,
+    },
+    Single {
+        face: DieValue,
+        points: u32,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ScoringPattern {
+    Triplet { face: DieValue, count: u8 },
+    Singles(Vec<(DieValue, u8)>),
+    Straight,
+    TriplePair,
+    Mixed(Vec<ScoringPattern>),
+}
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct Selection {
+    components: Vec<ScoreComponent>,
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    picks: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseSelectionError {
+    InvalidDieValue { val: String },
+    EmptySelection,
+    TooManyDice(usize),
+    NotScoring(&'static str),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GameAction {
+    Roll,
+    ToggleDie(usize),
+    TakeValue(DieValue),
+    ConfirmSelection,
+    UndoSelection,
+    Bank,
+    ForfeitTurn,
+    PassToTeammate(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownAction;
+
+#[deprecated(note = "use GameAction; presentation commands belong to the front ends")]
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum MoveType {
+    Roll,
+    Bank,
+    Exit,
+    View,
+    Pick,
+    Help,
+    Hand,
+    Unpick,
+}
+
+pub type Hand = Vec<Selection>;
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    hand: Hand,
+    score: u32,
+    name: String,
+    stats: PlayerStats,
+    #[cfg(feature = "turn-timer")]
+    turn_time: std::time::Duration,
+    #[cfg(feature = "turn-timer")]
+    timed_turns: u32,
+}
+
+impl Display for RollType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RollType::Farkle => write!(f, "Farkle"),
+            RollType::Simple => write!(f, "Simple roll"),
+            RollType::TriplePair => write!(f, "Triple pair"),
+            RollType::Straight => write!(f, "Straight"),
+        }
+    }
+}
+
+impl GameState {
+    pub fn can_roll(&self) -> bool {
+        matches!(
+            self,
+            GameState::FirstRoll | GameState::Rolling | GameState::HotDice
+        )
+    }
+
+    pub fn can_bank(&self) -> bool {
+        matches!(self, GameState::Rolling | GameState::HotDice)
+    }
+}
+
+impl Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::FirstRoll => write!(f, "First roll"),
+            GameState::Rolling => write!(f, "Rolling"),
+            GameState::HotDice => write!(f, "Hot dice"),
+            GameState::Picking => write!(f, "Picking"),
+            GameState::TurnEnded => write!(f, "Turn ended"),
+        }
+    }
+}
+
+impl TryFrom<char> for GameAction {
+    type Error = UnknownAction;
+
+    fn try_from(key: char) -> Result<Self, Self::Error> {
+        match key {
+            'r' => Ok(GameAction::Roll),
+            'b' => Ok(GameAction::Bank),
+            'u' => Ok(GameAction::UndoSelection),
+            'f' => Ok(GameAction::ForfeitTurn),
+            _ => Err(UnknownAction),
+        }
+    }
+}
+
+impl GameAction {
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, GameAction::Bank | GameAction::ForfeitTurn)
+    }
+}
+
+impl FromStr for GameAction {
+    type Err = UnknownAction;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "roll" => Ok(GameAction::Roll),
+            "bank" => Ok(GameAction::Bank),
+            "unpick" => Ok(GameAction::UndoSelection),
+            "forfeit" => Ok(GameAction::ForfeitTurn),
+            _ => match s.split_once(' ') {
+                Some(("take", value)) => match value.trim().parse() {
+                    Ok(value) if value > 0 => Ok(GameAction::TakeValue(value)),
+                    _ => Err(UnknownAction),
+                },
+                Some(("pass", player)) => match player.trim().parse::<usize>() {
+                    Ok(player) if player > 0 => Ok(GameAction::PassToTeammate(player - 1)),
+                    _ => Err(UnknownAction),
+                },
+                _ => Err(UnknownAction),
+            },
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl MoveType {
+    pub fn action(&self) -> Option<GameAction> {
+        match self {
+            MoveType::Roll => Some(GameAction::Roll),
+            MoveType::Bank => Some(GameAction::Bank),
+            MoveType::Pick => Some(GameAction::ConfirmSelection),
+            MoveType::Unpick => Some(GameAction::UndoSelection),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScoreComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<_> = self.values().iter().map(DieValue::to_string).collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+pub fn set_base_value(face: DieValue, faces: &DiceFaces) -> u32 {
+    match faces.single_points(face) {
+        Some(points) => points * SINGLE_SET_MULTIPLIER,
+        None => face as u32 * SET_SCALE_VALUE,
+    }
+}
+
+impl ScoreComponent {
+    pub fn special_roll(counts: &[usize]) -> Option<ScoreComponent> {
+        if counts
+            .get(..6)
+            .is_some_and(|run| run.iter().all(|&c| c == 1))
+        {
+            return Some(ScoreComponent::Straight);
+        }
+        let pairs: Vec<DieValue> = (1..=counts.len())
+            .filter(|&face| counts[face - 1] == 2)
+            .collect();
+        match pairs[..] {
+            [a, b, c] if counts.iter().all(|&c| c == 0 || c == 2) => {
+                Some(ScoreComponent::TriplePair([a, b, c]))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn values(&self) -> Vec<DieValue> {
+        match *self {
+            ScoreComponent::Straight => (1..=6).collect(),
+            ScoreComponent::TriplePair(pairs) => pairs.iter().flat_map(|&v| [v, v]).collect(),
+            ScoreComponent::Set { face, count, .. } => vec![face; count],
+            ScoreComponent::FourOfAKind { face, .. } => vec![face; 4],
+            ScoreComponent::Single { face, .. } => vec![face],
+        }
+    }
+
+    pub fn die_count(&self) -> usize {
+        match *self {
+            ScoreComponent::Straight | ScoreComponent::TriplePair(_) => 6,
+            ScoreComponent::Set { count, .. } => count,
+            ScoreComponent::FourOfAKind { .. } => 4,
+            ScoreComponent::Single { .. } => 1,
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        match *self {
+            ScoreComponent::Straight => STRAIGHT_VALUE,
+            ScoreComponent::TriplePair(_) => TRIPLE_PAIR_VALUE,
+            ScoreComponent::Set { count, base, .. } => base * (count as u32 - 2),
+            ScoreComponent::FourOfAKind { points, .. } => points,
+            ScoreComponent::Single { points, .. } => points,
+        }
+    }
+}
+
+impl Die {
+    pub fn new_with_value(value: usize) -> Self {
+        Die {
+            value,
+            picked: false,
+            picked_this_roll: false,
+            faces: Arc::new(STANDARD_FACES),
+        }
+    }
+
+    pub fn with_faces(value: DieValue, faces: Arc<[DieValue]>) -> Self {
+        Die {
+            faces,
+            ..Die::new_with_value(value)
+        }
+    }
+
+    pub fn faces(&self) -> &[DieValue] {
+        &self.faces
+    }
+
+    pub fn roll_with_rng(&mut self, rng: &mut impl Rng) {
+        self.value = self.faces[rng.gen_range(0..self.faces.len())];
+    }
+
+    fn pick(&mut self) {
+        self.picked = true;
+        self.picked_this_roll = true;
+    }
+
+    fn unpick(&mut self) {
+        self.picked = false;
+        self.picked_this_roll = false;
+    }
+
+    pub fn picked(&self) -> bool {
+        self.picked
+    }
+
+    pub fn picked_this_roll(&self) -> bool {
+        self.picked_this_roll
+    }
+
+    pub fn value(&self) -> DieValue {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: DieValue) {
+        self.value = value;
+    }
+
+    pub fn is_scoreable_single(&self, faces: &DiceFaces) -> bool {
+        faces.is_single(self.value)
+    }
+
+    pub fn is_scoreable_triple(count: usize) -> bool {
+        count >= 3
+    }
+
+    pub fn pips(&self) -> [String; 3] {
+        let mut rows = [(); 3].map(|_| "   ".to_string());
+        if self.picked {
+            return rows;
+        }
+        match PIP_CELLS.get(self.value.wrapping_sub(1)) {
+            Some(cells) => {
+                for cell in *cells {
+                    rows[cell / 3].replace_range(cell % 3..cell % 3 + 1, "o");
+                }
+            }
+            None => rows[1] = format!("{:^3}", self.value),
+        }
+        rows
+    }
+}
+
+impl Roll {
+    pub fn with_faces(faces: DiceFaces) -> Self {
+        let numbered: Arc<[DieValue]> = (1..=faces.count).collect();
+        Roll {
+            dice: core::array::from_fn(|i| Die::with_faces(i % faces.count + 1, numbered.clone())),
+            faces,
+        }
+    }
+
+    pub fn faces(&self) -> &DiceFaces {
+        &self.faces
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        for die in &self.dice {
+            if !die.picked {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn count_values(&self) -> Vec<usize> {
+        let mut res = vec![0; self.faces.count];
+        for die in &self.dice {
+            if !die.picked || die.picked_this_roll {
+                res[die.value - 1] += 1;
+            }
+        }
+        res
+    }
+
+    pub fn determine_pickable(&self, occurrences: Option<&[usize]>) -> [bool; 6] {
+        let mut res = [false; 6];
+        let counts = match occurrences {
+            Some(c) => c.to_vec(),
+            None => self.count_values(),
+        };
+        for (i, die) in self.dice.iter().enumerate() {
+            let count = counts[die.value - 1];
+            res[i] = !die.picked
+                && (die.is_scoreable_single(&self.faces) || Die::is_scoreable_triple(count));
+        }
+        res
+    }
+
+    fn pick_die(&mut self, die: usize) -> bool {
+        let allowed = self.determine_pickable(None);
+        if allowed[die] {
+            self.dice[die].pick();
+            return true;
+        }
+        false
+    }
+
+    fn unpick_die(&mut self, die: usize) -> bool {
+        let die = &mut self.dice[die];
+        if die.picked_this_roll {
+            die.unpick();
+            return true;
+        }
+        false
+    }
+
+    pub fn deselect(&mut self) {
+        for i in 0..6 {
+            self.unpick_die(i);
+        }
+    }
+
+    pub fn new_roll(&mut self) {
+        self.new_roll_with_rng(&mut rand::thread_rng());
+    }
+
+    pub fn new_roll_with_rng(&mut self, rng: &mut impl Rng) {
+        self.lock_current_picks();
+        for die in &mut self.dice {
+            if !die.picked {
+                die.roll_with_rng(rng);
+            }
+        }
+    }
+
+    pub fn from_values(values: [DieValue; 6]) -> Self {
+        let mut roll = Roll::default();
+        roll.reroll_with(|idx| values[idx]);
+        roll
+    }
+
+    pub fn from_seed_and_roll_number(seed: u64, roll_n: usize) -> Self {
+        let mut roll = Roll::default();
+        roll.new_roll_from_seed(seed, roll_n);
+        roll
+    }
+
+    pub fn new_roll_from_seed(&mut self, seed: u64, roll_n: usize) {
+        let count = self.faces.count as u64;
+        let mut state = seed ^ (roll_n as u64).wrapping_mul(GOLDEN_RATIO_U64);
+        let values: [DieValue; 6] = core::array::from_fn(|_| {
+            state = state.wrapping_add(GOLDEN_RATIO_U64);
+            (mix64(state) % count) as DieValue + 1
+        });
+        self.reroll_with(|idx| values[idx]);
+    }
+
+    pub fn new_roll_from_values(&mut self, values: &[DieValue]) -> Result<(), GameError> {
+        let expected = self.dice.iter().filter(|die| !die.picked).count();
+        if values.len() != expected {
+            return Err(GameError::WrongDiceCount {
+                expected,
+                given: values.len(),
+            });
+        }
+        let mut values = values.iter().copied();
+        self.reroll_with(|_| values.next().unwrap_or_default());
+        Ok(())
+    }
+
+    pub fn release_all(&mut self) {
+        for die in &mut self.dice {
+            die.picked = false;
+            die.picked_this_roll = false;
+        }
+    }
+
+    fn reroll_with(&mut self, mut value_for: impl FnMut(usize) -> DieValue) {
+        self.lock_current_picks();
+        for (idx, die) in self.dice.iter_mut().enumerate() {
+            if !die.picked {
+                die.value = value_for(idx);
+            }
+        }
+    }
+
+    fn debug_check_flags(&self) {
+        debug_assert!(
+            self.dice
+                .iter()
+                .all(|die| die.picked || !die.picked_this_roll),
+            "Die picked this roll without being picked"
+        );
+    }
+
+    pub fn set_aside(&mut self, die: usize) {
+        self.dice[die].picked = true;
+        self.dice[die].picked_this_roll = false;
+    }
+
+    pub fn release(&mut self, die: usize) {
+        self.dice[die].picked = false;
+        self.dice[die].picked_this_roll = false;
+    }
+
+    pub fn lock_current_picks(&mut self) {
+        for die in &mut self.dice {
+            die.picked_this_roll = false;
+        }
+        self.debug_check_flags();
+    }
+
+    pub fn current_roll_picks(&self) -> Vec<usize> {
+        (0..self.dice.len())
+            .filter(|&i| self.dice[i].picked_this_roll)
+            .collect()
+    }
+
+    pub fn prior_roll_picks(&self) -> Vec<usize> {
+        (0..self.dice.len())
+            .filter(|&i| self.dice[i].picked && !self.dice[i].picked_this_roll)
+            .collect()
+    }
+
+    pub fn pick_all_of_value(&mut self, value: DieValue) -> usize {
+        let allowed = self.determine_pickable(None);
+        let mut picked = 0;
+        for (die, allowed) in self.dice.iter_mut().zip(allowed) {
+            if allowed && die.value == value {
+                die.pick();
+                picked += 1;
+            }
+        }
+        picked
+    }
+
+    pub fn toggle_die(&mut self, die: usize) -> ToggleResult {
+        self.debug_check_flags();
+        if self.dice[die].picked {
+            if self.unpick_die(die) {
+                ToggleResult::Unpicked
+            } else {
+                ToggleResult::NotUnpickable
+            }
+        } else if self.pick_die(die) {
+            ToggleResult::Picked
+        } else {
+            ToggleResult::NotPickable
+        }
+    }
+
+    pub fn determine_type(&mut self) -> (Selection, RollType) {
+        self.debug_check_flags();
+        debug_assert!(
+            self.current_roll_picks().is_empty(),
+            "Dice picked before the roll was classified"
+        );
+        let mut selection = Selection::default();
+        let counts = self.count_values();
+
+        if let Some(special) = ScoreComponent::special_roll(&counts) {
+            for die in &mut self.dice {
+                die.pick();
+            }
+            let roll_type = match special {
+                ScoreComponent::Straight => RollType::Straight,
+                _ => RollType::TriplePair,
+            };
+            selection.components.push(special);
+            return (
+                selection.with_picks((0..self.dice.len()).collect()),
+                roll_type,
+            );
+        }
+
+        let pickable = self.determine_pickable(Some(counts.as_slice()));
+        for allowed in pickable {
+            if allowed {
+                return (selection, RollType::Simple);
+            }
+        }
+        (selection, RollType::Farkle)
+    }
+
+    pub fn validate_state(&self) -> Result<(), GameError> {
+        for (idx, die) in self.dice.iter().enumerate() {
+            if !(1..=self.faces.count).contains(&die.value) {
+                return Err(GameError::CorruptedState {
+                    field: format!("dice[{}].value", idx),
+                    reason: format!(
+                        "{} is not a face of a {}-sided die",
+                        die.value, self.faces.count
+                    ),
+                });
+            }
+            if die.picked_this_roll && !die.picked {
+                return Err(GameError::CorruptedState {
+                    field: format!("dice[{}].picked_this_roll", idx),
+                    reason: "die was picked this roll but is not marked as picked".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unpicked_values(&self) -> Vec<DieValue> {
+        self.dice
+            .iter()
+            .filter(|die| !die.picked)
+            .map(Die::value)
+            .collect()
+    }
+
+    pub fn pretty(&self) -> String {
+        let edge = self.dice.iter().map(|_| "+---+").join(" ");
+        let faces: Vec<_> = self.dice.iter().map(Die::pips).collect();
+        let rows = (0..3).map(|row| {
+            faces
+                .iter()
+                .map(|face| format!("|{}|", face[row]))
+                .join(" ")
+        });
+        [edge.clone()]
+            .into_iter()
+            .chain(rows)
+            .chain([edge])
+            .join("\n")
+    }
+
+    pub fn picked_values(&self) -> Vec<DieValue> {
+        self.dice
+            .iter()
+            .filter(|die| die.picked)
+            .map(Die::value)
+            .collect()
+    }
+
+    pub fn picked_counts(&self) -> Vec<usize> {
+        let mut chosen = vec![0; self.faces.count];
+        for die in &self.dice {
+            if die.picked_this_roll {
+                chosen[die.value - 1] += 1;
+            }
+        }
+        chosen
+    }
+
+    pub fn construct_selection(&self) -> Result<Selection, &'static str> {
+        score_counts(&self.picked_counts(), FourKindRule::Scale, &self.faces, &[])
+            .map(|sel| sel.with_picks(self.current_roll_picks()))
+    }
+
+    pub fn construct_selection_with(
+        &self,
+        rules: &dyn ScoringRules,
+    ) -> Result<Selection, &'static str> {
+        rules
+            .score(&self.picked_counts())
+            .map(|sel| sel.with_picks(self.current_roll_picks()))
+    }
+
+    fn counts_for_pick(&self, indices: &[usize]) -> Result<Vec<usize>, GameError> {
+        let mut counts = vec![0; self.faces.count];
+        for &idx in indices.iter().sorted().dedup() {
+            let die = self.dice.get(idx).ok_or(GameError::DieOutOfRange(idx))?;
+            if die.picked && !die.picked_this_roll {
+                return Err(GameError::DieAlreadyPicked(idx));
+            }
+            counts[die.value - 1] += 1;
+        }
+        Ok(counts)
+    }
+
+    pub fn validate_pick(&self, indices: &[usize]) -> Result<Selection, GameError> {
+        let counts = self.counts_for_pick(indices)?;
+        score_counts(&counts, FourKindRule::Scale, &self.faces, &[])
+            .map(|sel| sel.with_picks(indices.iter().copied().sorted().dedup().collect()))
+            .map_err(GameError::InvalidSelection)
+    }
+
+    pub fn validate_pick_with(
+        &self,
+        indices: &[usize],
+        rules: &dyn ScoringRules,
+    ) -> Result<Selection, GameError> {
+        let counts = self.counts_for_pick(indices)?;
+        rules
+            .score(&counts)
+            .map(|sel| sel.with_picks(indices.iter().copied().sorted().dedup().collect()))
+            .map_err(GameError::InvalidSelection)
+    }
+
+    pub fn dice(&self) -> &[Die] {
+        &self.dice
+    }
+
+    pub fn dice_values(&self) -> [DieValue; 6] {
+        self.dice.each_ref().map(|die| die.value)
+    }
+
+    pub fn dice_mut(&mut self) -> &mut [Die] {
+        &mut self.dice
+    }
+}
+
+impl Default for Roll {
+    fn default() -> Self {
+        Roll::with_faces(DiceFaces::default())
+    }
+}
+
+pub fn score_counts(
+    chosen: &[usize],
+    four_kind: FourKindRule,
+    faces: &DiceFaces,
+    singles_only: &[DieValue],
+) -> Result<Selection, &'static str> {
+    let mut sel = Selection::default();
+    for (idx, &count) in chosen.iter().enumerate() {
+        let face = idx + 1;
+        let base = set_base_value(face, faces);
+        let sets = !(singles_only.contains(&face) && faces.is_single(face));
+        if count == 4 && sets {
+            sel.components.push(four_kind.component(face, base));
+        } else if count >= 3 && sets {
+            sel.components
+                .push(ScoreComponent::Set { face, count, base });
+        } else if let Some(points) = faces.single_points(face) {
+            for _ in 0..count {
+                sel.components.push(ScoreComponent::Single { face, points });
+            }
+        } else if count > 0 {
+            return Err(if faces.is_standard() {
+                "Can only select 3 or more dice that aren't 1 or 5"
+            } else {
+                "Can only select 3 or more dice that aren't single-scoring faces"
+            });
+        }
+    }
+
+    if sel.value() > 0 {
+        Ok(sel)
+    } else {
+        Err("Selection must have positive value")
+    }
+}
+
+// Higher value wins; between equal values, the selection using fewer dice wins.
+// Remaining ties fall back to the components so only equal selections compare equal.
+impl Ord for Selection {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value()
+            .cmp(&other.value())
+            .then_with(|| other.die_count().cmp(&self.die_count()))
+            .then_with(|| self.components.cmp(&other.components))
+    }
+}
+
+impl PartialOrd for Selection {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Selection {
+    fn eq(&self, other: &Self) -> bool {
+        self.components == other.components
+    }
+}
+
+impl Eq for Selection {}
+
+impl AddAssign for Selection {
+    fn add_assign(&mut self, rhs: Self) {
+        debug_assert!(
+            !self.picks.iter().any(|die| rhs.picks.contains(die)),
+            "Merged selections share a die"
+        );
+        self.components.extend(rhs.components);
+        self.picks.extend(rhs.picks);
+    }
+}
+
+impl Add for Selection {
+    type Output = Selection;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let groups: Vec<_> = self.components.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", groups.join(" | "))
+    }
+}
+
+impl Display for ParseSelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseSelectionError::InvalidDieValue { val } => {
+                write!(f, "\"{}\" is not a die value from 1 to 6", val)
+            }
+            ParseSelectionError::EmptySelection => write!(f, "The selection has no dice"),
+            ParseSelectionError::TooManyDice(count) => {
+                write!(f, "A selection cannot use {} dice", count)
+            }
+            ParseSelectionError::NotScoring(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = ParseSelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut counts = vec![0; DiceFaces::default().count];
+        let mut dice = 0;
+        for val in s.split_whitespace() {
+            match val.parse::<DieValue>() {
+                Ok(value @ 1..=6) => counts[value - 1] += 1,
+                _ => {
+                    return Err(ParseSelectionError::InvalidDieValue {
+                        val: val.to_string(),
+                    })
+                }
+            }
+            dice += 1;
+        }
+        match dice {
+            0 => Err(ParseSelectionError::EmptySelection),
+            1..=6 => StandardRules
+                .score(&counts)
+                .map_err(ParseSelectionError::NotScoring),
+            n => Err(ParseSelectionError::TooManyDice(n)),
+        }
+    }
+}
+
+impl Display for ScoringPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScoringPattern::Triplet { face, count } => {
+                write!(f, "{} of a kind ({}s)", count, face)
+            }
+            ScoringPattern::Singles(singles) => {
+                let parts: Vec<_> = singles
+                    .iter()
+                    .map(|(face, count)| match count {
+                        1 => format!("a single {}", face),
+                        _ => format!("{} single {}s", count, face),
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" and "))
+            }
+            ScoringPattern::Straight => write!(f, "a straight"),
+            ScoringPattern::TriplePair => write!(f, "a triple pair"),
+            ScoringPattern::Mixed(patterns) => {
+                let parts: Vec<_> = patterns.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", parts.join(" + "))
+            }
+        }
+    }
+}
+
+impl Selection {
+    pub fn from_values_str(s: &str) -> Result<Selection, ParseSelectionError> {
+        s.parse()
+    }
+
+    pub fn die_count(&self) -> usize {
+        self.components.iter().map(ScoreComponent::die_count).sum()
+    }
+
+    pub fn dominant_pattern(&self) -> ScoringPattern {
+        let mut patterns = vec![];
+        let (mut singles, mut single_points) = (BTreeMap::<DieValue, u8>::new(), 0);
+        for component in &self.components {
+            let pattern = match *component {
+                ScoreComponent::Straight => ScoringPattern::Straight,
+                ScoreComponent::TriplePair(_) => ScoringPattern::TriplePair,
+                ScoreComponent::Set { face, count, .. } => ScoringPattern::Triplet {
+                    face,
+                    count: count as u8,
+                },
+                ScoreComponent::FourOfAKind { face, .. } => {
+                    ScoringPattern::Triplet { face, count: 4 }
+                }
+                ScoreComponent::Single { face, points } => {
+                    *singles.entry(face).or_default() += 1;
+                    single_points += points;
+                    continue;
+                }
+            };
+            patterns.push((component.value(), pattern));
+        }
+        if !singles.is_empty() {
+            let singles = singles.into_iter().collect();
+            patterns.push((single_points, ScoringPattern::Singles(singles)));
+        }
+        patterns.sort_by_key(|(points, _)| std::cmp::Reverse(*points));
+
+        let mut patterns: Vec<_> = patterns.into_iter().map(|(_, p)| p).collect();
+        if patterns.len() == 1 {
+            patterns.remove(0)
+        } else {
+            ScoringPattern::Mixed(patterns)
+        }
+    }
+
+    pub fn explain(&self) -> String {
+        format!("{}: {} points", self.dominant_pattern(), self.value())
+    }
+
+    fn is_consistent(&self, rules: &dyn ScoringRules) -> bool {
+        let mut counts = vec![0; rules.faces().count];
+        for value in self.values() {
+            match counts.get_mut(value.wrapping_sub(1)) {
+                Some(count) => *count += 1,
+                None => return false,
+            }
+        }
+        if counts.iter().sum::<usize>() > 6 {
+            return false;
+        }
+        match self.components[..] {
+            // Straights and triple pairs cover all six dice, so nothing else can share them
+            [special @ (ScoreComponent::Straight | ScoreComponent::TriplePair(_))] => {
+                ScoreComponent::special_roll(&counts) == Some(special)
+            }
+            _ if self
+                .components
+                .iter()
+                .any(|component| component.die_count() == 6) =>
+            {
+                false
+            }
+            _ => rules.score(&counts).map(|sel| sel.value()) == Ok(self.value()),
+        }
+    }
+
+    fn has_valid_picks(&self, dice: usize) -> bool {
+        self.picks.is_empty()
+            || self.picks.len() == self.die_count()
+                && self.picks.iter().unique().count() == self.picks.len()
+                && self.picks.iter().all(|&idx| idx < dice)
+    }
+
+    fn matches_roll(&self, roll: &Roll) -> bool {
+        let held = self.picks.iter().map(|&idx| roll.dice[idx].value).sorted();
+        self.picks.iter().all(|&idx| roll.dice[idx].picked) && held.eq(self.values().sorted())
+    }
+
+    pub fn is_valid_under(&self, rules: &Ruleset) -> bool {
+        !self.components.is_empty() && self.is_consistent(rules) && self.has_valid_picks(6)
+    }
+
+    pub fn components(&self) -> std::slice::Iter<'_, ScoreComponent> {
+        self.components.iter()
+    }
+
+    pub fn with_picks(mut self, picks: Vec<usize>) -> Self {
+        self.picks = picks;
+        self
+    }
+
+    pub fn picks(&self) -> &[usize] {
+        &self.picks
+    }
+
+    pub fn per_die_value(&self) -> f64 {
+        match self.die_count() {
+            0 => 0.,
+            dice => self.value() as f64 / dice as f64,
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = DieValue> + '_ {
+        self.components.iter().flat_map(ScoreComponent::values)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.components.iter().map(ScoreComponent::value).sum()
+    }
+}
+
+impl Player {
+    pub fn new(name: String) -> Self {
+        Player::with_score(name, 0)
+    }
+
+    pub fn with_score(name: String, starting_score: u32) -> Self {
+        Player {
+            hand: Hand::default(),
+            score: starting_score,
+            name,
+            stats: PlayerStats::default(),
+            #[cfg(feature = "turn-timer")]
+            turn_time: std::time::Duration::ZERO,
+            #[cfg(feature = "turn-timer")]
+            timed_turns: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn stats(&self) -> &PlayerStats {
+        &self.stats
+    }
+
+    pub fn stats_mut(&mut self) -> &mut PlayerStats {
+        &mut self.stats
+    }
+
+    #[cfg(feature = "turn-timer")]
+    pub fn record_turn_time(&mut self, elapsed: std::time::Duration) {
+        self.turn_time += elapsed;
+        self.timed_turns += 1;
+    }
+
+    #[cfg(feature = "turn-timer")]
+    pub fn total_turn_time(&self) -> std::time::Duration {
+        self.turn_time
+    }
+
+    #[cfg(feature = "turn-timer")]
+    pub fn average_turn_time(&self) -> Option<std::time::Duration> {
+        (self.timed_turns > 0).then(|| self.turn_time / self.timed_turns)
+    }
+
+    pub fn forfeit_hand(&mut self) -> u32 {
+        let lost = self.hand_value();
+        self.hand.clear();
+        lost
+    }
+
+    #[deprecated(note = "use forfeit_hand, which reports the points thrown away")]
+    pub fn empty_hand(&mut self) {
+        self.forfeit_hand();
+    }
+
+    pub fn selections(&self) -> std::slice::Iter<'_, Selection> {
+        self.hand.iter()
+    }
+
+    pub fn take_hand(&mut self) -> Hand {
+        std::mem::take(&mut self.hand)
+    }
+
+    pub fn receive_hand(&mut self, hand: Hand) {
+        self.hand.extend(hand);
+    }
+
+    pub fn add_selection(&mut self, selection: Selection) {
+        self.hand.push(selection);
+    }
+
+    pub fn undo_selection(&mut self) -> Option<Selection> {
+        self.hand.pop()
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &ScoreComponent> {
+        self.hand.iter().flat_map(Selection::components)
+    }
+
+    pub fn wipe_score(&mut self) -> u32 {
+        self.hand.clear();
+        std::mem::take(&mut self.score)
+    }
+
+    pub fn award(&mut self, points: u32) {
+        self.score += points;
+    }
+
+    pub fn penalize(&mut self, points: u32) {
+        self.score = self.score.saturating_sub(points);
+    }
+
+    pub fn validate_state(&self, faces: &DiceFaces) -> Result<(), GameError> {
+        for (idx, sel) in self.hand.iter().enumerate() {
+            if sel.value() == 0 {
+                return Err(GameError::CorruptedState {
+                    field: format!("hand[{}]", idx),
+                    reason: "selection has no value".to_string(),
+                });
+            }
+            if let Some(value) = sel.values().find(|v| !(1..=faces.count).contains(v)) {
+                return Err(GameError::CorruptedState {
+                    field: format!("hand[{}]", idx),
+                    reason: format!("{} is not a face of a {}-sided die", value, faces.count),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_hand(&self, rules: &dyn ScoringRules, roll: &Roll) -> bool {
+        if !self
+            .hand
+            .iter()
+            .all(|sel| sel.is_consistent(rules) && sel.has_valid_picks(roll.dice.len()))
+        {
+            return false;
+        }
+        // Only the selections since the last hot dice still sit on the held dice
+        let mut held = vec![];
+        let mut count = 0;
+        for sel in &self.hand {
+            if count == roll.dice.len() {
+                held.clear();
+                count = 0;
+            }
+            count += sel.die_count();
+            held.push(sel);
+        }
+        held.iter()
+            .all(|sel| sel.picks.is_empty() || sel.matches_roll(roll))
+    }
+
+    pub fn hand_values(&self) -> impl Iterator<Item = u32> + '_ {
+        self.hand.iter().map(Selection::value)
+    }
+
+    pub fn hand_value(&self) -> u32 {
+        self.hand_values().sum()
+    }
+
+    pub fn effective_score(&self) -> u32 {
+        self.score + self.hand_value()
+    }
+
+    pub fn has_uncommitted_points(&self) -> bool {
+        self.hand_value() > 0
+    }
+
+    pub fn projected_final_score(&self, turns_remaining: u32) -> u32 {
+        self.projected_final_score_with_fallback(turns_remaining, FALLBACK_TURN_AVERAGE)
+    }
+
+    pub fn projected_final_score_with_fallback(&self, turns_remaining: u32, fallback: u32) -> u32 {
+        let average = if self.stats.turns() < MIN_TURNS_FOR_AVERAGE {
+            fallback
+        } else {
+            self.stats.avg_score_per_turn() as u32
+        };
+        self.score + self.hand_value() + turns_remaining * average
+    }
+
+    pub fn is_on_pace_to_win(&self, other_players: &[Player], turns_remaining: u32) -> bool {
+        let projected = self.projected_final_score(turns_remaining);
+        other_players
+            .iter()
+            .all(|other| other.projected_final_score(turns_remaining) <= projected)
+    }
+
+    pub fn kept_values_this_turn(&self) -> Vec<DieValue> {
+        self.hand.iter().flat_map(Selection::values).collect()
+    }
+
+    pub fn dice_in_hand(&self) -> usize {
+        self.hand.iter().map(Selection::die_count).sum()
+    }
+
+    pub fn bank(&mut self, rules: &Ruleset) -> u32 {
+        let total = rules.round_bank(self.hand_value());
+        self.score += total;
+        self.hand.clear();
+        total
+    }
+}
+
+impl Ord for Player {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.score.cmp(&other.score)
+    }
+}
+
+impl PartialOrd for Player {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score
+    }
+}
+
+impl Eq for Player {}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::rules::{Rounding, Ruleset, StandardRules};
+
+    fn classic_score(counts: &[usize]) -> Option<u32> {
+        let mut value = 0;
+        for (idx, &count) in counts.iter().enumerate() {
+            let face = idx as u32 + 1;
+            value += match (face, count) {
+                (1, 0..=2) => 100 * count as u32,
+                (5, 0..=2) => 50 * count as u32,
+                (1, _) => 1000 * (count as u32 - 2),
+                (5, _) => 500 * (count as u32 - 2),
+                (_, 0) => 0,
+                (_, 1..=2) => return None,
+                _ => face * 100 * (count as u32 - 2),
+            };
+        }
+        (value > 0).then_some(value)
+    }
+
+    fn pick_counts() -> Vec<Vec<usize>> {
+        (0..7usize.pow(6))
+            .map(|mut code| {
+                (0..6)
+                    .map(|_| {
+                        let count = code % 7;
+                        code /= 7;
+                        count
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|counts| (1..=6).contains(&counts.iter().sum::<usize>()))
+            .collect()
+    }
+
+    fn all_rolls() -> impl Iterator<Item = [DieValue; 6]> {
+        (0..6usize.pow(6)).map(|mut code| {
+            core::array::from_fn(|_| {
+                let value = code % 6 + 1;
+                code /= 6;
+                value
+            })
+        })
+    }
+
+    #[test]
+    fn six_face_scoring_matches_the_classic_rules() {
+        for counts in pick_counts() {
+            let expected = classic_score(&counts);
+            let scored = [
+                Ruleset::default().score(&counts),
+                StandardRules.score(&counts),
+                score_counts(&counts, FourKindRule::Scale, &DiceFaces::default(), &[]),
+            ];
+            for selection in scored {
+                assert_eq!(selection.map(|s| s.value()).ok(), expected, "{:?}", counts);
+            }
+        }
+    }
+
+    #[test]
+    fn six_face_rolls_pick_like_the_classic_rules() {
+        for values in all_rolls() {
+            let mut roll = Roll::default();
+            for (die, value) in roll.dice.iter_mut().zip(values) {
+                die.value = value;
+            }
+            let counts = roll.count_values();
+            let expected = values.map(|v| v == 1 || v == 5 || counts[v - 1] >= 3);
+            assert_eq!(roll.determine_pickable(None), expected, "{:?}", values);
+        }
+    }
+
+    #[test]
+    fn picks_lock_in_when_the_dice_are_rerolled() {
+        let mut roll = Roll::default();
+        for (die, value) in roll.dice.iter_mut().zip([1, 5, 2, 3, 4, 6]) {
+            die.value = value;
+        }
+        roll.toggle_die(0);
+        roll.toggle_die(1);
+        assert_eq!(roll.current_roll_picks(), [0, 1]);
+        assert!(roll.prior_roll_picks().is_empty());
+
+        roll.new_roll_with_rng(&mut StdRng::seed_from_u64(1));
+        assert!(roll.current_roll_picks().is_empty());
+        assert_eq!(roll.prior_roll_picks(), [0, 1]);
+        assert_eq!((roll.dice[0].value, roll.dice[1].value), (1, 5));
+        roll.dice[2].value = 1;
+        roll.toggle_die(2);
+        assert_eq!(roll.current_roll_picks(), [2]);
+        assert_eq!(roll.prior_roll_picks(), [0, 1]);
+
+        roll.lock_current_picks();
+        assert!(roll.current_roll_picks().is_empty());
+        assert_eq!(roll.prior_roll_picks(), [0, 1, 2]);
+    }
+
+    fn claimed(components: Vec<ScoreComponent>, picks: Vec<usize>) -> Selection {
+        Selection { components, picks }
+    }
+
+    fn hand_of(selections: Vec<Selection>) -> Player {
+        let mut player = Player::new("Ann".to_string());
+        for selection in selections {
+            player.add_selection(selection);
+        }
+        player
+    }
+
+    #[test]
+    fn hands_cannot_claim_extra_special_rolls() {
+        let rules = Ruleset::default();
+        let roll = roll_of(DiceFaces::default(), [1, 2, 3, 4, 5, 6]);
+        let one = ScoreComponent::Single {
+            face: 1,
+            points: 100,
+        };
+        let ones = ScoreComponent::Set {
+            face: 1,
+            count: 3,
+            base: 1000,
+        };
+        let straight = claimed(vec![ScoreComponent::Straight], vec![]);
+        assert!(hand_of(vec![straight]).validate_hand(&rules, &roll));
+        for components in [
+            vec![ScoreComponent::Straight, ScoreComponent::Straight],
+            vec![ScoreComponent::Straight, ones],
+            vec![one, ScoreComponent::Straight],
+            vec![ScoreComponent::TriplePair([2, 3, 3])],
+        ] {
+            let player = hand_of(vec![claimed(components.clone(), vec![])]);
+            assert!(!player.validate_hand(&rules, &roll), "{:?}", components);
+        }
+    }
+
+    #[test]
+    fn hands_must_match_the_held_dice() {
+        let rules = Ruleset::default();
+        let mut roll = roll_of(DiceFaces::default(), [1, 5, 2, 3, 4, 4]);
+        roll.toggle_die(1);
+        let five = ScoreComponent::Single {
+            face: 5,
+            points: 50,
+        };
+        let one = ScoreComponent::Single {
+            face: 1,
+            points: 100,
+        };
+        assert!(hand_of(vec![claimed(vec![five], vec![1])]).validate_hand(&rules, &roll));
+        assert!(!hand_of(vec![claimed(vec![one], vec![1])]).validate_hand(&rules, &roll));
+        assert!(!hand_of(vec![claimed(vec![five], vec![0])]).validate_hand(&rules, &roll));
+        assert!(!hand_of(vec![claimed(vec![five], vec![1, 1])]).validate_hand(&rules, &roll));
+        assert!(!hand_of(vec![claimed(vec![five], vec![6])]).validate_hand(&rules, &roll));
+
+        // After hot dice the earlier selections' dice are rolled again
+        let ones = ScoreComponent::Set {
+            face: 1,
+            count: 3,
+            base: 1000,
+        };
+        let fives = ScoreComponent::Set {
+            face: 5,
+            count: 3,
+            base: 500,
+        };
+        let player = hand_of(vec![
+            claimed(vec![ones], vec![0, 2, 3]),
+            claimed(vec![fives], vec![1, 4, 5]),
+            claimed(vec![five], vec![1]),
+        ]);
+        assert!(player.validate_hand(&rules, &roll));
+    }
+
+    fn roll_of(faces: DiceFaces, values: [DieValue; 6]) -> Roll {
+        let mut roll = Roll::with_faces(faces);
+        for (die, value) in roll.dice.iter_mut().zip(values) {
+            die.value = value;
+        }
+        roll
+    }
+
+    fn corrupted_field(result: Result<(), GameError>) -> String {
+        match result {
+            Err(GameError::CorruptedState { field, .. }) => field,
+            other => panic!("Expected a corrupted state, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn corrupted_rolls_fail_validation() {
+        let picked = || {
+            let mut roll = roll_of(DiceFaces::default(), [1, 5, 2, 3, 4, 6]);
+            roll.toggle_die(0);
+            roll
+        };
+        assert_eq!(picked().validate_state(), Ok(()));
+
+        let mut bad = picked();
+        bad.dice[2].value = 0;
+        assert_eq!(corrupted_field(bad.validate_state()), "dice[2].value");
+        let mut bad = picked();
+        bad.dice[5].value = 7;
+        assert_eq!(corrupted_field(bad.validate_state()), "dice[5].value");
+        let mut bad = picked();
+        bad.dice[3].picked_this_roll = true;
+        assert_eq!(
+            corrupted_field(bad.validate_state()),
+            "dice[3].picked_this_roll"
+        );
+
+        let d8 = roll_of(DiceFaces::d8(), [8, 8, 8, 7, 1, 2]);
+        assert_eq!(d8.validate_state(), Ok(()));
+    }
+
+    #[test]
+    fn corrupted_hands_fail_validation() {
+        let faces = DiceFaces::default();
+        let one = ScoreComponent::Single {
+            face: 1,
+            points: 100,
+        };
+        let player = hand_of(vec![claimed(vec![one], vec![])]);
+        assert_eq!(player.validate_state(&faces), Ok(()));
+
+        let bad = hand_of(vec![claimed(vec![one], vec![]), Selection::default()]);
+        assert_eq!(corrupted_field(bad.validate_state(&faces)), "hand[1]");
+
+        let seven = ScoreComponent::Single {
+            face: 7,
+            points: 50,
+        };
+        let bad = hand_of(vec![
+            claimed(vec![one], vec![]),
+            claimed(vec![seven], vec![]),
+        ]);
+        assert_eq!(corrupted_field(bad.validate_state(&faces)), "hand[1]");
+        assert_eq!(bad.validate_state(&DiceFaces::d8()), Ok(()));
+    }
+
+    #[test]
+    fn dominant_pattern_names_the_scoring_dice() {
+        let rules = Ruleset::default();
+        let mixed = rules.score(&[1, 3, 0, 0, 0, 0]).unwrap();
+        assert_eq!(
+            mixed.dominant_pattern(),
+            ScoringPattern::Mixed(vec![
+                ScoringPattern::Triplet { face: 2, count: 3 },
+                ScoringPattern::Singles(vec![(1, 1)]),
+            ])
+        );
+        assert_eq!(mixed.explain(), "3 of a kind (2s) + a single 1: 300 points");
+
+        let (straight, _) = roll_of(DiceFaces::default(), [1, 2, 3, 4, 5, 6]).determine_type();
+        assert_eq!(straight.dominant_pattern(), ScoringPattern::Straight);
+        assert_eq!(straight.explain(), "a straight: 3000 points");
+
+        let ones = rules.score(&[3, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(
+            ones.dominant_pattern(),
+            ScoringPattern::Triplet { face: 1, count: 3 }
+        );
+        assert_eq!(ones.explain(), "3 of a kind (1s): 1000 points");
+    }
+
+    #[test]
+    fn dominant_pattern_uses_the_single_faces_of_the_dice() {
+        let d8 = Ruleset {
+            faces: DiceFaces::d8(),
+            ..Ruleset::default()
+        };
+        let sel = d8.score(&[1, 0, 0, 0, 0, 0, 0, 2]).unwrap();
+        assert_eq!(
+            sel.dominant_pattern(),
+            ScoringPattern::Singles(vec![(1, 1), (8, 2)])
+        );
+        assert_eq!(sel.explain(), "a single 1 and 2 single 8s: 200 points");
+    }
+
+    #[test]
+    fn triple_pairs_take_the_whole_roll() {
+        let mut roll = roll_of(DiceFaces::default(), [4, 2, 3, 2, 4, 3]);
+        let (selection, roll_type) = roll.determine_type();
+        assert_eq!(roll_type, RollType::TriplePair);
+        assert_eq!(selection.value(), TRIPLE_PAIR_VALUE);
+        assert_eq!(
+            selection.components().collect::<Vec<_>>(),
+            [&ScoreComponent::TriplePair([2, 3, 4])]
+        );
+        assert!(roll.is_exhausted());
+
+        let (_, roll_type) = roll_of(DiceFaces::default(), [1, 1, 5, 5, 6, 6]).determine_type();
+        assert_eq!(roll_type, RollType::TriplePair);
+
+        let mut roll = roll_of(DiceFaces::d8(), [7, 2, 8, 7, 2, 8]);
+        assert_eq!(roll.determine_type().1, RollType::TriplePair);
+    }
+
+    #[test]
+    fn near_triple_pairs_are_not_special() {
+        for values in [[2, 2, 2, 2, 3, 3], [2, 2, 3, 3, 4, 6], [6, 6, 6, 6, 6, 6]] {
+            let (_, roll_type) = roll_of(DiceFaces::default(), values).determine_type();
+            assert_ne!(roll_type, RollType::TriplePair, "{:?}", values);
+        }
+        let (_, roll_type) = roll_of(DiceFaces::default(), [6, 2, 5, 1, 3, 4]).determine_type();
+        assert_eq!(roll_type, RollType::Straight);
+    }
+
+    #[test]
+    fn hot_dice_reset_clears_every_flag() {
+        let mut roll = roll_of(DiceFaces::default(), [1, 1, 1, 5, 5, 5]);
+        assert_eq!(roll.determine_type().1, RollType::Simple);
+        for die in 0..6 {
+            assert_eq!(roll.toggle_die(die), ToggleResult::Picked);
+        }
+        assert!(roll.is_exhausted());
+
+        roll.release_all();
+        let values = [2, 2, 3, 3, 4, 4];
+        roll.reroll_with(|idx| values[idx]);
+        assert!(roll
+            .dice()
+            .iter()
+            .all(|die| !die.picked() && !die.picked_this_roll()));
+        assert_eq!(roll.count_values(), [0, 2, 2, 2, 0, 0]);
+        assert_eq!(roll.determine_type().1, RollType::TriplePair);
+    }
+
+    #[test]
+    #[should_panic(expected = "Die picked this roll without being picked")]
+    fn stray_pick_flags_are_caught_in_debug_builds() {
+        let mut roll = roll_of(DiceFaces::default(), [1, 2, 3, 4, 6, 6]);
+        roll.dice[0].picked_this_roll = true;
+        roll.toggle_die(1);
+    }
+
+    #[test]
+    fn banking_rounds_the_hand() {
+        for (rounding, banked) in [
+            (Rounding::Nearest, 500),
+            (Rounding::Up, 500),
+            (Rounding::Down, 400),
+        ] {
+            let rules = Ruleset {
+                bank_rounding: Some(100),
+                rounding,
+                ..Default::default()
+            };
+            let mut player = Player::new("Ann".to_string());
+            player.add_selection("4 4 4 5".parse().unwrap());
+            assert_eq!(player.bank(&rules), banked);
+            assert_eq!(player.score(), banked);
+            assert_eq!(player.hand_value(), 0);
+        }
+    }
+
+    fn selection(values: &[DieValue]) -> Selection {
+        let mut counts = [0; 6];
+        for &value in values {
+            counts[value - 1] += 1;
+        }
+        Ruleset::default().score(&counts).unwrap()
+    }
+
+    #[test]
+    fn selections_order_by_value_then_fewer_dice() {
+        assert!(selection(&[1, 5]) > selection(&[1]));
+        assert!(selection(&[1]) > selection(&[5, 5]));
+        assert_eq!(
+            selection(&[1]).cmp(&selection(&[5, 5])),
+            std::cmp::Ordering::Greater
+        );
+        let picks = [selection(&[5, 5]), selection(&[1]), selection(&[1, 5])];
+        assert_eq!(picks.iter().max(), Some(&selection(&[1, 5])));
+    }
+
+    #[test]
+    fn equal_value_selections_are_still_distinct() {
+        let (set, singles) = (selection(&[2, 2, 2]), selection(&[1, 5, 5]));
+        assert_eq!(set.value(), singles.value());
+        assert_ne!(set, singles);
+        assert_ne!(set.cmp(&singles), std::cmp::Ordering::Equal);
+        assert_eq!(set.cmp(&singles), singles.cmp(&set).reverse());
+        assert!(![set].contains(&singles));
+        assert_eq!(selection(&[5, 1]), selection(&[1, 5]));
+    }
+
+    #[test]
+    fn merging_a_set_with_singles() {
+        let roll = roll_of(DiceFaces::default(), [1, 5, 4, 4, 4, 2]);
+        let set = roll.validate_pick(&[2, 3, 4]).unwrap();
+        let singles = roll.validate_pick(&[0, 1]).unwrap();
+        let merged = set.clone() + singles.clone();
+        assert_eq!(merged.value(), 550);
+        assert_eq!(merged.die_count(), 5);
+        assert_eq!(merged.per_die_value(), 110.);
+        assert_eq!(merged.picks(), [2, 3, 4, 0, 1]);
+        assert_eq!(
+            merged.components().copied().collect::<Vec<_>>(),
+            set.components()
+                .chain(singles.components())
+                .copied()
+                .collect::<Vec<_>>()
+        );
+
+        let mut total = singles;
+        total += set;
+        assert_eq!(total.value(), 550);
+    }
+
+    #[test]
+    #[should_panic(expected = "Merged selections share a die")]
+    fn merging_selections_that_share_a_die_panics() {
+        let roll = roll_of(DiceFaces::default(), [1, 5, 4, 4, 4, 2]);
+        let _ = roll.validate_pick(&[0]).unwrap() + roll.validate_pick(&[0, 1]).unwrap();
+    }
+
+    #[test]
+    fn take_accepts_faces_beyond_six() {
+        assert_eq!("take 8".parse(), Ok(GameAction::TakeValue(8)));
+        assert_eq!("take 0".parse::<GameAction>(), Err(UnknownAction));
+        assert_eq!("take x".parse::<GameAction>(), Err(UnknownAction));
+    }
+
+    #[test]
+    fn inflated_selections_are_invalid() {
+        let lone_five = |points| Selection {
+            components: vec![ScoreComponent::Single { face: 5, points }],
+            picks: vec![],
+        };
+        let rules = Ruleset::default();
+        assert!(lone_five(50).is_valid_under(&rules));
+        assert!(!lone_five(9999).is_valid_under(&rules));
+        assert!(!Selection::default().is_valid_under(&rules));
+
+        let inflated_set = Selection {
+            components: vec![ScoreComponent::Set {
+                face: 2,
+                count: 3,
+                base: 9999,
+            }],
+            picks: vec![],
+        };
+        assert!(!inflated_set.is_valid_under(&rules));
+
+        let straight = ScoreComponent::Straight;
+        let ones = ScoreComponent::Set {
+            face: 1,
+            count: 3,
+            base: 1000,
+        };
+        let pairs = ScoreComponent::TriplePair([2, 3, 4]);
+        let five = ScoreComponent::Single {
+            face: 5,
+            points: 50,
+        };
+        assert!(claimed(vec![straight], vec![]).is_valid_under(&rules));
+        assert!(claimed(vec![pairs], (0..6).collect()).is_valid_under(&rules));
+        for components in [
+            vec![straight, straight],
+            vec![straight, ones],
+            vec![pairs, pairs],
+            vec![ScoreComponent::TriplePair([2, 2, 4])],
+            vec![ones, ones, five],
+            vec![five; 7],
+        ] {
+            let selection = claimed(components, vec![]);
+            assert!(!selection.is_valid_under(&rules), "{:?}", selection);
+        }
+        assert!(!claimed(vec![ones], vec![0, 1]).is_valid_under(&rules));
+        assert!(!claimed(vec![five], vec![6]).is_valid_under(&rules));
+    }
+
+    fn player_with_history(name: &str, turns: &[u32]) -> Player {
+        let mut player = Player::new(name.to_string());
+        player.award(turns.iter().sum());
+        for &banked in turns {
+            player.stats_mut().record_turn(banked);
+        }
+        player
+    }
+
+    #[test]
+    fn projections_fall_back_until_there_is_history() {
+        let fresh = player_with_history("Ann", &[]);
+        assert_eq!(fresh.projected_final_score(10), 10 * FALLBACK_TURN_AVERAGE);
+        assert_eq!(fresh.projected_final_score_with_fallback(10, 250), 2500);
+
+        let started = player_with_history("Ann", &[1000, 0]);
+        assert_eq!(started.projected_final_score_with_fallback(4, 250), 2000);
+    }
+
+    #[test]
+    fn projections_use_the_average_turn() {
+        let mut ann = player_with_history("Ann", &[500, 300, 400]);
+        assert_eq!(ann.projected_final_score(5), 1200 + 5 * 400);
+        assert_eq!(ann.projected_final_score_with_fallback(5, 0), 3200);
+        ann.add_selection(selection(&[1]));
+        assert_eq!(ann.projected_final_score(5), 3300);
+        assert_eq!(ann.projected_final_score(0), 1300);
+    }
+
+    #[test]
+    fn pace_compares_projections() {
+        let players = [
+            player_with_history("Ann", &[500, 300, 400]),
+            player_with_history("Bob", &[900, 0, 0]),
+            player_with_history("Cid", &[1000, 1000, 1000]),
+        ];
+        let [ann, bob, cid] = &players;
+        assert!(ann.is_on_pace_to_win(std::slice::from_ref(bob), 5));
+        assert!(!bob.is_on_pace_to_win(std::slice::from_ref(ann), 5));
+        assert!(!ann.is_on_pace_to_win(&players[1..], 5));
+        assert!(cid.is_on_pace_to_win(&players[..2], 0));
+        assert!(ann.is_on_pace_to_win(&[], 5));
+        assert!(ann.is_on_pace_to_win(std::slice::from_ref(ann), 5));
+    }
+
+    #[test]
+    fn value_lists_follow_the_picks() {
+        let mut roll = roll_of(DiceFaces::default(), [2, 1, 5, 2, 2, 6]);
+        let iterated: Vec<_> = roll.dice().iter().map(Die::value).collect();
+        assert_eq!(roll.dice_values().to_vec(), iterated);
+        assert_eq!(roll.unpicked_values(), [2, 1, 5, 2, 2, 6]);
+        assert!(roll.picked_values().is_empty());
+
+        roll.toggle_die(1);
+        roll.toggle_die(0);
+        roll.toggle_die(3);
+        roll.toggle_die(4);
+        assert_eq!(roll.unpicked_values(), [5, 6]);
+        assert_eq!(roll.picked_values(), [2, 1, 2, 2]);
+
+        let values = [0, 0, 3, 0, 0, 4];
+        roll.reroll_with(|idx| values[idx]);
+        let iterated: Vec<_> = roll.dice().iter().map(Die::value).collect();
+        assert_eq!(roll.dice_values().to_vec(), iterated);
+        assert_eq!(roll.dice_values(), [2, 1, 3, 2, 2, 4]);
+        assert_eq!(roll.unpicked_values(), [3, 4]);
+        assert_eq!(roll.picked_values(), [2, 1, 2, 2]);
+
+        roll.release_all();
+        assert_eq!(roll.unpicked_values(), roll.dice_values());
+        assert!(roll.picked_values().is_empty());
+    }
+
+    #[test]
+    fn rerolls_need_a_value_for_every_unpicked_die() {
+        let mut roll = roll_of(DiceFaces::default(), [1, 2, 3, 4, 5, 6]);
+        roll.toggle_die(0);
+        assert_eq!(
+            roll.new_roll_from_values(&[2, 2]),
+            Err(GameError::WrongDiceCount {
+                expected: 5,
+                given: 2
+            })
+        );
+        assert_eq!(
+            roll.new_roll_from_values(&[2; 6]),
+            Err(GameError::WrongDiceCount {
+                expected: 5,
+                given: 6
+            })
+        );
+        assert_eq!(roll.dice_values(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(roll.new_roll_from_values(&[6; 5]), Ok(()));
+        assert_eq!(roll.dice_values(), [1, 6, 6, 6, 6, 6]);
+    }
+
+    #[test]
+    fn only_three_or_more_make_a_triple() {
+        assert!(!Die::is_scoreable_triple(2));
+        assert!(Die::is_scoreable_triple(3));
+        assert!(Die::is_scoreable_triple(6));
+    }
+
+    fn picked_selection(values: &[DieValue]) -> Result<Selection, &'static str> {
+        let mut padded = [6; 6];
+        padded[..values.len()].copy_from_slice(values);
+        let mut roll = roll_of(DiceFaces::default(), padded);
+        for die in &mut roll.dice[..values.len()] {
+            die.pick();
+        }
+        roll.construct_selection()
+    }
+
+    #[test]
+    fn parsed_selections_match_picked_dice() {
+        let cases = [
+            "1",
+            "5",
+            "1 5",
+            "1 1",
+            "5 5",
+            "1 1 5 5",
+            "1 1 1",
+            "2 2 2",
+            "3 3 3",
+            "4 4 4",
+            "5 5 5",
+            "6 6 6",
+            "2 2 2 1",
+            "3 3 3 5 5",
+            "1 1 1 1",
+            "2 2 2 2",
+            "5 5 5 5 5",
+            "6 6 6 6 6 6",
+            "1 1 1 5 5 5",
+            "4 4 4 1 5",
+            "  1   5  ",
+        ];
+        for case in cases {
+            let values: Vec<DieValue> = case
+                .split_whitespace()
+                .map(|v| v.parse().unwrap())
+                .collect();
+            let parsed: Selection = case.parse().unwrap();
+            let picked = picked_selection(&values).unwrap();
+            assert_eq!(parsed.value(), picked.value(), "{:?}", case);
+            assert_eq!(parsed.die_count(), values.len(), "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn bad_selections_fail_to_parse() {
+        let invalid = |val: &str| ParseSelectionError::InvalidDieValue {
+            val: val.to_string(),
+        };
+        let cases = [
+            ("", ParseSelectionError::EmptySelection),
+            ("   ", ParseSelectionError::EmptySelection),
+            ("0", invalid("0")),
+            ("7", invalid("7")),
+            ("1 x", invalid("x")),
+            ("-1", invalid("-1")),
+            ("1,5", invalid("1,5")),
+            ("1 1 1 1 1 1 1", ParseSelectionError::TooManyDice(7)),
+        ];
+        for (case, error) in cases {
+            assert_eq!(case.parse::<Selection>().unwrap_err(), error, "{:?}", case);
+        }
+        for case in ["2", "3 3", "1 2", "5 5 6 6"] {
+            let values: Vec<DieValue> = case
+                .split_whitespace()
+                .map(|v| v.parse().unwrap())
+                .collect();
+            let reason = picked_selection(&values).unwrap_err();
+            assert_eq!(
+                case.parse::<Selection>().unwrap_err(),
+                ParseSelectionError::NotScoring(reason),
+                "{:?}",
+                case
+            );
+        }
+    }
+
+    #[test]
+    fn dice_roll_their_own_faces() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        assert_eq!(Die::new_with_value(3).faces(), &[1, 2, 3, 4, 5, 6]);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut die = Die::with_faces(1, Arc::new([1, 2, 4, 8]));
+        let mut seen = [false; 9];
+        for _ in 0..1000 {
+            die.roll_with_rng(&mut rng);
+            assert!([1, 2, 4, 8].contains(&die.value()));
+            seen[die.value()] = true;
+        }
+        assert_eq!(
+            seen,
+            [false, true, true, false, true, false, false, false, true]
+        );
+
+        let d8 = Roll::with_faces(DiceFaces::d8());
+        assert!(d8
+            .dice()
+            .iter()
+            .all(|die| die.faces() == [1, 2, 3, 4, 5, 6, 7, 8]));
+        let d40 = Roll::with_faces(DiceFaces {
+            count: 40,
+            singles: vec![],
+        });
+        assert_eq!(d40.dice()[0].faces(), (1..=40).collect::<Vec<_>>());
+        assert!(d8.dice().iter().all(|die| die.faces().len() == 8));
+        assert!(Arc::ptr_eq(&d8.dice[0].faces, &d8.dice[5].faces));
+        assert_eq!(Roll::default().dice()[0].faces(), STANDARD_FACES);
+    }
+
+    #[cfg(not(feature = "debug-dice"))]
+    #[test]
+    fn standard_rolls_draw_as_before() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut roll = Roll::default();
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut expected = StdRng::seed_from_u64(11);
+        for _ in 0..500 {
+            roll.new_roll_with_rng(&mut rng);
+            let values: [DieValue; 6] = core::array::from_fn(|_| expected.gen_range(1..=6));
+            assert_eq!(roll.dice_values(), values);
+            assert!(roll.dice().iter().all(|die| die.faces() == STANDARD_FACES));
+        }
+    }
+}
@@ -0,0 +1,3096 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::VecDeque, fmt::Display};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[cfg(feature = "debug-dice")]
+use crate::debug_dice::DebugDice;
+use crate::farkle::*;
+use crate::notation::{self, NotationError, TurnToken};
+use crate::odds::Recommendation;
+use crate::order::{RollOff, RoundOrder, TurnOrder};
+use crate::rules::*;
+use crate::stats::RollStats;
+use crate::team::{Team, TeamScoring};
+
+const DEFAULT_PLAYERS: usize = 2;
+const DEFAULT_TURNS: u32 = 10;
+
+#[derive(Debug, Clone, Default)]
+pub struct GameConfig {
+    pub player_names: Vec<String>,
+    pub turns: u32,
+    pub seed: Option<u64>,
+    pub rules: Ruleset,
+    pub roll_off: bool,
+    pub roll_seed: Option<u64>,
+    pub handicaps: Vec<u32>,
+    pub round_order: RoundOrder,
+    pub turn_order: TurnOrder,
+    pub teams: Vec<Team>,
+    pub team_scoring: TeamScoring,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GameEvent {
+    Rolled { roll_type: RollType, value: u32 },
+    Toggled(ToggleResult),
+    Taken(usize),
+    Selected(u32),
+    Unpicked,
+    Banked(u32),
+    Farkled { lost: u32 },
+    ScoreWiped { player: usize, lost: u32 },
+    Forfeited,
+    Passed { from: usize, to: usize },
+    PotClaimed(u32),
+    Special(SpecialEvent),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GameError {
+    IllegalAction {
+        action: GameAction,
+        state: GameState,
+    },
+    InvalidSelection(&'static str),
+    DieOutOfRange(usize),
+    DieAlreadyPicked(usize),
+    NoSuchFace(DieValue),
+    NoDiceLeft,
+    WrongDiceCount {
+        expected: usize,
+        given: usize,
+    },
+    GameOver,
+    NoSuchPlayer(usize),
+    PlayerAlreadyLeft(usize),
+    NoRoundsLeft,
+    BetweenTurns,
+    RollLimitReached,
+    NotATeammate(usize),
+    AlreadyPassed,
+    CorruptedState {
+        field: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    InvalidEnvVar {
+        var: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSummary {
+    turns: u32,
+    standings: Vec<(String, u32)>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Standing {
+    pub player: usize,
+    pub score: u32,
+    pub behind: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize))]
+pub struct DieSnapshot {
+    pub value: DieValue,
+    pub picked: bool,
+    pub picked_this_roll: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize))]
+pub struct PlayerSnapshot {
+    pub name: String,
+    pub score: u32,
+    pub hand: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize))]
+pub struct GameSnapshot {
+    pub dice: Vec<DieSnapshot>,
+    pub players: Vec<PlayerSnapshot>,
+    pub current_player: usize,
+    pub current_turn: u32,
+    pub turns: u32,
+    pub state: GameState,
+    pub pot: Option<u32>,
+    pub rolls_this_turn: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TurnStep {
+    Rolled {
+        values: Vec<DieValue>,
+        roll_type: RollType,
+        value: u32,
+    },
+    Picked {
+        values: Vec<DieValue>,
+        value: u32,
+    },
+    Unpicked,
+    Passed {
+        to: usize,
+        name: String,
+    },
+    MissedPoints {
+        taken: u32,
+        best: u32,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TurnOutcome {
+    Banked(u32),
+    Farkled(u32),
+    Wiped(u32),
+    Forfeited,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TurnRecord {
+    pub player: String,
+    pub turn: u32,
+    pub rolls: usize,
+    pub steps: Vec<TurnStep>,
+    pub outcome: Option<TurnOutcome>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RollRecap {
+    pub values: Vec<DieValue>,
+    pub kept: Vec<DieValue>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TurnSummary {
+    pub player: String,
+    pub rolls: usize,
+    pub roll_history: Vec<RollRecap>,
+    pub selections: Vec<(Vec<DieValue>, u32)>,
+    pub hot_dice: usize,
+    pub passed_to: Vec<String>,
+    pub hand: u32,
+    pub dice_kept: usize,
+    pub outcome: Option<TurnOutcome>,
+}
+
+#[derive(Debug)]
+pub struct TranscriptError {
+    line: usize,
+    text: String,
+    reason: String,
+}
+
+#[derive(Debug)]
+pub struct Playback {
+    moves: Vec<(usize, String)>,
+    next: usize,
+    farkled: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    config: GameConfig,
+    seed: u64,
+    rng: StdRng,
+    players: Vec<Player>,
+    departed: Vec<bool>,
+    joining: Vec<Player>,
+    seats: Vec<usize>,
+    seat: usize,
+    overtime: u32,
+    contenders: Vec<usize>,
+    roll: Roll,
+    state: GameState,
+    current_player: usize,
+    current_turn: u32,
+    pot: u32,
+    events: Vec<GameEvent>,
+    transcript: Vec<String>,
+    roll_off: Option<RollOff>,
+    entry_order: Vec<usize>,
+    teams: Vec<Team>,
+    rolls: usize,
+    rolls_this_turn: usize,
+    turn_log: Vec<TurnRecord>,
+    roll_stats: RollStats,
+    scripted_rolls: VecDeque<Vec<DieValue>>,
+    #[cfg(feature = "turn-timer")]
+    turn_started: std::time::Instant,
+    #[cfg(feature = "debug-dice")]
+    debug_dice: DebugDice,
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::IllegalAction { action, state } => {
+                write!(f, "Cannot perform {:?} in state {:?}", action, state)
+            }
+            GameError::InvalidSelection(e) => write!(f, "The selection is invalid: {}", e),
+            GameError::DieOutOfRange(idx) => write!(f, "There is no die {}", idx + 1),
+            GameError::DieAlreadyPicked(idx) => {
+                write!(f, "Die {} was picked on an earlier roll", idx + 1)
+            }
+            GameError::NoSuchFace(value) => write!(f, "The dice have no face {}", value),
+            GameError::NoDiceLeft => write!(f, "No dice left to roll — you must bank."),
+            GameError::WrongDiceCount { expected, given } => {
+                write!(f, "Expected {} dice but got {}", expected, given)
+            }
+            GameError::GameOver => write!(f, "The game is over"),
+            GameError::NoSuchPlayer(idx) => write!(f, "There is no player {}", idx + 1),
+            GameError::PlayerAlreadyLeft(idx) => {
+                write!(f, "Player {} has already left the game", idx + 1)
+            }
+            GameError::NoRoundsLeft => write!(f, "There are no rounds left to join"),
+            GameError::BetweenTurns => write!(f, "Wait for the next turn to start"),
+            GameError::RollLimitReached => write!(f, "Roll limit reached — you must bank."),
+            GameError::NotATeammate(idx) => write!(f, "Player {} is not your teammate", idx + 1),
+            GameError::AlreadyPassed => write!(f, "The dice can only be passed once per turn"),
+            GameError::CorruptedState { field, reason } => {
+                write!(f, "Corrupted state in {}: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::InvalidEnvVar { var, value, reason } => {
+                write!(f, "Invalid value \"{}\" for {}: {}", value, var, reason)
+            }
+        }
+    }
+}
+
+impl Display for TranscriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}: invalid move \"{}\": {}",
+            self.line, self.text, self.reason
+        )
+    }
+}
+
+impl TranscriptError {
+    fn new(line: usize, text: &str, reason: impl Display) -> Self {
+        TranscriptError {
+            line,
+            text: text.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl GameSummary {
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    pub fn standings(&self) -> std::slice::Iter<'_, (String, u32)> {
+        self.standings.iter()
+    }
+
+    pub fn winners(&self) -> Vec<&str> {
+        let best = self.standings.first().map_or(0, |(_, score)| *score);
+        self.standings
+            .iter()
+            .take_while(|(_, score)| best > 0 && *score == best)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+impl TurnSummary {
+    pub fn new(record: &TurnRecord) -> Self {
+        let mut summary = TurnSummary {
+            player: record.player.clone(),
+            rolls: record.rolls,
+            roll_history: vec![],
+            selections: vec![],
+            hot_dice: 0,
+            passed_to: vec![],
+            hand: 0,
+            dice_kept: 0,
+            outcome: record.outcome,
+        };
+        let mut kept: Vec<DieValue> = vec![];
+        for step in &record.steps {
+            match step {
+                TurnStep::Rolled {
+                    values,
+                    roll_type,
+                    value,
+                } => {
+                    if kept.len() == 6 {
+                        kept.clear();
+                    }
+                    let special = matches!(roll_type, RollType::Straight | RollType::TriplePair);
+                    if special {
+                        summary.selections.push((values.clone(), *value));
+                        kept.extend(values);
+                    }
+                    summary.roll_history.push(RollRecap {
+                        values: values.clone(),
+                        kept: if special { values.clone() } else { vec![] },
+                    });
+                }
+                TurnStep::Picked { values, value } => {
+                    summary.selections.push((values.clone(), *value));
+                    kept.extend(values);
+                    if let Some(recap) = summary.roll_history.last_mut() {
+                        recap.kept.extend(values);
+                    }
+                }
+                TurnStep::Unpicked => {
+                    if let Some((values, _)) = summary.selections.pop() {
+                        if kept.len() == 6 {
+                            summary.hot_dice -= 1;
+                        }
+                        kept.truncate(kept.len().saturating_sub(values.len()));
+                        if let Some(recap) = summary.roll_history.last_mut() {
+                            recap
+                                .kept
+                                .truncate(recap.kept.len().saturating_sub(values.len()));
+                        }
+                    }
+                }
+                TurnStep::Passed { name, .. } => {
+                    summary.passed_to.push(name.clone());
+                    continue;
+                }
+                TurnStep::MissedPoints { .. } => continue,
+            }
+            if kept.len() == 6 && !matches!(step, TurnStep::Unpicked) {
+                summary.hot_dice += 1;
+            }
+        }
+        summary.hand = summary.selections.iter().map(|(_, value)| value).sum();
+        summary.dice_kept = summary
+            .selections
+            .iter()
+            .map(|(values, _)| values.len())
+            .sum();
+        summary
+    }
+
+    pub fn points_per_die(&self) -> Option<f64> {
+        match self.outcome {
+            Some(TurnOutcome::Banked(points)) if self.dice_kept > 0 => {
+                Some(points as f64 / self.dice_kept as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for RollRecap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} → ", self.values.iter().join(" "))?;
+        if self.kept.is_empty() {
+            write!(f, "kept nothing")
+        } else {
+            write!(f, "kept {}", self.kept.iter().join(" "))
+        }
+    }
+}
+
+impl Display for TurnSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}'s turn:", self.player)?;
+        writeln!(f, "  • Rolled {} time(s)", self.rolls)?;
+        for recap in &self.roll_history {
+            writeln!(f, "      {}", recap)?;
+        }
+        if self.selections.is_empty() {
+            writeln!(f, "  • Kept nothing")?;
+        } else {
+            let kept = self
+                .selections
+                .iter()
+                .map(|(values, value)| format!("{} ({})", values.iter().join(" "), value))
+                .join(", ");
+            writeln!(f, "  • Kept {}", kept)?;
+        }
+        if self.hot_dice > 0 {
+            writeln!(f, "  • Hot dice {} time(s)", self.hot_dice)?;
+        }
+        if !self.passed_to.is_empty() {
+            writeln!(f, "  • Passed the dice to {}", self.passed_to.join(", "))?;
+        }
+        writeln!(f, "  • {} points in hand", self.hand)?;
+        match self.outcome {
+            Some(TurnOutcome::Banked(points)) => {
+                write!(f, "  • Banked {} points", points)?;
+                match self.points_per_die() {
+                    Some(per_die) => {
+                        let dice = if self.dice_kept == 1 { "die" } else { "dice" };
+                        write!(
+                            f,
+                            " off {} {} ({:.0} per die)",
+                            self.dice_kept, dice, per_die
+                        )
+                    }
+                    None => Ok(()),
+                }
+            }
+            Some(TurnOutcome::Farkled(lost)) => write!(f, "  • Farkled and lost {} points", lost),
+            Some(TurnOutcome::Wiped(lost)) => {
+                write!(f, "  • Rolled three 1s and lost a score of {}", lost)
+            }
+            Some(TurnOutcome::Forfeited) => {
+                write!(f, "  • Forfeited {} points", self.hand)
+            }
+            None => write!(f, "  • Turn in progress"),
+        }
+    }
+}
+
+fn env_var<T>(var: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: Display,
+{
+    let value = match std::env::var(var) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    value
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|e: T::Err| ConfigError::InvalidEnvVar {
+            var,
+            reason: e.to_string(),
+            value,
+        })
+}
+
+impl GameConfig {
+    pub fn from_env() -> Result<GameConfig, ConfigError> {
+        let players: usize = env_var("FARKLE_PLAYERS")?.unwrap_or(DEFAULT_PLAYERS);
+        if players == 0 {
+            return Err(ConfigError::InvalidEnvVar {
+                var: "FARKLE_PLAYERS",
+                value: players.to_string(),
+                reason: "at least one player is required".to_string(),
+            });
+        }
+        // No rule set has a minimum bank, so the setting is refused rather than ignored
+        if let Some(value) = env_var::<String>("FARKLE_MIN_BANK")? {
+            return Err(ConfigError::InvalidEnvVar {
+                var: "FARKLE_MIN_BANK",
+                value,
+                reason: "minimum banks are not supported".to_string(),
+            });
+        }
+        let mut rules = match env_var::<String>("FARKLE_RULE_SET")? {
+            Some(name) => Ruleset::preset(&name).ok_or_else(|| ConfigError::InvalidEnvVar {
+                var: "FARKLE_RULE_SET",
+                reason: format!("expected one of {}", Ruleset::PRESETS.join(", ")),
+                value: name,
+            })?,
+            None => Ruleset::default(),
+        };
+        if let Some(contribution) = env_var("FARKLE_POT")? {
+            rules.pot = Some(PotRule::with_contribution(contribution));
+        }
+        if let Some(increment) = env_var("FARKLE_BANK_ROUNDING")? {
+            rules.bank_rounding = Some(increment);
+        }
+        if let Some(rounding) = env_var("FARKLE_ROUNDING")? {
+            rules.rounding = rounding;
+        }
+        if let Some(hot_dice) = env_var("FARKLE_HOT_DICE")? {
+            rules.hot_dice = hot_dice;
+        }
+        if let Some(limit) = env_var("FARKLE_ROLL_LIMIT")? {
+            rules.max_rolls_per_turn = Some(limit);
+        }
+        if let Some(sudden_death) = env_var("FARKLE_SUDDEN_DEATH")? {
+            rules.sudden_death = sudden_death;
+        }
+        if let Some(zilch_wipe) = env_var("FARKLE_ZILCH_WIPE")? {
+            rules.zilch_wipe = zilch_wipe;
+        }
+        let handicaps = match env_var::<String>("FARKLE_HANDICAP")? {
+            Some(list) => list
+                .split(',')
+                .map(|points| {
+                    points.trim().parse().map_err(|e: std::num::ParseIntError| {
+                        ConfigError::InvalidEnvVar {
+                            var: "FARKLE_HANDICAP",
+                            value: list.clone(),
+                            reason: e.to_string(),
+                        }
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+        Ok(GameConfig {
+            player_names: (1..=players).map(|i| format!("Player {}", i)).collect(),
+            turns: env_var("FARKLE_TURNS")?.unwrap_or(DEFAULT_TURNS),
+            seed: env_var("FARKLE_SEED")?,
+            rules,
+            roll_off: env_var("FARKLE_ROLL_OFF")?.unwrap_or(false),
+            roll_seed: env_var("FARKLE_ROLL_SEED")?,
+            handicaps,
+            round_order: env_var("FARKLE_ORDER")?.unwrap_or_default(),
+            turn_order: env_var("FARKLE_TURN_ORDER")?.unwrap_or_default(),
+            teams: vec![],
+            team_scoring: env_var("FARKLE_TEAM_SCORING")?.unwrap_or_default(),
+        })
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new(GameConfig::default())
+    }
+}
+
+impl Game {
+    pub fn new(config: GameConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+        let roll_off = config.roll_off.then(|| {
+            RollOff::run(
+                config.player_names.len(),
+                config.rules.faces.count,
+                &mut rng,
+            )
+        });
+        let entry_order = match &roll_off {
+            Some(roll_off) => roll_off.order().to_vec(),
+            None => config
+                .turn_order
+                .arrange(config.player_names.len(), &config.handicaps, &mut rng)
+                .into_order(),
+        };
+        let players = entry_order
+            .iter()
+            .map(|&idx| {
+                let handicap = config.handicaps.get(idx).copied().unwrap_or(0);
+                Player::with_score(config.player_names[idx].clone(), handicap)
+            })
+            .collect::<Vec<_>>();
+        let teams = config
+            .teams
+            .iter()
+            .map(|team| {
+                let members = team
+                    .members
+                    .iter()
+                    .filter_map(|&idx| entry_order.iter().position(|&entry| entry == idx))
+                    .collect();
+                Team::new(&team.name, members)
+            })
+            .collect();
+        let roll = Roll::with_faces(config.rules.faces.clone());
+        Game {
+            config,
+            seed,
+            rng,
+            departed: vec![false; players.len()],
+            joining: vec![],
+            seats: (0..players.len()).collect(),
+            seat: 0,
+            overtime: 0,
+            contenders: vec![],
+            players,
+            roll,
+            state: GameState::default(),
+            current_player: 0,
+            current_turn: 1,
+            pot: 0,
+            events: vec![],
+            transcript: vec![],
+            roll_off,
+            entry_order,
+            teams,
+            rolls: 0,
+            rolls_this_turn: 0,
+            turn_log: vec![],
+            roll_stats: RollStats::default(),
+            scripted_rolls: VecDeque::new(),
+            #[cfg(feature = "turn-timer")]
+            turn_started: std::time::Instant::now(),
+            #[cfg(feature = "debug-dice")]
+            debug_dice: DebugDice::default(),
+        }
+    }
+
+    pub fn from_transcript(moves: &str) -> Result<(Game, GameSummary), TranscriptError> {
+        let (mut game, mut playback) = Playback::start(moves)?;
+        playback.finish(&mut game)?;
+        let summary = game.summary();
+        Ok((game, summary))
+    }
+
+    pub fn from_notation(config: GameConfig, text: &str) -> Result<Game, NotationError> {
+        let mut game = Game::new(config);
+        for (n, turn) in notation::parse(text)?.iter().enumerate() {
+            let line = turn.to_string();
+            if n > 0 {
+                if game.state != GameState::TurnEnded {
+                    return Err(NotationError::new(&line, "The previous turn did not end"));
+                }
+                game.next_turn();
+            }
+            if game.is_over() {
+                return Err(NotationError::new(&line, "The game is already over"));
+            }
+            match &turn.player {
+                Some(name) if name != game.current_player().name() => {
+                    return Err(NotationError::new(
+                        &line,
+                        format!("It is {}'s turn", game.current_player().name()),
+                    ));
+                }
+                _ => (),
+            }
+            for token in &turn.tokens {
+                game.apply_token(token)
+                    .map_err(|reason| NotationError::new(&token.to_string(), reason))?;
+            }
+        }
+        Ok(game)
+    }
+
+    fn apply_token(&mut self, token: &TurnToken) -> Result<(), String> {
+        let action =
+            match token {
+                TurnToken::Roll(values) => {
+                    let expected = self.roll.remaining_count();
+                    if values.len() != expected {
+                        return Err(format!("Expected {} dice", expected));
+                    }
+                    let faces = self.config.rules.faces.count;
+                    if let Some(value) = values.iter().find(|&&value| value > faces) {
+                        return Err(format!("The dice have no face {}", value));
+                    }
+                    self.scripted_rolls.push_back(values.clone());
+                    let result = self.apply(GameAction::Roll);
+                    self.scripted_rolls.clear();
+                    return result.map(|_| ()).map_err(|e| e.to_string());
+                }
+                TurnToken::Keep(values) => {
+                    for &value in values {
+                        let idx = self
+                            .roll
+                            .dice()
+                            .iter()
+                            .position(|die| !die.picked() && die.value() == value)
+                            .ok_or_else(|| format!("There is no unpicked {}", value))?;
+                        self.apply(GameAction::ToggleDie(idx))
+                            .map_err(|e| e.to_string())?;
+                    }
+                    GameAction::ConfirmSelection
+                }
+                TurnToken::Unpick => GameAction::UndoSelection,
+                TurnToken::Bank => GameAction::Bank,
+                TurnToken::Forfeit => GameAction::ForfeitTurn,
+                TurnToken::Pass(player) => GameAction::PassToTeammate(*player),
+                TurnToken::Farkle => {
+                    let farkled = self.turn_log.last().is_some_and(|record| {
+                        matches!(record.outcome, Some(TurnOutcome::Farkled(_)))
+                    });
+                    return if self.state == GameState::TurnEnded && farkled {
+                        Ok(())
+                    } else {
+                        Err("The roll was not a farkle".to_string())
+                    };
+                }
+                TurnToken::Wipe => {
+                    let wiped = self.turn_log.last().is_some_and(|record| {
+                        matches!(record.outcome, Some(TurnOutcome::Wiped(_)))
+                    });
+                    return if self.state == GameState::TurnEnded && wiped {
+                        Ok(())
+                    } else {
+                        Err("The roll did not wipe the score".to_string())
+                    };
+                }
+            };
+        self.apply(action).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    pub fn notation(&self) -> String {
+        notation::to_string(&self.turn_log)
+    }
+
+    pub fn validate_state(&self) -> Result<(), GameError> {
+        let nested = |prefix: String| {
+            move |e| match e {
+                GameError::CorruptedState { field, reason } => GameError::CorruptedState {
+                    field: format!("{}.{}", prefix, field),
+                    reason,
+                },
+                e => e,
+            }
+        };
+        if self.current_player >= self.players.len() {
+            return Err(GameError::CorruptedState {
+                field: "current_player".to_string(),
+                reason: format!("there are only {} players", self.players.len()),
+            });
+        }
+        if self
+            .seats
+            .iter()
+            .copied()
+            .sorted()
+            .ne(0..self.players.len())
+        {
+            return Err(GameError::CorruptedState {
+                field: "seats".to_string(),
+                reason: "not a permutation of the players".to_string(),
+            });
+        }
+        let seated = self.seats.get(self.seat).copied();
+        let passed_to = seated.is_some_and(|player| {
+            self.team_of(player).is_some()
+                && self.team_of(player) == self.team_of(self.current_player)
+        });
+        if seated != Some(self.current_player) && !passed_to {
+            return Err(GameError::CorruptedState {
+                field: "seat".to_string(),
+                reason: format!("does not hold player {}", self.current_player + 1),
+            });
+        }
+        if self.departed.len() != self.players.len() {
+            return Err(GameError::CorruptedState {
+                field: "departed".to_string(),
+                reason: format!("expected {} entries", self.players.len()),
+            });
+        }
+        self.roll
+            .validate_state()
+            .map_err(nested("roll".to_string()))?;
+        for (idx, player) in self.players.iter().enumerate() {
+            player
+                .validate_state(&self.config.rules.faces)
+                .map_err(nested(format!("players[{}]", idx)))?;
+        }
+        Ok(())
+    }
+
+    pub fn assert_invariants(&self) {
+        if let Err(e) = self.validate_state() {
+            panic!("Invalid game state: {}", e);
+        }
+    }
+
+    pub fn apply(&mut self, action: GameAction) -> Result<GameEvent, GameError> {
+        let start = self.events.len();
+        let event = self.perform(action)?;
+        #[cfg(feature = "turn-timer")]
+        if self.state == GameState::TurnEnded {
+            let elapsed = self.turn_started.elapsed();
+            self.players[self.current_player].record_turn_time(elapsed);
+        }
+        self.record_step(&event);
+        let ended = self.events[start..]
+            .iter()
+            .find(|raised| {
+                matches!(
+                    raised,
+                    GameEvent::Farkled { .. } | GameEvent::ScoreWiped { .. }
+                )
+            })
+            .cloned();
+        if let Some(ended) = ended {
+            self.record_step(&ended);
+        }
+        self.events.insert(start, event.clone());
+        Ok(event)
+    }
+
+    pub fn recommend(&self) -> Option<Recommendation> {
+        if !self.state.can_bank() || !self.can_reroll() {
+            return None;
+        }
+        Some(Recommendation::for_hand(
+            self.current_player().hand_value(),
+            self.roll.remaining_count(),
+            &self.config.rules,
+        ))
+    }
+
+    pub fn can_reroll(&self) -> bool {
+        self.state.can_roll()
+            && !self.roll_limit_reached()
+            && (self.config.rules.hot_dice || !self.roll.is_exhausted())
+    }
+
+    pub fn roll_limit_reached(&self) -> bool {
+        self.config
+            .rules
+            .max_rolls_per_turn
+            .is_some_and(|limit| self.rolls_this_turn >= limit.get() as usize)
+    }
+
+    // Special rolls pick their own dice, so only a selection the player made can be undone
+    pub fn can_undo_selection(&self) -> bool {
+        let last_step = self.turn_log.last().and_then(|record| {
+            record
+                .steps
+                .iter()
+                .rev()
+                .find(|step| !matches!(step, TurnStep::MissedPoints { .. }))
+        });
+        matches!(self.state, GameState::Rolling | GameState::HotDice)
+            && matches!(last_step, Some(TurnStep::Picked { .. }))
+    }
+
+    fn after_selection(&self) -> GameState {
+        if self.roll.is_exhausted() && self.config.rules.hot_dice {
+            GameState::HotDice
+        } else {
+            GameState::Rolling
+        }
+    }
+
+    fn reroll_all(&mut self) -> Result<(), GameError> {
+        if self.roll.is_exhausted() {
+            if !self.config.rules.hot_dice {
+                return Err(GameError::NoDiceLeft);
+            }
+            self.roll.release_all();
+        }
+        if let Some(values) = self.scripted_rolls.pop_front() {
+            self.roll.new_roll_from_values(&values)?;
+            self.rolls += 1;
+            self.rolls_this_turn += 1;
+            self.transcript.push("ROLL".to_string());
+            return Ok(());
+        }
+        self.rolls += 1;
+        self.rolls_this_turn += 1;
+        #[cfg(feature = "debug-dice")]
+        if self.debug_dice.roll(&mut self.roll) {
+            self.transcript.push("ROLL".to_string());
+            return Ok(());
+        }
+        match self.config.roll_seed {
+            Some(seed) => {
+                self.roll.new_roll_from_seed(seed, self.rolls);
+                self.transcript.push(format!("ROLL {}", self.rolls));
+            }
+            None => {
+                self.roll.new_roll_with_rng(&mut self.rng);
+                self.transcript.push("ROLL".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn record_step(&mut self, event: &GameEvent) {
+        if self
+            .turn_log
+            .last()
+            .is_none_or(|record| record.outcome.is_some())
+        {
+            self.turn_log.push(TurnRecord {
+                player: self.current_player().name().to_string(),
+                turn: self.current_turn,
+                rolls: 0,
+                steps: vec![],
+                outcome: None,
+            });
+        }
+        let dice = self.roll.dice();
+        let record = self.turn_log.last_mut().unwrap();
+        match *event {
+            GameEvent::Rolled { roll_type, value } => {
+                let values = dice
+                    .iter()
+                    .filter(|die| !die.picked() || die.picked_this_roll())
+                    .map(Die::value)
+                    .collect();
+                record.rolls += 1;
+                record.steps.push(TurnStep::Rolled {
+                    values,
+                    roll_type,
+                    value,
+                });
+            }
+            GameEvent::Selected(value) => {
+                let values = dice
+                    .iter()
+                    .filter(|die| die.picked_this_roll())
+                    .map(Die::value)
+                    .collect();
+                record.steps.push(TurnStep::Picked { values, value });
+            }
+            GameEvent::Unpicked => record.steps.push(TurnStep::Unpicked),
+            GameEvent::Passed { to, .. } => record.steps.push(TurnStep::Passed {
+                to,
+                name: self.players[to].name().to_string(),
+            }),
+            GameEvent::Banked(points) => record.outcome = Some(TurnOutcome::Banked(points)),
+            GameEvent::Farkled { lost } => record.outcome = Some(TurnOutcome::Farkled(lost)),
+            GameEvent::ScoreWiped { lost, .. } => record.outcome = Some(TurnOutcome::Wiped(lost)),
+            GameEvent::Forfeited => record.outcome = Some(TurnOutcome::Forfeited),
+            _ => (),
+        }
+    }
+
+    pub fn roll_stats(&self) -> &RollStats {
+        &self.roll_stats
+    }
+
+    pub fn turn_log(&self) -> &[TurnRecord] {
+        &self.turn_log
+    }
+
+    pub fn record_missed_points(&mut self, taken: u32, best: u32) {
+        if let Some(record) = self.turn_log.last_mut() {
+            record.steps.push(TurnStep::MissedPoints { taken, best });
+        }
+    }
+
+    pub fn last_turn_summary(&self) -> Option<TurnSummary> {
+        self.turn_log.last().map(TurnSummary::new)
+    }
+
+    pub fn current_turn_summary(&self) -> Option<TurnSummary> {
+        self.turn_log
+            .last()
+            .filter(|record| record.outcome.is_none())
+            .map(TurnSummary::new)
+    }
+
+    pub fn format_as_text(&self) -> String {
+        let mut text = String::from("Farkle game log\n");
+        text += &format!("Players: {}\n", self.config.player_names.join(", "));
+        text += &format!("Turns: {}\n", self.config.turns);
+        text += &format!("Seed: {}\n", self.seed);
+        if let Some(pot) = self.config.rules.pot {
+            text += &format!("Shared pot: {} per farkle\n", pot.contribution);
+        }
+        if !self.config.rules.faces.is_standard() {
+            text += &format!("Dice faces: {}\n", self.config.rules.faces);
+        }
+        for face in self.config.rules.singles_only() {
+            text += &format!("{}s only score as singles\n", face);
+        }
+        if let Some(seed) = self.config.roll_seed {
+            text += &format!("Roll seed: {}\n", seed);
+        }
+        if !self.config.rules.hot_dice {
+            text += "No hot dice: bank once every die has scored\n";
+        }
+        if let Some(limit) = self.config.rules.max_rolls_per_turn {
+            text += &format!("Roll limit: {} per turn\n", limit);
+        }
+        if self.config.rules.sudden_death {
+            text += "Ties go to sudden death\n";
+        }
+        if self.config.rules.zilch_wipe {
+            text += "Three 1s in one roll wipe your score\n";
+        }
+        if let Some(increment) = self.config.rules.bank_rounding {
+            text += &format!(
+                "Banks rounded to multiples of {} ({})\n",
+                increment, self.config.rules.rounding
+            );
+        }
+        if self.roll_off.is_some() {
+            let order = self.players.iter().map(Player::name).join(", ");
+            text += &format!("Turn order by roll-off: {}\n", order);
+        } else if self.config.turn_order != TurnOrder::Sequential {
+            let order = self.players.iter().map(Player::name).join(", ");
+            text += &format!("Turn order ({}): {}\n", self.config.turn_order, order);
+        }
+        if !self.teams.is_empty() {
+            let teams = self
+                .teams
+                .iter()
+                .map(|team| {
+                    let members = team
+                        .members
+                        .iter()
+                        .map(|&member| self.players[member].name())
+                        .join(", ");
+                    format!("{} ({})", team.name, members)
+                })
+                .join(" vs ");
+            text += &format!("Teams ({} scoring): {}\n", self.config.team_scoring, teams);
+        }
+
+        let values = |values: &[DieValue]| values.iter().join(" ");
+        for record in &self.turn_log {
+            text += &format!("\nTurn {} - {}\n", record.turn, record.player);
+            for step in &record.steps {
+                text += &match step {
+                    TurnStep::Rolled {
+                        values: rolled,
+                        roll_type: roll_type @ (RollType::Straight | RollType::TriplePair),
+                        value,
+                    } => format!("  Rolled {} ({}, {})\n", values(rolled), roll_type, value),
+                    TurnStep::Rolled { values: rolled, .. } => {
+                        format!("  Rolled {}\n", values(rolled))
+                    }
+                    TurnStep::Picked {
+                        values: picked,
+                        value,
+                    } => format!("  Picked {} ({})\n", values(picked), value),
+                    TurnStep::Unpicked => "  Unpicked\n".to_string(),
+                    TurnStep::Passed { name, .. } => format!("  Passed the dice to {}\n", name),
+                    TurnStep::MissedPoints { taken, best } => {
+                        format!("  Could have taken {} instead of {}\n", best, taken)
+                    }
+                };
+            }
+            text += &match record.outcome {
+                Some(TurnOutcome::Banked(points)) => format!("  Banked {}\n", points),
+                Some(TurnOutcome::Farkled(lost)) => format!("  Farkle! Lost {}\n", lost),
+                Some(TurnOutcome::Wiped(lost)) => format!("  Three 1s! Score of {} wiped\n", lost),
+                Some(TurnOutcome::Forfeited) => "  Forfeited\n".to_string(),
+                None => "  (in progress)\n".to_string(),
+            };
+        }
+
+        text += "\nScores\n";
+        for (name, score) in self.summary().standings() {
+            text += &format!("{} - {}\n", name, score);
+        }
+        text
+    }
+
+    fn perform(&mut self, action: GameAction) -> Result<GameEvent, GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        let illegal = GameError::IllegalAction {
+            action,
+            state: self.state,
+        };
+        let player = &mut self.players[self.current_player];
+        match action {
+            GameAction::Roll => {
+                if !self.state.can_roll() {
+                    return Err(illegal);
+                }
+                if self.roll_limit_reached() {
+                    return Err(GameError::RollLimitReached);
+                }
+                self.reroll_all()?;
+                let player = &mut self.players[self.current_player];
+
+                if self.config.rules.zilch_wipe && self.roll.count_values()[0] >= 3 {
+                    let (_, roll_type) = self.roll.determine_type();
+                    player.stats_mut().record_roll(roll_type);
+                    self.roll_stats.record(&roll_type, 0).record_wipe();
+                    let lost = player.wipe_score();
+                    player.stats_mut().record_turn(0);
+                    player.stats_mut().record_wipe(lost);
+                    self.events.push(GameEvent::ScoreWiped {
+                        player: self.current_player,
+                        lost,
+                    });
+                    self.state = GameState::TurnEnded;
+                    return Ok(GameEvent::Rolled {
+                        roll_type,
+                        value: 0,
+                    });
+                }
+
+                if let Some(pot) = self.config.rules.pot {
+                    if self.pot > 0 && (pot.trigger)(&self.roll.count_values()) {
+                        player.award(self.pot);
+                        self.events.push(GameEvent::PotClaimed(self.pot));
+                        self.pot = 0;
+                    }
+                }
+
+                if let Some(hook) = self.config.rules.on_special_roll {
+                    if let Some(special) = hook(&self.roll.count_values()) {
+                        match special {
+                            SpecialEvent::Bonus(points, _) => player.award(points),
+                            SpecialEvent::Penalty(points, _) => player.penalize(points),
+                            SpecialEvent::Message(_) => (),
+                        }
+                        self.events.push(GameEvent::Special(special));
+                    }
+                }
+
+                let (selection, roll_type) = self.roll.determine_type();
+                let value = selection.value();
+                player.stats_mut().record_roll(roll_type);
+                let roll_value = match roll_type {
+                    RollType::Simple => self.roll.max_possible_score(&self.config.rules),
+                    _ => value,
+                };
+                self.roll_stats.record(&roll_type, roll_value);
+                match roll_type {
+                    RollType::Farkle => {
+                        let lost = player.forfeit_hand();
+                        player.stats_mut().record_turn(0);
+                        player.stats_mut().record_farkle_loss(lost);
+                        self.events.push(GameEvent::Farkled { lost });
+                        if let Some(pot) = self.config.rules.pot {
+                            self.pot += pot.contribution;
+                        }
+                        self.state = GameState::TurnEnded;
+                        self.transcript.push("FARKLE".to_string());
+                    }
+                    RollType::Straight | RollType::TriplePair => {
+                        player.add_selection(selection);
+                        self.state = self.after_selection();
+                    }
+                    RollType::Simple => self.state = GameState::Picking,
+                }
+                Ok(GameEvent::Rolled { roll_type, value })
+            }
+            GameAction::ToggleDie(idx) => {
+                if self.state != GameState::Picking {
+                    return Err(illegal);
+                }
+                if idx >= self.roll.dice().len() {
+                    return Err(GameError::DieOutOfRange(idx));
+                }
+                Ok(GameEvent::Toggled(self.roll.toggle_die(idx)))
+            }
+            GameAction::TakeValue(value) => {
+                if self.state != GameState::Picking {
+                    return Err(illegal);
+                }
+                if !(1..=self.config.rules.faces.count).contains(&value) {
+                    return Err(GameError::NoSuchFace(value));
+                }
+                Ok(GameEvent::Taken(self.roll.pick_all_of_value(value)))
+            }
+            GameAction::ConfirmSelection => {
+                if self.state != GameState::Picking {
+                    return Err(illegal);
+                }
+                match self.roll.construct_selection_with(&self.config.rules) {
+                    Ok(selection) => {
+                        let picks = self
+                            .roll
+                            .current_roll_picks()
+                            .iter()
+                            .map(|idx| (idx + 1).to_string())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        self.transcript.push(format!("PICK {}", picks));
+
+                        let value = selection.value();
+                        player.add_selection(selection);
+                        self.state = self.after_selection();
+                        Ok(GameEvent::Selected(value))
+                    }
+                    Err(e) => {
+                        self.roll.deselect();
+                        Err(GameError::InvalidSelection(e))
+                    }
+                }
+            }
+            GameAction::UndoSelection => {
+                if !self.can_undo_selection() {
+                    return Err(illegal);
+                }
+                self.roll.deselect();
+                let _ = self.players[self.current_player].undo_selection();
+                self.state = GameState::Picking;
+                self.transcript.push("UNPICK".to_string());
+                Ok(GameEvent::Unpicked)
+            }
+            GameAction::Bank => {
+                if !self.state.can_bank() {
+                    return Err(illegal);
+                }
+                if !player.validate_hand(&self.config.rules, &self.roll) {
+                    return Err(GameError::InvalidSelection(
+                        "Hand does not match the dice it claims",
+                    ));
+                }
+                let dice = player.dice_in_hand();
+                let points = player.bank(&self.config.rules);
+                player.stats_mut().record_turn(points);
+                player.stats_mut().record_banked_dice(dice);
+                self.state = GameState::TurnEnded;
+                self.transcript.push("BANK".to_string());
+                Ok(GameEvent::Banked(points))
+            }
+            GameAction::ForfeitTurn => {
+                if self.state == GameState::TurnEnded {
+                    return Err(illegal);
+                }
+                self.transcript.push("FORFEIT".to_string());
+                Ok(self.forfeit())
+            }
+            GameAction::PassToTeammate(idx) => {
+                if !self.state.can_bank() {
+                    return Err(illegal);
+                }
+                if idx >= self.players.len() {
+                    return Err(GameError::NoSuchPlayer(idx));
+                }
+                if !self.teammates(self.current_player).contains(&idx) {
+                    return Err(GameError::NotATeammate(idx));
+                }
+                if self.passed_this_turn() {
+                    return Err(GameError::AlreadyPassed);
+                }
+                let player = &mut self.players[self.current_player];
+                if !player.validate_hand(&self.config.rules, &self.roll) {
+                    return Err(GameError::InvalidSelection(
+                        "Hand does not match the dice it claims",
+                    ));
+                }
+                let hand = player.take_hand();
+                self.players[idx].receive_hand(hand);
+                let from = self.current_player;
+                self.current_player = idx;
+                self.transcript.push(format!("PASS {}", idx + 1));
+                Ok(GameEvent::Passed { from, to: idx })
+            }
+        }
+    }
+
+    fn forfeit(&mut self) -> GameEvent {
+        let player = &mut self.players[self.current_player];
+        player.forfeit_hand();
+        player.stats_mut().record_turn(0);
+        player.stats_mut().record_forfeit();
+        self.state = GameState::TurnEnded;
+        GameEvent::Forfeited
+    }
+
+    pub fn next_turn(&mut self) {
+        loop {
+            if self.seat + 1 < self.seats.len() {
+                self.seat += 1;
+            } else {
+                self.seat = 0;
+                self.current_turn += 1;
+                if self.current_turn > self.total_turns() && self.overtime_due() {
+                    self.contenders = self.tied_leaders();
+                    self.overtime += 1;
+                }
+                for player in self.joining.drain(..) {
+                    self.entry_order.push(self.players.len());
+                    self.seats.push(self.players.len());
+                    self.players.push(player);
+                    self.departed.push(false);
+                }
+                self.config
+                    .round_order
+                    .reorder(&mut self.seats, self.seed, self.current_turn);
+            }
+            self.current_player = self.seats[self.seat];
+            if !self.sits_out(self.current_player) || self.is_over() {
+                break;
+            }
+        }
+        self.roll = Roll::with_faces(self.config.rules.faces.clone());
+        self.state = GameState::FirstRoll;
+        self.rolls_this_turn = 0;
+        #[cfg(feature = "turn-timer")]
+        {
+            self.turn_started = std::time::Instant::now();
+        }
+
+        if self.is_over() {
+            let winners = self.winners();
+            for (idx, player) in self.players.iter_mut().enumerate() {
+                let score = player.score();
+                player
+                    .stats_mut()
+                    .record_game(score, winners.contains(&idx));
+            }
+        }
+    }
+
+    pub fn is_final_turn(&self) -> bool {
+        self.current_turn == self.total_turns()
+            && self.seats[self.seat + 1..]
+                .iter()
+                .all(|&player| self.sits_out(player))
+            && !self.overtime_due()
+    }
+
+    pub fn total_turns(&self) -> u32 {
+        self.config.turns + self.overtime
+    }
+
+    pub fn overtime_round(&self) -> Option<u32> {
+        (self.overtime > 0).then_some(self.overtime)
+    }
+
+    pub fn contenders(&self) -> &[usize] {
+        &self.contenders
+    }
+
+    fn sits_out(&self, player: usize) -> bool {
+        self.departed[player] || (self.overtime > 0 && !self.contenders.contains(&player))
+    }
+
+    fn tied_leaders(&self) -> Vec<usize> {
+        let active: Vec<_> = (0..self.players.len())
+            .filter(|&player| !self.sits_out(player))
+            .collect();
+        let best = active
+            .iter()
+            .map(|&player| self.players[player].score())
+            .max()
+            .unwrap_or(0);
+        active
+            .into_iter()
+            .filter(|&player| self.players[player].score() == best)
+            .collect()
+    }
+
+    fn overtime_due(&self) -> bool {
+        self.config.rules.sudden_death && self.tied_leaders().len() > 1
+    }
+
+    pub fn add_player(&mut self, player: Player) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        if self.state == GameState::TurnEnded {
+            return Err(GameError::BetweenTurns);
+        }
+        if self.current_turn >= self.config.turns {
+            return Err(GameError::NoRoundsLeft);
+        }
+        self.transcript
+            .push(format!("JOIN {} {}", player.score(), player.name()));
+        self.joining.push(player);
+        Ok(())
+    }
+
+    pub fn remove_player(&mut self, idx: usize) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        if self.state == GameState::TurnEnded {
+            return Err(GameError::BetweenTurns);
+        }
+        match self.departed.get(idx) {
+            None => return Err(GameError::NoSuchPlayer(idx)),
+            Some(true) => return Err(GameError::PlayerAlreadyLeft(idx)),
+            Some(false) => (),
+        }
+        if idx == self.current_player {
+            let event = self.forfeit();
+            self.record_step(&event);
+            self.events.push(event);
+        }
+        self.departed[idx] = true;
+        self.transcript.push(format!("LEAVE {}", idx + 1));
+        Ok(())
+    }
+
+    pub fn has_left(&self, player: usize) -> bool {
+        self.departed[player]
+    }
+
+    pub fn legal_moves(&self) -> Vec<GameAction> {
+        if self.is_over() {
+            return vec![];
+        }
+        let mut moves = match self.state {
+            GameState::FirstRoll => vec![GameAction::Roll, GameAction::ForfeitTurn],
+            GameState::Rolling | GameState::HotDice => {
+                let mut moves = vec![GameAction::Roll, GameAction::Bank, GameAction::ForfeitTurn];
+                if self.can_undo_selection() {
+                    moves.insert(1, GameAction::UndoSelection);
+                }
+                if !self.can_reroll() {
+                    moves.retain(|&mov| mov != GameAction::Roll);
+                }
+                moves
+            }
+            GameState::Picking => {
+                let available: Vec<_> = self
+                    .roll
+                    .dice()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, die)| !die.picked() || die.picked_this_roll())
+                    .collect();
+                available
+                    .iter()
+                    .map(|&(idx, _)| GameAction::ToggleDie(idx))
+                    .chain(
+                        available
+                            .iter()
+                            .map(|(_, die)| die.value())
+                            .unique()
+                            .map(GameAction::TakeValue),
+                    )
+                    .chain([GameAction::ConfirmSelection, GameAction::ForfeitTurn])
+                    .collect()
+            }
+            GameState::TurnEnded => vec![],
+        };
+        if self.state.can_bank() && !self.passed_this_turn() {
+            moves.extend(
+                self.teammates(self.current_player)
+                    .into_iter()
+                    .map(GameAction::PassToTeammate),
+            );
+        }
+        moves
+    }
+
+    pub fn teams(&self) -> &[Team] {
+        &self.teams
+    }
+
+    pub fn team_of(&self, player: usize) -> Option<usize> {
+        self.teams.iter().position(|team| team.contains(player))
+    }
+
+    pub fn teammates(&self, player: usize) -> Vec<usize> {
+        match self.team_of(player) {
+            Some(team) => self.teams[team]
+                .members
+                .iter()
+                .copied()
+                .filter(|&member| member != player && !self.departed[member])
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn passed_this_turn(&self) -> bool {
+        self.turn_log.last().is_some_and(|record| {
+            record.outcome.is_none()
+                && record
+                    .steps
+                    .iter()
+                    .any(|step| matches!(step, TurnStep::Passed { .. }))
+        })
+    }
+
+    pub fn team_score(&self, team: usize) -> u32 {
+        self.teams[team]
+            .members
+            .iter()
+            .map(|&member| self.players[member].score())
+            .sum()
+    }
+
+    pub fn team_standings(&self) -> Vec<(usize, u32)> {
+        let mut standings: Vec<_> = (0..self.teams.len())
+            .map(|team| (team, self.team_score(team)))
+            .collect();
+        standings.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        standings
+    }
+
+    pub fn turns_remaining(&self, player: usize) -> u32 {
+        if self.is_over() || self.sits_out(player) {
+            return 0;
+        }
+        let later_rounds = self.total_turns() - self.current_turn;
+        let seat = self.seats.iter().position(|&seated| seated == player);
+        later_rounds + u32::from(seat > Some(self.seat))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.current_turn > self.total_turns()
+    }
+
+    fn rank_players(&self, score: impl Fn(&Player) -> u32) -> Vec<Standing> {
+        let mut players: Vec<_> = (0..self.players.len()).collect();
+        players.sort_by_key(|&idx| std::cmp::Reverse(score(&self.players[idx])));
+        let best = players.first().map_or(0, |&idx| score(&self.players[idx]));
+        players
+            .into_iter()
+            .map(|player| {
+                let score = score(&self.players[player]);
+                Standing {
+                    player,
+                    score,
+                    behind: best - score,
+                }
+            })
+            .collect()
+    }
+
+    pub fn standings(&self) -> Vec<Standing> {
+        self.rank_players(Player::score)
+    }
+
+    pub fn winners(&self) -> Vec<usize> {
+        if !self.teams.is_empty() && self.config.team_scoring == TeamScoring::Shared {
+            let standings = self.team_standings();
+            let best = standings.first().map_or(0, |&(_, score)| score);
+            return standings
+                .into_iter()
+                .take_while(|&(_, score)| best > 0 && score == best)
+                .flat_map(|(team, _)| self.teams[team].members.iter().copied())
+                .collect();
+        }
+        self.standings()
+            .into_iter()
+            .take_while(|standing| standing.behind == 0 && standing.score > 0)
+            .map(|standing| standing.player)
+            .collect()
+    }
+
+    pub fn live_standings(&self) -> Vec<Standing> {
+        self.rank_players(Player::effective_score)
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        let standings = self
+            .standings()
+            .into_iter()
+            .map(|standing| {
+                let name = self.players[standing.player].name().to_string();
+                (name, standing.score)
+            })
+            .collect();
+        GameSummary {
+            turns: self.config.turns,
+            standings,
+        }
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            dice: self
+                .roll
+                .dice()
+                .iter()
+                .map(|die| DieSnapshot {
+                    value: die.value(),
+                    picked: die.picked(),
+                    picked_this_roll: die.picked_this_roll(),
+                })
+                .collect(),
+            players: self
+                .players
+                .iter()
+                .map(|p| PlayerSnapshot {
+                    name: p.name().to_string(),
+                    score: p.score(),
+                    hand: p.hand_value(),
+                })
+                .collect(),
+            current_player: self.current_player,
+            current_turn: self.current_turn,
+            turns: self.total_turns(),
+            state: self.state,
+            pot: self.pot(),
+            rolls_this_turn: self.rolls_this_turn,
+        }
+    }
+
+    pub fn transcript(&self) -> String {
+        let mut header = vec![format!("SEED {}", self.seed)];
+        header.push(format!("TURNS {}", self.config.turns));
+        for (idx, name) in self.config.player_names.iter().enumerate() {
+            header.push(format!("PLAYER {}", name));
+            match self.config.handicaps.get(idx) {
+                Some(&handicap) if handicap > 0 => header.push(format!("HANDICAP {}", handicap)),
+                _ => (),
+            }
+        }
+        if self.config.rules.sudden_death {
+            header.push("SUDDENDEATH".to_string());
+        }
+        if self.config.rules.zilch_wipe {
+            header.push("ZILCHWIPE".to_string());
+        }
+        if self.config.roll_off {
+            header.push("ROLLOFF".to_string());
+        }
+        if self.config.turn_order != TurnOrder::Sequential {
+            header.push(format!("TURNORDER {}", self.config.turn_order));
+        }
+        for team in &self.config.teams {
+            header.push(format!("TEAM {}", team));
+        }
+        if !self.config.teams.is_empty() && self.config.team_scoring != TeamScoring::Shared {
+            header.push(format!("TEAMSCORING {}", self.config.team_scoring));
+        }
+        if self.config.round_order != RoundOrder::Fixed {
+            header.push(format!("ORDER {}", self.config.round_order));
+        }
+        if let Some(pot) = self.config.rules.pot {
+            header.push(format!("POT {}", pot.contribution));
+        }
+        if !self.config.rules.faces.is_standard() {
+            header.push(format!("FACES {}", self.config.rules.faces));
+        }
+        if self.config.rules.four_of_a_kind != FourKindRule::Scale {
+            header.push(format!("FOURKIND {}", self.config.rules.four_of_a_kind));
+        }
+        if self.config.rules.hidden_scores {
+            header.push("HIDDENSCORES".to_string());
+        }
+        if !self.config.rules.hot_dice {
+            header.push("NOHOTDICE".to_string());
+        }
+        if let Some(hook) = self.config.rules.on_special_roll {
+            // Unnamed hooks cannot be replayed, so leave a name that fails to load
+            let name = SpecialEvent::hook_name(hook).unwrap_or("custom");
+            header.push(format!("SPECIALROLLS {}", name));
+        }
+        let singles_only = self.config.rules.singles_only();
+        if !singles_only.is_empty() {
+            header.push(format!("NOSETBONUS {}", singles_only.iter().join(" ")));
+        }
+        if let Some(seed) = self.config.roll_seed {
+            header.push(format!("ROLLSEED {}", seed));
+        }
+        if let Some(limit) = self.config.rules.max_rolls_per_turn {
+            header.push(format!("ROLLLIMIT {}", limit));
+        }
+        if let Some(increment) = self.config.rules.bank_rounding {
+            header.push(format!(
+                "ROUNDING {} {}",
+                increment, self.config.rules.rounding
+            ));
+        }
+        header
+            .iter()
+            .chain(self.transcript.iter())
+            .fold(String::new(), |acc, line| acc + line + "\n")
+    }
+
+    pub fn pot(&self) -> Option<u32> {
+        self.config.rules.pot.map(|_| self.pot)
+    }
+
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn roll(&self) -> &Roll {
+        &self.roll
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn current_player(&self) -> &Player {
+        &self.players[self.current_player]
+    }
+
+    pub fn current_player_index(&self) -> usize {
+        self.current_player
+    }
+
+    pub fn current_turn(&self) -> u32 {
+        self.current_turn
+    }
+
+    pub fn roll_count(&self) -> usize {
+        self.rolls
+    }
+
+    pub fn rolls_this_turn(&self) -> usize {
+        self.rolls_this_turn
+    }
+
+    pub fn roll_off(&self) -> Option<&RollOff> {
+        self.roll_off.as_ref()
+    }
+
+    pub fn entry_position(&self, player: usize) -> usize {
+        self.entry_order[player]
+    }
+
+    pub fn seating(&self) -> &[usize] {
+        &self.seats
+    }
+}
+
+impl Playback {
+    pub fn start(moves: &str) -> Result<(Game, Playback), TranscriptError> {
+        let mut config = GameConfig::default();
+        let mut lines = moves
+            .lines()
+            .enumerate()
+            .map(|(no, line)| (no + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+
+        while let Some((no, line)) = lines.peek().copied() {
+            let (keyword, arg) = line.split_once(' ').unwrap_or((line, ""));
+            match keyword {
+                "SEED" => {
+                    let seed = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.seed = Some(seed);
+                }
+                "TURNS" => {
+                    config.turns = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "PLAYER" => config.player_names.push(arg.trim().to_string()),
+                "HANDICAP" => {
+                    let points = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.handicaps.resize(config.player_names.len(), 0);
+                    match config.handicaps.last_mut() {
+                        Some(handicap) => *handicap = points,
+                        None => {
+                            return Err(TranscriptError::new(no, line, "no player to handicap"))
+                        }
+                    }
+                }
+                "POT" => {
+                    let contribution = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.rules.pot = Some(PotRule::with_contribution(contribution));
+                }
+                "FACES" => {
+                    config.rules.faces =
+                        arg.parse().map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "FOURKIND" => {
+                    config.rules.four_of_a_kind = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "NOSETBONUS" => {
+                    for word in arg.split_whitespace() {
+                        let single = config
+                            .rules
+                            .faces
+                            .singles
+                            .iter()
+                            .position(|&(face, _)| face.to_string() == word);
+                        match single {
+                            Some(0) => config.rules.ones_set_bonus = false,
+                            Some(1) => config.rules.fives_set_bonus = false,
+                            _ => {
+                                return Err(TranscriptError::new(
+                                    no,
+                                    line,
+                                    "Expected a single-scoring face",
+                                ))
+                            }
+                        }
+                    }
+                }
+                "ROLLOFF" => config.roll_off = true,
+                "HIDDENSCORES" => config.rules.hidden_scores = true,
+                "NOHOTDICE" => config.rules.hot_dice = false,
+                "SPECIALROLLS" => match SpecialEvent::hook_named(arg.trim()) {
+                    Some(hook) => config.rules.on_special_roll = Some(hook),
+                    None => {
+                        return Err(TranscriptError::new(no, line, "Unknown special roll hook"))
+                    }
+                },
+                "SUDDENDEATH" => config.rules.sudden_death = true,
+                "ZILCHWIPE" => config.rules.zilch_wipe = true,
+                "TEAM" => {
+                    let team = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.teams.push(team);
+                }
+                "TEAMSCORING" => {
+                    config.team_scoring = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "TURNORDER" => {
+                    config.turn_order = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "ORDER" => {
+                    config.round_order = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                }
+                "ROLLLIMIT" => {
+                    let limit = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.rules.max_rolls_per_turn = Some(limit);
+                }
+                "ROUNDING" => {
+                    let mut words = arg.split_whitespace();
+                    let increment = words
+                        .next()
+                        .unwrap_or_default()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.rules.bank_rounding = Some(increment);
+                    if let Some(word) = words.next() {
+                        config.rules.rounding = word
+                            .parse()
+                            .map_err(|e| TranscriptError::new(no, line, e))?;
+                    }
+                }
+                "ROLLSEED" => {
+                    let seed = arg
+                        .trim()
+                        .parse()
+                        .map_err(|e| TranscriptError::new(no, line, e))?;
+                    config.roll_seed = Some(seed);
+                }
+                _ => break,
+            }
+            lines.next();
+        }
+        if config.seed.is_none() {
+            return Err(TranscriptError::new(0, "", "Transcript has no SEED header"));
+        }
+        if config.player_names.is_empty() {
+            return Err(TranscriptError::new(0, "", "Transcript has no players"));
+        }
+
+        let playback = Playback {
+            moves: lines.map(|(no, line)| (no, line.to_string())).collect(),
+            next: 0,
+            farkled: false,
+        };
+        Ok((Game::new(config), playback))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.moves.len()
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (self.next, self.moves.len())
+    }
+
+    pub fn step(&mut self, game: &mut Game) -> Result<Option<&str>, TranscriptError> {
+        let Some((no, line)) = self.moves.get(self.next) else {
+            return Ok(None);
+        };
+        self.next += 1;
+        let (no, line) = (*no, line.as_str());
+        let err = |reason| TranscriptError::new(no, line, reason);
+        if game.state == GameState::TurnEnded && line == "FARKLE" && self.farkled {
+            self.farkled = false;
+            return Ok(Some(line));
+        }
+        if self.farkled {
+            return Err(err("Farkle was not recorded".to_string()));
+        }
+        let mut words = line.split_whitespace();
+        let roster_change = matches!(line.split_once(' '), Some(("JOIN" | "LEAVE", _)));
+        if game.state == GameState::TurnEnded && roster_change {
+            game.next_turn();
+        }
+        match line.split_once(' ') {
+            Some(("JOIN", arg)) => {
+                let (score, name) = arg
+                    .split_once(' ')
+                    .ok_or_else(|| err("Expected a score and a name".to_string()))?;
+                let score = score
+                    .parse()
+                    .map_err(|_| err(format!("Bad score {}", score)))?;
+                game.add_player(Player::with_score(name.to_string(), score))
+                    .map_err(|e| err(e.to_string()))?;
+                return Ok(Some(line));
+            }
+            Some(("LEAVE", arg)) => {
+                let idx: usize = arg
+                    .parse()
+                    .map_err(|_| err(format!("Bad player {}", arg)))?;
+                if idx == 0 {
+                    return Err(err(format!("Bad player {}", arg)));
+                }
+                game.remove_player(idx - 1)
+                    .map_err(|e| err(e.to_string()))?;
+                return Ok(Some(line));
+            }
+            _ => (),
+        }
+        if game.state == GameState::TurnEnded {
+            game.next_turn();
+        }
+        let action = match words.next() {
+            Some("ROLL") => {
+                if let Some(word) = words.next() {
+                    let roll_n: usize = word
+                        .parse()
+                        .map_err(|_| err(format!("Bad roll {}", word)))?;
+                    if roll_n != game.rolls + 1 {
+                        return Err(err(format!("Expected roll {}", game.rolls + 1)));
+                    }
+                }
+                GameAction::Roll
+            }
+            Some("BANK") => GameAction::Bank,
+            Some("UNPICK") => GameAction::UndoSelection,
+            Some("FORFEIT") => GameAction::ForfeitTurn,
+            Some("PASS") => match words.next().map(str::parse::<usize>) {
+                Some(Ok(player)) if player > 0 => GameAction::PassToTeammate(player - 1),
+                _ => return Err(err("Bad player to pass to".to_string())),
+            },
+            Some("PICK") => {
+                for word in words {
+                    let idx: usize = word.parse().map_err(|_| err(format!("Bad die {}", word)))?;
+                    if idx == 0 {
+                        return Err(err(format!("Bad die {}", word)));
+                    }
+                    match game.apply(GameAction::ToggleDie(idx - 1)) {
+                        Ok(GameEvent::Toggled(ToggleResult::Picked)) => (),
+                        Ok(_) => return Err(err(format!("Cannot pick die {}", idx))),
+                        Err(e) => return Err(err(e.to_string())),
+                    }
+                }
+                GameAction::ConfirmSelection
+            }
+            Some("FARKLE") => return Err(err("The roll was not a farkle".to_string())),
+            _ => return Err(err("Unknown move".to_string())),
+        };
+        let event = game.apply(action).map_err(|e| err(e.to_string()))?;
+        self.farkled = matches!(
+            event,
+            GameEvent::Rolled {
+                roll_type: RollType::Farkle,
+                ..
+            }
+        );
+        Ok(Some(line))
+    }
+
+    pub fn finish(&mut self, game: &mut Game) -> Result<(), TranscriptError> {
+        while self.step(game)?.is_some() {}
+        if self.farkled {
+            return Err(TranscriptError::new(0, "", "Farkle was not recorded"));
+        }
+        game.validate_state()
+            .map_err(|e| TranscriptError::new(0, "", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::*;
+
+    // Searches seeds for a first roll the test needs, starting from a given pot. The roll is
+    // scripted so the search still finds it when debug dice replace random rolls.
+    fn first_roll(rules: &Ruleset, pot: u32, wanted: impl Fn(&[usize]) -> bool) -> Game {
+        let values = (0..)
+            .map(|seed| {
+                let mut roll = Roll::with_faces(rules.faces.clone());
+                roll.new_roll_with_rng(&mut StdRng::seed_from_u64(seed));
+                roll
+            })
+            .find(|roll| wanted(&roll.count_values()))
+            .unwrap()
+            .dice_values();
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            rules: rules.clone(),
+            ..Default::default()
+        });
+        game.pot = pot;
+        game.scripted_rolls.push_back(values.to_vec());
+        game.apply(GameAction::Roll).unwrap();
+        game
+    }
+
+    fn is_farkle(counts: &[usize]) -> bool {
+        counts[0] == 0 && counts[4] == 0 && counts.iter().all(|&count| count < 3)
+    }
+
+    fn three_ones(counts: &[usize]) -> bool {
+        counts[0] >= 3
+    }
+
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn config_from_env(vars: &[(&str, &str)]) -> Result<GameConfig, ConfigError> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for (var, _) in std::env::vars().filter(|(var, _)| var.starts_with("FARKLE_")) {
+            std::env::remove_var(var);
+        }
+        for (var, value) in vars {
+            std::env::set_var(var, value);
+        }
+        let config = GameConfig::from_env();
+        for (var, _) in vars {
+            std::env::remove_var(var);
+        }
+        config
+    }
+
+    fn env_error(var: &'static str, value: &str, reason: &str) -> ConfigError {
+        ConfigError::InvalidEnvVar {
+            var,
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn env_config_falls_back_to_defaults() {
+        let config = config_from_env(&[]).unwrap();
+        assert_eq!(config.player_names, ["Player 1", "Player 2"]);
+        assert_eq!(config.turns, DEFAULT_TURNS);
+        assert_eq!((config.seed, config.roll_seed), (None, None));
+        assert!(config.handicaps.is_empty());
+        assert!(!config.roll_off);
+        assert!(config.rules.hot_dice && !config.rules.zilch_wipe);
+        assert!(config.rules.pot.is_none());
+    }
+
+    #[test]
+    fn env_config_reads_every_variable() {
+        let config = config_from_env(&[
+            ("FARKLE_PLAYERS", "3"),
+            ("FARKLE_TURNS", " 7 "),
+            ("FARKLE_SEED", "42"),
+            ("FARKLE_ROLL_SEED", "9"),
+            ("FARKLE_RULE_SET", "hasbro"),
+            ("FARKLE_POT", "300"),
+            ("FARKLE_BANK_ROUNDING", "50"),
+            ("FARKLE_ROUNDING", "down"),
+            ("FARKLE_ROLL_LIMIT", "4"),
+            ("FARKLE_SUDDEN_DEATH", "true"),
+            ("FARKLE_ZILCH_WIPE", "true"),
+            ("FARKLE_HOT_DICE", "false"),
+            ("FARKLE_ROLL_OFF", "true"),
+            ("FARKLE_ORDER", "rotate"),
+            ("FARKLE_TURN_ORDER", "random"),
+            ("FARKLE_TEAM_SCORING", "separate"),
+            ("FARKLE_HANDICAP", "0, 500,1000"),
+        ])
+        .unwrap();
+        assert_eq!(config.player_names.len(), 3);
+        assert_eq!(config.turns, 7);
+        assert_eq!((config.seed, config.roll_seed), (Some(42), Some(9)));
+        assert_eq!(config.rules.four_of_a_kind, FourKindRule::Flat(1000));
+        assert_eq!(config.rules.pot.map(|pot| pot.contribution), Some(300));
+        assert_eq!(config.rules.bank_rounding, Some(50));
+        assert_eq!(config.rules.rounding, Rounding::Down);
+        assert_eq!(config.rules.max_rolls_per_turn, NonZeroU32::new(4));
+        assert!(config.rules.sudden_death && config.rules.zilch_wipe);
+        assert!(!config.rules.hot_dice);
+        assert!(config.roll_off);
+        assert_eq!(config.round_order, RoundOrder::Rotate);
+        assert_eq!(config.turn_order, TurnOrder::Random);
+        assert_eq!(config.team_scoring, TeamScoring::Separate);
+        assert_eq!(config.handicaps, [0, 500, 1000]);
+    }
+
+    #[test]
+    fn env_config_reports_invalid_values() {
+        assert_eq!(
+            config_from_env(&[("FARKLE_PLAYERS", "three")]).unwrap_err(),
+            env_error("FARKLE_PLAYERS", "three", "invalid digit found in string")
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_PLAYERS", "0")]).unwrap_err(),
+            env_error("FARKLE_PLAYERS", "0", "at least one player is required")
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_ROLL_LIMIT", "0")]).unwrap_err(),
+            env_error(
+                "FARKLE_ROLL_LIMIT",
+                "0",
+                "number would be zero for non-zero type"
+            )
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_MIN_BANK", "300")]).unwrap_err(),
+            env_error("FARKLE_MIN_BANK", "300", "minimum banks are not supported")
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_RULE_SET", "chaos")]).unwrap_err(),
+            env_error(
+                "FARKLE_RULE_SET",
+                "chaos",
+                &format!("expected one of {}", Ruleset::PRESETS.join(", "))
+            )
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_HANDICAP", "0,lots")]).unwrap_err(),
+            env_error("FARKLE_HANDICAP", "0,lots", "invalid digit found in string")
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_ROUNDING", "sideways")]).unwrap_err(),
+            env_error(
+                "FARKLE_ROUNDING",
+                "sideways",
+                "Expected nearest, up or down"
+            )
+        );
+        assert_eq!(
+            config_from_env(&[("FARKLE_HOT_DICE", "maybe")]).unwrap_err(),
+            env_error(
+                "FARKLE_HOT_DICE",
+                "maybe",
+                "provided string was not `true` or `false`"
+            )
+        );
+    }
+
+    fn all_six_score(counts: &[usize]) -> bool {
+        counts
+            .iter()
+            .enumerate()
+            .all(|(face, &count)| count == 0 || count >= 3 || face == 0 || face == 4)
+    }
+
+    // Takes every die of the first roll, leaving the roll exhausted
+    fn take_all_six(rules: &Ruleset) -> Game {
+        let mut game = first_roll(rules, 0, all_six_score);
+        for value in game
+            .roll()
+            .dice()
+            .iter()
+            .map(|die| die.value())
+            .unique()
+            .collect::<Vec<_>>()
+        {
+            game.apply(GameAction::TakeValue(value)).unwrap();
+        }
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        assert!(game.roll().is_exhausted());
+        game
+    }
+
+    fn scripted_game() -> Game {
+        Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn hot_dice_state_machine() {
+        let mut game = scripted_game();
+        assert_eq!(game.state(), GameState::FirstRoll);
+        game.scripted_rolls.push_back(vec![1, 1, 1, 5, 5, 5]);
+        assert!(matches!(
+            game.apply(GameAction::Roll),
+            Ok(GameEvent::Rolled {
+                roll_type: RollType::Simple,
+                ..
+            })
+        ));
+        assert_eq!(game.state(), GameState::Picking);
+        for die in 0..6 {
+            game.apply(GameAction::ToggleDie(die)).unwrap();
+        }
+        assert_eq!(
+            game.apply(GameAction::ConfirmSelection),
+            Ok(GameEvent::Selected(1500))
+        );
+        assert_eq!(game.state(), GameState::HotDice);
+        assert_eq!(game.state().to_string(), "Hot dice");
+        assert!(game.state().can_roll() && game.state().can_bank());
+        for action in [GameAction::ToggleDie(0), GameAction::ConfirmSelection] {
+            assert_eq!(
+                game.apply(action),
+                Err(GameError::IllegalAction {
+                    action,
+                    state: GameState::HotDice
+                })
+            );
+        }
+
+        assert!(game.legal_moves().contains(&GameAction::UndoSelection));
+        assert_eq!(
+            game.apply(GameAction::UndoSelection),
+            Ok(GameEvent::Unpicked)
+        );
+        assert_eq!(game.state(), GameState::Picking);
+        assert_eq!(game.current_player().hand_value(), 0);
+        assert!(!game.roll().is_exhausted());
+
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::TakeValue(5)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        assert_eq!(game.state(), GameState::HotDice);
+
+        game.scripted_rolls.push_back(vec![2, 3, 3, 4, 6, 5]);
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::Picking);
+        assert_eq!(game.roll().dice_values(), [2, 3, 3, 4, 6, 5]);
+        game.apply(GameAction::TakeValue(5)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        assert_eq!(game.state(), GameState::Rolling);
+        assert_eq!(game.apply(GameAction::Bank), Ok(GameEvent::Banked(1550)));
+        assert_eq!(game.state(), GameState::TurnEnded);
+    }
+
+    #[test]
+    fn hot_dice_reroll_starts_from_fresh_dice() {
+        let mut game = scripted_game();
+        game.scripted_rolls.push_back(vec![1, 1, 1, 5, 5, 5]);
+        game.scripted_rolls.push_back(vec![2, 2, 3, 3, 4, 4]);
+        game.apply(GameAction::Roll).unwrap();
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::TakeValue(5)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::HotDice);
+        assert_eq!(game.current_player().hand_value(), 3500);
+        let Some(TurnStep::Rolled { roll_type, .. }) = game.turn_log()[0].steps.last() else {
+            panic!("The last step should be the triple pair roll");
+        };
+        assert_eq!(*roll_type, RollType::TriplePair);
+
+        let mut game = scripted_game();
+        game.scripted_rolls.push_back(vec![1, 1, 1, 5, 5, 5]);
+        game.scripted_rolls.push_back(vec![2, 3, 4, 6, 5, 2]);
+        game.apply(GameAction::Roll).unwrap();
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::TakeValue(5)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::Picking);
+        assert!(game
+            .roll()
+            .dice()
+            .iter()
+            .all(|die| !die.picked() && !die.picked_this_roll()));
+    }
+
+    #[test]
+    fn special_rolls_cannot_be_undone() {
+        let mut game = scripted_game();
+        game.scripted_rolls.push_back(vec![1, 2, 3, 4, 5, 6]);
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::HotDice);
+        assert!(!game.legal_moves().contains(&GameAction::UndoSelection));
+        assert_eq!(
+            game.apply(GameAction::UndoSelection),
+            Err(GameError::IllegalAction {
+                action: GameAction::UndoSelection,
+                state: GameState::HotDice
+            })
+        );
+    }
+
+    #[test]
+    fn recommend_uses_the_hand_and_the_dice_left() {
+        let mut game = scripted_game();
+        game.scripted_rolls.push_back(vec![1, 1, 1, 5, 5, 4]);
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.recommend(), None);
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::TakeValue(5)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        assert_eq!(game.current_player().hand_value(), 1100);
+        assert_eq!(
+            game.recommend(),
+            Some(Recommendation::for_hand(1100, 1, &Ruleset::default()))
+        );
+        assert!(!game.recommend().unwrap().should_roll());
+    }
+
+    #[test]
+    fn farkles_feed_the_pot_until_it_is_claimed() {
+        let rules = Ruleset {
+            pot: Some(PotRule::with_contribution(300)),
+            ..Ruleset::default()
+        };
+        let game = first_roll(&rules, 0, is_farkle);
+        assert_eq!(game.pot(), Some(300));
+        let game = first_roll(&rules, 300, is_farkle);
+        assert_eq!(game.pot(), Some(600));
+
+        let game = first_roll(&rules, 600, three_ones);
+        assert_eq!(game.pot(), Some(0));
+        assert!(game.events.contains(&GameEvent::PotClaimed(600)));
+        assert_eq!(game.players()[0].score(), 600);
+    }
+
+    #[test]
+    fn farkles_skip_the_pot_when_it_is_off() {
+        let rules = Ruleset::default();
+        assert_eq!(first_roll(&rules, 0, is_farkle).pot(), None);
+        let game = first_roll(&rules, 0, three_ones);
+        assert_eq!(game.pot(), None);
+        assert!(!game
+            .events
+            .iter()
+            .any(|event| matches!(event, GameEvent::PotClaimed(_))));
+        assert_eq!(game.players()[0].score(), 0);
+    }
+
+    #[test]
+    fn farkles_report_the_points_lost() {
+        let mut game = position(Ruleset::default(), "r:122346 k:1");
+        game.scripted_rolls.push_back(vec![2, 2, 3, 4, 6]);
+        game.apply(GameAction::Roll).unwrap();
+        assert!(game.events.contains(&GameEvent::Farkled { lost: 100 }));
+        assert_eq!(game.turn_log()[0].outcome, Some(TurnOutcome::Farkled(100)));
+        assert_eq!(game.players()[0].stats().farkle_losses(), 100);
+    }
+
+    #[test]
+    fn forfeits_are_not_farkles() {
+        let rules = Ruleset::default();
+        let mut game = first_roll(&rules, 0, |counts| counts[0] > 0 && !three_ones(counts));
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        assert_eq!(
+            game.apply(GameAction::ForfeitTurn),
+            Ok(GameEvent::Forfeited)
+        );
+        assert_eq!(game.state(), GameState::TurnEnded);
+        assert_eq!(game.turn_log()[0].outcome, Some(TurnOutcome::Forfeited));
+        let ann = &game.players()[0];
+        assert_eq!((ann.score(), ann.hand_value()), (0, 0));
+        assert_eq!((ann.stats().forfeits(), ann.stats().farkles()), (1, 0));
+
+        let game = first_roll(&rules, 0, is_farkle);
+        assert_eq!(game.turn_log()[0].outcome, Some(TurnOutcome::Farkled(0)));
+        let ann = &game.players()[0];
+        assert_eq!((ann.stats().forfeits(), ann.stats().farkles()), (0, 1));
+    }
+
+    #[test]
+    fn special_rolls_cannot_be_undone_without_hot_dice() {
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            rules: Ruleset {
+                hot_dice: false,
+                ..Ruleset::default()
+            },
+            ..Default::default()
+        });
+        game.scripted_rolls.push_back(vec![1, 2, 3, 4, 5, 6]);
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::Rolling);
+        assert!(!game.can_undo_selection());
+        assert!(!game.legal_moves().contains(&GameAction::UndoSelection));
+        assert_eq!(
+            game.apply(GameAction::UndoSelection),
+            Err(GameError::IllegalAction {
+                action: GameAction::UndoSelection,
+                state: GameState::Rolling
+            })
+        );
+
+        let mut game = scripted_game();
+        game.scripted_rolls.push_back(vec![1, 5, 5, 2, 3, 4]);
+        game.apply(GameAction::Roll).unwrap();
+        game.apply(GameAction::TakeValue(1)).unwrap();
+        game.apply(GameAction::ConfirmSelection).unwrap();
+        game.record_missed_points(100, 200);
+        assert!(game.can_undo_selection());
+    }
+
+    #[test]
+    fn roll_past_the_limit_is_rejected() {
+        let rules = Ruleset {
+            max_rolls_per_turn: NonZeroU32::new(2),
+            ..Default::default()
+        };
+        let mut game = position(rules.clone(), "r:123466 k:1");
+        assert!(!game.roll_limit_reached());
+        game.scripted_rolls.push_back(vec![5, 2, 3, 4, 6]);
+        assert!(game.apply(GameAction::Roll).is_ok());
+
+        let mut game = position(rules, "r:123466 k:1 r:52346 k:5");
+        assert!(game.roll_limit_reached());
+        assert_eq!(
+            game.apply(GameAction::Roll),
+            Err(GameError::RollLimitReached)
+        );
+        assert_eq!(game.apply(GameAction::Bank), Ok(GameEvent::Banked(150)));
+    }
+
+    #[test]
+    fn hot_dice_roll_all_six_again() {
+        let mut game = take_all_six(&Ruleset::default());
+        assert!(game.can_reroll());
+        assert!(matches!(
+            game.apply(GameAction::Roll),
+            Ok(GameEvent::Rolled { .. })
+        ));
+        let Some(TurnStep::Rolled { values, .. }) = game.turn_log()[0].steps.last() else {
+            panic!("The last step should be the new roll");
+        };
+        assert_eq!(values.len(), 6);
+    }
+
+    #[test]
+    fn without_hot_dice_an_exhausted_roll_must_bank() {
+        let rules = Ruleset {
+            hot_dice: false,
+            ..Ruleset::default()
+        };
+        let mut game = take_all_six(&rules);
+        assert_eq!(game.state(), GameState::Rolling);
+        assert!(!game.can_reroll());
+        assert_eq!(game.recommend(), None);
+        assert!(!game.legal_moves().contains(&GameAction::Roll));
+        assert_eq!(game.apply(GameAction::Roll), Err(GameError::NoDiceLeft));
+        let hand = game.players()[0].hand_value();
+        assert_eq!(game.apply(GameAction::Bank), Ok(GameEvent::Banked(hand)));
+
+        let transcript = Game::new(config(rules)).transcript();
+        let (replayed, _) = Game::from_transcript(&transcript).unwrap();
+        assert!(!replayed.config().rules.hot_dice);
+    }
+
+    #[test]
+    fn take_accepts_every_face_of_the_ruleset() {
+        let rules = Ruleset {
+            faces: DiceFaces::d8(),
+            ..Ruleset::default()
+        };
+        let mut game = first_roll(&rules, 0, |counts| counts[7] == 3);
+        assert_eq!(
+            game.apply(GameAction::TakeValue(9)),
+            Err(GameError::NoSuchFace(9))
+        );
+        assert_eq!(
+            game.apply(GameAction::TakeValue(8)),
+            Ok(GameEvent::Taken(3))
+        );
+
+        let mut game = first_roll(&Ruleset::default(), 0, |counts| counts[0] == 3);
+        assert_eq!(
+            game.apply(GameAction::TakeValue(8)),
+            Err(GameError::NoSuchFace(8))
+        );
+    }
+
+    #[test]
+    fn special_rolls_apply_the_hook() {
+        let rules = Ruleset {
+            on_special_roll: Some(SpecialEvent::even_steven),
+            ..Ruleset::default()
+        };
+        let game = first_roll(&rules, 0, |counts| {
+            counts.iter().step_by(2).sum::<usize>() == 0
+        });
+        let penalty = SpecialEvent::even_steven(&game.roll().count_values()).unwrap();
+        assert!(game.events.contains(&GameEvent::Special(penalty)));
+        assert_eq!(game.players()[0].score(), 0);
+    }
+
+    #[test]
+    fn transcripts_keep_hidden_scores_and_special_rolls() {
+        let rules = Ruleset {
+            hidden_scores: true,
+            on_special_roll: Some(SpecialEvent::even_steven),
+            ..Ruleset::default()
+        };
+        let transcript = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            seed: Some(1),
+            rules,
+            ..Default::default()
+        })
+        .transcript();
+        assert!(transcript.contains("HIDDENSCORES\nSPECIALROLLS evensteven"));
+        let (replayed, _) = Game::from_transcript(&transcript).unwrap();
+        let rules = &replayed.config().rules;
+        assert!(rules.hidden_scores);
+        assert_eq!(
+            rules.on_special_roll.and_then(SpecialEvent::hook_name),
+            Some("evensteven")
+        );
+        assert_eq!(replayed.transcript(), transcript);
+
+        let custom = transcript.replace("evensteven", "custom");
+        assert!(Game::from_transcript(&custom).is_err());
+    }
+
+    #[test]
+    fn transcripts_keep_a_nonzero_roll_limit() {
+        let rules = Ruleset {
+            max_rolls_per_turn: NonZeroU32::new(3),
+            ..Ruleset::default()
+        };
+        let transcript = Game::new(config(rules)).transcript();
+        assert!(transcript.contains("ROLLLIMIT 3"));
+        let (replayed, _) = Game::from_transcript(&transcript).unwrap();
+        assert_eq!(
+            replayed.config().rules.max_rolls_per_turn,
+            NonZeroU32::new(3)
+        );
+
+        let zero = transcript.replace("ROLLLIMIT 3", "ROLLLIMIT 0");
+        assert!(Game::from_transcript(&zero).is_err());
+    }
+
+    // The log follows seeded dice, which debug dice would replace
+    #[cfg(not(feature = "debug-dice"))]
+    #[test]
+    fn game_logs_read_turn_by_turn() {
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            seed: Some(0),
+            ..Default::default()
+        });
+        use GameAction::*;
+        let ann = [
+            Roll,
+            TakeValue(1),
+            ConfirmSelection,
+            Roll,
+            TakeValue(1),
+            ConfirmSelection,
+            Bank,
+        ];
+        let bob = [Roll, Roll, TakeValue(6), ConfirmSelection, Bank];
+        for action in ann {
+            game.apply(action).unwrap();
+        }
+        game.next_turn();
+        for action in bob {
+            game.apply(action).unwrap();
+        }
+        let expected = "\
+Farkle game log
+Players: Ann, Bob
+Turns: 3
+Seed: 0
+
+Turn 1 - Ann
+  Rolled 5 5 1 4 2 5
+  Picked 1 (100)
+  Rolled 2 5 1 1 3
+  Picked 1 1 (200)
+  Banked 300
+
+Turn 1 - Bob
+  Rolled 6 2 4 6 2 4 (Triple pair, 2000)
+  Rolled 6 6 1 2 6 5
+  Picked 6 6 6 (600)
+  Banked 2600
+
+Scores
+Bob - 2600
+Ann - 300
+";
+        assert_eq!(game.format_as_text(), expected);
+    }
+
+    fn turn(steps: Vec<TurnStep>, outcome: TurnOutcome) -> TurnSummary {
+        TurnSummary::new(&TurnRecord {
+            player: "Ann".to_string(),
+            turn: 1,
+            rolls: steps
+                .iter()
+                .filter(|step| matches!(step, TurnStep::Rolled { .. }))
+                .count(),
+            steps,
+            outcome: Some(outcome),
+        })
+    }
+
+    fn rolled(values: &[DieValue]) -> TurnStep {
+        TurnStep::Rolled {
+            values: values.to_vec(),
+            roll_type: RollType::Simple,
+            value: 0,
+        }
+    }
+
+    fn picked(values: &[DieValue], value: u32) -> TurnStep {
+        TurnStep::Picked {
+            values: values.to_vec(),
+            value,
+        }
+    }
+
+    #[test]
+    fn summaries_follow_a_banked_turn() {
+        let summary = turn(
+            vec![
+                rolled(&[1, 5, 5, 2, 3, 4]),
+                picked(&[1], 100),
+                rolled(&[1, 5, 2, 3, 4]),
+                picked(&[1], 100),
+                rolled(&[5, 2, 3, 4]),
+                picked(&[5], 50),
+            ],
+            TurnOutcome::Banked(250),
+        );
+        assert_eq!((summary.player.as_str(), summary.rolls), ("Ann", 3));
+        assert_eq!(
+            summary.selections,
+            [(vec![1], 100), (vec![1], 100), (vec![5], 50)]
+        );
+        assert_eq!(summary.roll_history[2].values, [5, 2, 3, 4]);
+        assert_eq!(summary.roll_history[2].kept, [5]);
+        assert_eq!(
+            (summary.hand, summary.dice_kept, summary.hot_dice),
+            (250, 3, 0)
+        );
+        assert_eq!(summary.outcome, Some(TurnOutcome::Banked(250)));
+        assert!(summary
+            .to_string()
+            .contains("Banked 250 points off 3 dice (83 per die)"));
+    }
+
+    #[test]
+    fn summaries_follow_a_farkle() {
+        let summary = turn(vec![rolled(&[2, 2, 3, 4, 6, 6])], TurnOutcome::Farkled(0));
+        assert_eq!(summary.rolls, 1);
+        assert!(summary.selections.is_empty());
+        assert!(summary.roll_history[0].kept.is_empty());
+        assert_eq!((summary.hand, summary.dice_kept), (0, 0));
+        assert_eq!(summary.outcome, Some(TurnOutcome::Farkled(0)));
+        assert_eq!(summary.points_per_die(), None);
+        assert!(summary.to_string().contains("Kept nothing"));
+    }
+
+    #[test]
+    fn summaries_count_hot_dice() {
+        let summary = turn(
+            vec![
+                rolled(&[1, 1, 1, 5, 5, 5]),
+                picked(&[1, 1, 1, 5, 5, 5], 1500),
+                rolled(&[1, 5, 5, 2, 3, 4]),
+                picked(&[1], 100),
+            ],
+            TurnOutcome::Banked(1600),
+        );
+        assert_eq!((summary.rolls, summary.hot_dice), (2, 1));
+        assert_eq!((summary.hand, summary.dice_kept), (1600, 7));
+        assert_eq!(summary.outcome, Some(TurnOutcome::Banked(1600)));
+        assert!(summary.to_string().contains("Hot dice 1 time(s)"));
+    }
+
+    #[test]
+    fn live_standings_count_the_hand_in_play() {
+        let rules = Ruleset {
+            hidden_scores: true,
+            ..Ruleset::default()
+        };
+        let mut game = Game::new(GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            seed: Some(1),
+            rules: rules.clone(),
+            ..Default::default()
+        });
+        game.players[0].award(500);
+        game.players[1].add_selection(rules.score(&[1, 0, 0, 0, 0, 0]).unwrap());
+        game.next_turn();
+        let (ann, bob) = (&game.players()[0], &game.players()[1]);
+        assert_eq!(ann.effective_score(), ann.score());
+        assert_eq!((bob.score(), bob.effective_score()), (0, 100));
+        let live = game.live_standings();
+        assert_eq!((live[0].player, live[0].score), (0, 500));
+        assert_eq!(
+            (live[1].player, live[1].score, live[1].behind),
+            (1, 100, 400)
+        );
+
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        assert_eq!(game.state(), GameState::TurnEnded);
+        let bob = &game.players()[1];
+        assert_eq!((bob.score(), bob.effective_score()), (0, 0));
+        assert_eq!(game.live_standings(), game.standings());
+    }
+
+    fn config(rules: Ruleset) -> GameConfig {
+        GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 3,
+            seed: Some(1),
+            rules,
+            ..Default::default()
+        }
+    }
+
+    fn position(rules: Ruleset, text: &str) -> Game {
+        Game::from_notation(config(rules), text).unwrap()
+    }
+
+    #[test]
+    fn rotating_the_order_changes_who_starts_each_round() {
+        let mut game = Game::new(GameConfig {
+            player_names: ["Ann", "Bob", "Cid"].map(String::from).to_vec(),
+            turns: 3,
+            round_order: RoundOrder::Rotate,
+            ..Default::default()
+        });
+        let mut starters = vec![];
+        for _ in 0..3 {
+            starters.push(game.current_player);
+            for _ in 0..3 {
+                game.apply(GameAction::ForfeitTurn).unwrap();
+                game.next_turn();
+            }
+        }
+        assert_eq!(starters, [0, 1, 2]);
+        assert!(game.is_over());
+        let (replayed, _) = Game::from_transcript(&game.transcript()).unwrap();
+        assert_eq!(replayed.config().round_order, RoundOrder::Rotate);
+    }
+
+    #[test]
+    fn players_join_at_the_next_round() {
+        let mut game = position(Ruleset::default(), "r:155234 k:1");
+        let cat = || Player::with_score("Cat".to_string(), 300);
+        game.add_player(cat()).unwrap();
+        assert_eq!(game.players().len(), 2);
+        game.apply(GameAction::Bank).unwrap();
+        assert_eq!(game.add_player(cat()), Err(GameError::BetweenTurns));
+        game.next_turn();
+        assert_eq!(game.players().len(), 2);
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        game.next_turn();
+        assert_eq!(game.players().len(), 3);
+        assert_eq!(game.players()[2].score(), 300);
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        game.next_turn();
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        game.next_turn();
+        assert_eq!(game.current_player, 2);
+    }
+
+    #[test]
+    fn players_cannot_join_the_last_round() {
+        let mut game = position(Ruleset::default(), "forfeit\nforfeit\nforfeit\nforfeit");
+        game.next_turn();
+        let cat = Player::with_score("Cat".to_string(), 0);
+        assert_eq!(game.add_player(cat), Err(GameError::NoRoundsLeft));
+    }
+
+    #[test]
+    fn removing_the_current_player_forfeits_their_turn() {
+        let mut game = position(Ruleset::default(), "r:155234 k:1");
+        game.remove_player(0).unwrap();
+        assert_eq!(game.state(), GameState::TurnEnded);
+        assert_eq!(game.players()[0].hand_value(), 0);
+        let summary = game.last_turn_summary().unwrap();
+        assert_eq!(summary.outcome, Some(TurnOutcome::Forfeited));
+        game.next_turn();
+        assert_eq!(game.current_player, 1);
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        game.next_turn();
+        assert_eq!((game.current_player, game.current_turn), (1, 2));
+    }
+
+    #[test]
+    fn removing_players_checks_the_index() {
+        let mut game = Game::new(config(Ruleset::default()));
+        assert_eq!(game.remove_player(2), Err(GameError::NoSuchPlayer(2)));
+        game.remove_player(1).unwrap();
+        assert_eq!(game.remove_player(1), Err(GameError::PlayerAlreadyLeft(1)));
+        assert_eq!(game.state(), GameState::FirstRoll);
+        game.apply(GameAction::ForfeitTurn).unwrap();
+        assert_eq!(game.remove_player(0), Err(GameError::BetweenTurns));
+    }
+
+    #[test]
+    fn removing_every_player_ends_the_game() {
+        let mut game = Game::new(config(Ruleset::default()));
+        game.remove_player(1).unwrap();
+        game.remove_player(0).unwrap();
+        game.next_turn();
+        assert!(game.is_over());
+        assert_eq!(game.remove_player(0), Err(GameError::GameOver));
+    }
+
+    #[cfg(feature = "debug-dice")]
+    #[test]
+    fn debug_dice_restart_for_each_game() {
+        let roll = || {
+            let mut game = Game::new(config(Ruleset::default()));
+            game.apply(GameAction::Roll).unwrap();
+            game.roll().dice_values()
+        };
+        let first = roll();
+        assert_eq!(roll(), first);
+        if std::env::var("FARKLE_DEBUG_DICE").is_err() {
+            assert_eq!(first, [1, 2, 3, 4, 5, 6]);
+        }
+    }
+
+    #[test]
+    fn ties_go_to_sudden_death() {
+        let rules = Ruleset {
+            sudden_death: true,
+            ..Ruleset::default()
+        };
+        let tied = "r:555234 k:555 bank\nr:555234 k:555 bank\n".to_string()
+            + &["r:223466 farkle"; 4].join("\n");
+        let mut game = position(rules.clone(), &tied);
+        game.next_turn();
+        assert!(!game.is_over());
+        assert_eq!(game.overtime_round(), Some(1));
+        assert_eq!((game.total_turns(), game.current_player), (4, 0));
+
+        let mut game = position(rules, &(tied + "\nr:155234 k:1 bank\nr:223466 farkle"));
+        assert!(game.is_final_turn());
+        game.next_turn();
+        assert!(game.is_over());
+        assert_eq!(game.winners(), [0]);
+        assert_eq!(game.overtime_round(), Some(1));
+    }
+
+    #[test]
+    fn ties_stand_without_sudden_death() {
+        let tied = "r:555234 k:555 bank\nr:555234 k:555 bank\n".to_string()
+            + &["r:223466 farkle"; 4].join("\n");
+        let mut game = position(Ruleset::default(), &tied);
+        game.next_turn();
+        assert!(game.is_over());
+        assert_eq!(game.overtime_round(), None);
+        assert_eq!(game.winners(), [0, 1]);
+    }
+
+    #[test]
+    fn transcripts_keep_singles_only_faces() {
+        let rules = Ruleset {
+            fives_set_bonus: false,
+            ..Ruleset::preset("d8").unwrap()
+        };
+        let transcript = Game::new(config(rules)).transcript();
+        assert!(transcript.contains("NOSETBONUS 8"));
+        let (replayed, _) = Game::from_transcript(&transcript).unwrap();
+        let rules = &replayed.config().rules;
+        assert!(rules.ones_set_bonus && !rules.fives_set_bonus);
+    }
+
+    #[test]
+    fn random_turn_order_follows_the_seed() {
+        let shuffled = |seed| {
+            Game::new(GameConfig {
+                player_names: ["Ann", "Bob", "Cid", "Dee"].map(String::from).to_vec(),
+                seed: Some(seed),
+                turn_order: TurnOrder::Random,
+                ..config(Ruleset::default())
+            })
+        };
+        let game = shuffled(8);
+        assert_eq!(shuffled(8).seating(), game.seating());
+        let (replayed, _) = Game::from_transcript(&game.transcript()).unwrap();
+        assert_eq!(replayed.seating(), game.seating());
+    }
+
+    fn wipe_position(text: &str) -> Game {
+        let rules = Ruleset {
+            zilch_wipe: true,
+            ..Default::default()
+        };
+        let mut game = position(
+            rules,
+            &format!("r:555234 k:555 bank\nr:223466 farkle\n{}", text),
+        );
+        if game.state() == GameState::TurnEnded {
+            game.next_turn();
+        }
+        assert_eq!(game.players()[0].score(), 500);
+        game
+    }
+
+    fn assert_wiped(game: &mut Game, roll: Vec<DieValue>, kept: u32) {
+        game.scripted_rolls.push_back(roll);
+        assert!(matches!(
+            game.apply(GameAction::Roll),
+            Ok(GameEvent::Rolled { value: 0, .. })
+        ));
+        assert_eq!(game.state(), GameState::TurnEnded);
+        assert!(game.events.contains(&GameEvent::ScoreWiped {
+            player: 0,
+            lost: 500
+        }));
+        let ann = &game.players()[0];
+        assert_eq!((ann.score(), ann.hand_value()), (0, 0));
+        let summary = game.last_turn_summary().unwrap();
+        assert_eq!(summary.outcome, Some(TurnOutcome::Wiped(500)));
+        assert_eq!(summary.hand, kept);
+    }
+
+    #[test]
+    fn three_ones_wipe_the_score() {
+        assert_wiped(&mut wipe_position(""), vec![1, 1, 1, 2, 3, 4], 0);
+    }
+
+    #[test]
+    fn four_ones_wipe_the_score() {
+        assert_wiped(&mut wipe_position(""), vec![1, 1, 1, 1, 2, 3], 0);
+    }
+
+    #[test]
+    fn three_ones_wipe_the_score_with_three_dice_left() {
+        let mut game = wipe_position("r:555234 k:555");
+        assert_eq!(game.current_player().hand_value(), 500);
+        assert_wiped(&mut game, vec![1, 1, 1], 500);
+    }
+
+    #[test]
+    fn two_ones_do_not_wipe_the_score() {
+        let mut game = wipe_position("");
+        game.scripted_rolls.push_back(vec![1, 1, 2, 3, 4, 6]);
+        game.apply(GameAction::Roll).unwrap();
+        assert_eq!(game.state(), GameState::Picking);
+        assert_eq!(game.players()[0].score(), 500);
+    }
+
+    fn team_game(team_scoring: TeamScoring) -> Game {
+        let config = GameConfig {
+            player_names: ["Ann", "Bob", "Cid", "Dee"].map(String::from).to_vec(),
+            teams: Team::pairs(4),
+            team_scoring,
+            ..config(Ruleset::default())
+        };
+        Game::from_notation(config, "r:155234 k:1").unwrap()
+    }
+
+    #[test]
+    fn passing_hands_the_dice_to_a_teammate() {
+        let mut game = team_game(TeamScoring::Shared);
+        assert_eq!(game.teammates(0), [1]);
+        assert!(game.legal_moves().contains(&GameAction::PassToTeammate(1)));
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(1)),
+            Ok(GameEvent::Passed { from: 0, to: 1 })
+        );
+        assert_eq!(game.current_player_index(), 1);
+        assert_eq!(game.players()[0].hand_value(), 0);
+        assert_eq!(game.current_player().hand_value(), 100);
+        game.validate_state().unwrap();
+
+        game.apply(GameAction::Bank).unwrap();
+        assert_eq!(game.players()[1].score(), 100);
+        let summary = game.last_turn_summary().unwrap();
+        assert_eq!(
+            (summary.player.as_str(), summary.passed_to),
+            ("Ann", vec!["Bob".to_string()])
+        );
+    }
+
+    #[test]
+    fn invalid_passes_are_rejected() {
+        let mut game = team_game(TeamScoring::Shared);
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(2)),
+            Err(GameError::NotATeammate(2))
+        );
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(0)),
+            Err(GameError::NotATeammate(0))
+        );
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(4)),
+            Err(GameError::NoSuchPlayer(4))
+        );
+        game.apply(GameAction::PassToTeammate(1)).unwrap();
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(0)),
+            Err(GameError::AlreadyPassed)
+        );
+        assert!(!game
+            .legal_moves()
+            .iter()
+            .any(|mov| matches!(mov, GameAction::PassToTeammate(_))));
+
+        let mut game = team_game(TeamScoring::Shared);
+        game.apply(GameAction::UndoSelection).unwrap();
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(1)),
+            Err(GameError::IllegalAction {
+                action: GameAction::PassToTeammate(1),
+                state: GameState::Picking
+            })
+        );
+
+        let mut game = position(Ruleset::default(), "r:155234 k:1");
+        assert_eq!(
+            game.apply(GameAction::PassToTeammate(1)),
+            Err(GameError::NotATeammate(1))
+        );
+    }
+
+    #[test]
+    fn team_scoring_decides_the_winners() {
+        let text = "r:155234 k:1 bank\nr:555234 k:555 bank\nr:155234 k:1 bank\nr:223466 farkle";
+        let config = |team_scoring| GameConfig {
+            player_names: ["Ann", "Bob", "Cid", "Dee"].map(String::from).to_vec(),
+            teams: Team::pairs(4),
+            team_scoring,
+            turns: 1,
+            ..config(Ruleset::default())
+        };
+        let mut game = Game::from_notation(config(TeamScoring::Shared), text).unwrap();
+        game.next_turn();
+        assert!(game.is_over());
+        assert_eq!((game.team_score(0), game.team_score(1)), (600, 100));
+        assert_eq!(game.team_standings(), [(0, 600), (1, 100)]);
+        assert_eq!(game.winners(), [0, 1]);
+
+        let mut game = Game::from_notation(config(TeamScoring::Separate), text).unwrap();
+        game.next_turn();
+        assert_eq!(game.winners(), [1]);
+    }
+}
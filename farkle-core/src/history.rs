@@ -0,0 +1,201 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+#[cfg(feature = "persistence")]
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use crate::game::GameSummary;
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameHistory {
+    games: Vec<GameSummary>,
+}
+
+impl GameHistory {
+    pub fn push(&mut self, summary: GameSummary) {
+        self.games.push(summary);
+    }
+
+    pub fn games(&self) -> std::slice::Iter<'_, GameSummary> {
+        self.games.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn wins_by_player(&self) -> HashMap<String, usize> {
+        let mut wins = HashMap::new();
+        for game in &self.games {
+            for name in game.winners() {
+                *wins.entry(name.to_string()).or_insert(0) += 1;
+            }
+        }
+        wins
+    }
+
+    pub fn session_winner(&self) -> Option<String> {
+        let wins = self.wins_by_player();
+        let most = *wins.values().max()?;
+        let mut leaders = wins.into_iter().filter(|&(_, count)| count == most);
+        match (leaders.next(), leaders.next()) {
+            (Some((name, _)), None) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn avg_score_by_player(&self) -> HashMap<String, f64> {
+        let mut totals: HashMap<String, (u32, u32)> = HashMap::new();
+        for (name, score) in self.games.iter().flat_map(GameSummary::standings) {
+            let entry = totals.entry(name.clone()).or_default();
+            entry.0 += score;
+            entry.1 += 1;
+        }
+        totals
+            .into_iter()
+            .map(|(name, (total, games))| (name, total as f64 / games as f64))
+            .collect()
+    }
+
+    pub fn to_report(&self) -> String {
+        let mut report = format!("Session history ({} games)\n", self.games.len());
+        for (idx, game) in self.games.iter().enumerate() {
+            let standings = game
+                .standings()
+                .map(|(name, score)| format!("{} {}", name, score))
+                .join(", ");
+            report += &format!("Game {} ({} turns): {}", idx + 1, game.turns(), standings);
+            if game.winners().is_empty() {
+                report += " (no winner)";
+            }
+            report += "\n";
+        }
+        let wins = self.wins_by_player();
+        report += "Average scores:\n";
+        for (name, avg) in self
+            .avg_score_by_player()
+            .into_iter()
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+        {
+            report += &format!(
+                "{}: {:.1} ({} wins)\n",
+                name,
+                avg,
+                wins.get(&name).unwrap_or(&0)
+            );
+        }
+        match self.session_winner() {
+            Some(name) => report += &format!("Session winner: {}", name),
+            None => report += "No single session winner",
+        }
+        report
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl GameHistory {
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(PathBuf::from(home).join(".local/share/rsfarkle/session_history.json"))
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(path)?;
+        Ok(serde_json::to_writer_pretty(file, self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, GameConfig};
+
+    fn summary(text: &str) -> GameSummary {
+        let config = GameConfig {
+            player_names: vec!["Ann".to_string(), "Bob".to_string()],
+            turns: 1,
+            seed: Some(1),
+            ..Default::default()
+        };
+        Game::from_notation(config, text).unwrap().summary()
+    }
+
+    fn session() -> GameHistory {
+        let mut history = GameHistory::default();
+        history.push(summary("r:555234 k:555 bank\nr:223466 farkle"));
+        history.push(summary("r:155234 k:1 bank\nr:111234 k:111 bank"));
+        history.push(summary("r:155234 k:15 bank\nr:155234 k:5 bank"));
+        history
+    }
+
+    #[test]
+    fn session_totals_follow_the_games() {
+        let history = session();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.wins_by_player()["Ann"], 2);
+        assert_eq!(history.wins_by_player()["Bob"], 1);
+        assert_eq!(history.session_winner(), Some("Ann".to_string()));
+        let averages = history.avg_score_by_player();
+        assert!((averages["Ann"] - 250.).abs() < 1e-9);
+        assert!((averages["Bob"] - 350.).abs() < 1e-9);
+        assert_eq!(
+            history.to_report(),
+            "Session history (3 games)\n\
+             Game 1 (1 turns): Ann 500, Bob 0\n\
+             Game 2 (1 turns): Bob 1000, Ann 100\n\
+             Game 3 (1 turns): Ann 150, Bob 50\n\
+             Average scores:\n\
+             Ann: 250.0 (2 wins)\n\
+             Bob: 350.0 (1 wins)\n\
+             Session winner: Ann"
+        );
+    }
+
+    #[test]
+    fn tied_sessions_have_no_winner() {
+        let mut history = session();
+        history.push(summary("r:223466 farkle\nr:155234 k:1 bank"));
+        assert_eq!(history.session_winner(), None);
+        assert_eq!(GameHistory::default().session_winner(), None);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn history_round_trips_through_a_file() {
+        let path = std::env::temp_dir()
+            .join(format!("rsfarkle_history_{}", std::process::id()))
+            .join("session_history.json");
+        let history = session();
+        history.save(&path).unwrap();
+        let loaded = GameHistory::load(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.to_report(), history.to_report());
+    }
+}
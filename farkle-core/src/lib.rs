@@ -0,0 +1,28 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+pub mod bot;
+#[cfg(feature = "debug-dice")]
+mod debug_dice;
+pub mod farkle;
+pub mod game;
+pub mod history;
+pub mod notation;
+pub mod odds;
+pub mod order;
+pub mod rules;
+pub mod stats;
+pub mod team;
+#[cfg(feature = "tuning")]
+pub mod tuning;
@@ -0,0 +1,304 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::farkle::{DieValue, Roll};
+use crate::game::{TurnOutcome, TurnRecord, TurnStep};
+use crate::rules::DiceFaces;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NotationError {
+    token: String,
+    reason: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct RollNotation {
+    pub kept: Vec<DieValue>,
+    pub live: Vec<DieValue>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TurnToken {
+    Roll(Vec<DieValue>),
+    Keep(Vec<DieValue>),
+    Unpick,
+    Bank,
+    Farkle,
+    Wipe,
+    Forfeit,
+    Pass(usize),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TurnNotation {
+    pub player: Option<String>,
+    pub tokens: Vec<TurnToken>,
+}
+
+impl NotationError {
+    pub fn new(token: &str, reason: impl Display) -> Self {
+        NotationError {
+            token: token.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid notation \"{}\": {}", self.token, self.reason)
+    }
+}
+
+fn parse_values(s: &str) -> Result<Vec<DieValue>, NotationError> {
+    s.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(value @ 1..) => Ok(value as DieValue),
+            _ => Err(NotationError::new(s, format!("'{}' is not a die value", c))),
+        })
+        .collect()
+}
+
+fn write_values(f: &mut std::fmt::Formatter<'_>, values: &[DieValue]) -> std::fmt::Result {
+    values.iter().try_for_each(|value| write!(f, "{}", value))
+}
+
+impl From<&Roll> for RollNotation {
+    fn from(roll: &Roll) -> Self {
+        let (kept, live) = roll.dice().iter().partition_map(|die| {
+            if die.picked() {
+                itertools::Either::Left(die.value())
+            } else {
+                itertools::Either::Right(die.value())
+            }
+        });
+        RollNotation { kept, live }
+    }
+}
+
+impl RollNotation {
+    pub fn to_roll(&self, faces: DiceFaces) -> Result<Roll, NotationError> {
+        let mut roll = Roll::with_faces(faces);
+        let values: Vec<_> = self.kept.iter().chain(&self.live).copied().collect();
+        if let Some(value) = values.iter().find(|&&value| value > roll.faces().count) {
+            return Err(NotationError::new(
+                &self.to_string(),
+                format!("The dice have no face {}", value),
+            ));
+        }
+        roll.new_roll_from_values(&values)
+            .map_err(|e| NotationError::new(&self.to_string(), e.to_string()))?;
+        for die in 0..self.kept.len() {
+            roll.set_aside(die);
+        }
+        Ok(roll)
+    }
+}
+
+impl Display for RollNotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.kept.is_empty() {
+            write!(f, "[")?;
+            write_values(f, &self.kept)?;
+            write!(f, "]")?;
+        }
+        write_values(f, &self.live)
+    }
+}
+
+impl FromStr for RollNotation {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kept, live) = match s.strip_prefix('[') {
+            Some(rest) => rest
+                .split_once(']')
+                .ok_or_else(|| NotationError::new(s, "Unclosed ["))?,
+            None => ("", s),
+        };
+        Ok(RollNotation {
+            kept: parse_values(kept)?,
+            live: parse_values(live)?,
+        })
+    }
+}
+
+impl Display for TurnToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TurnToken::Roll(values) => {
+                write!(f, "r:")?;
+                write_values(f, values)
+            }
+            TurnToken::Keep(values) => {
+                let groups = values.iter().sorted().group_by(|value| **value);
+                let groups = groups
+                    .into_iter()
+                    .map(|(_, mut group)| group.join(""))
+                    .join(",");
+                write!(f, "k:{}", groups)
+            }
+            TurnToken::Unpick => write!(f, "unpick"),
+            TurnToken::Bank => write!(f, "bank"),
+            TurnToken::Farkle => write!(f, "farkle"),
+            TurnToken::Wipe => write!(f, "wipe"),
+            TurnToken::Forfeit => write!(f, "forfeit"),
+            TurnToken::Pass(player) => write!(f, "pass:{}", player + 1),
+        }
+    }
+}
+
+impl FromStr for TurnToken {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("r", values)) if !values.is_empty() => Ok(TurnToken::Roll(parse_values(values)?)),
+            Some(("k", values)) if !values.is_empty() => {
+                Ok(TurnToken::Keep(parse_values(&values.replace(',', ""))?))
+            }
+            Some(("pass", player)) => match player.parse::<usize>() {
+                Ok(player) if player > 0 => Ok(TurnToken::Pass(player - 1)),
+                _ => Err(NotationError::new(s, "Expected a player number")),
+            },
+            Some(_) => Err(NotationError::new(
+                s,
+                "Expected r:<dice>, k:<dice> or pass:<player>",
+            )),
+            None => match s {
+                "unpick" => Ok(TurnToken::Unpick),
+                "bank" => Ok(TurnToken::Bank),
+                "farkle" => Ok(TurnToken::Farkle),
+                "wipe" => Ok(TurnToken::Wipe),
+                "forfeit" => Ok(TurnToken::Forfeit),
+                _ => Err(NotationError::new(s, "Unknown move")),
+            },
+        }
+    }
+}
+
+impl From<&TurnRecord> for TurnNotation {
+    fn from(record: &TurnRecord) -> Self {
+        let mut tokens: Vec<_> = record
+            .steps
+            .iter()
+            .filter_map(|step| match step {
+                TurnStep::Rolled { values, .. } => Some(TurnToken::Roll(values.clone())),
+                TurnStep::Picked { values, .. } => Some(TurnToken::Keep(values.clone())),
+                TurnStep::Unpicked => Some(TurnToken::Unpick),
+                TurnStep::Passed { to, .. } => Some(TurnToken::Pass(*to)),
+                TurnStep::MissedPoints { .. } => None,
+            })
+            .collect();
+        tokens.extend(record.outcome.map(|outcome| match outcome {
+            TurnOutcome::Banked(_) => TurnToken::Bank,
+            TurnOutcome::Farkled(_) => TurnToken::Farkle,
+            TurnOutcome::Wiped(_) => TurnToken::Wipe,
+            TurnOutcome::Forfeited => TurnToken::Forfeit,
+        }));
+        TurnNotation {
+            player: Some(record.player.clone()),
+            tokens,
+        }
+    }
+}
+
+impl Display for TurnNotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(player) = &self.player {
+            write!(f, "{}: ", player)?;
+        }
+        write!(f, "{}", self.tokens.iter().join(" "))
+    }
+}
+
+impl FromStr for TurnNotation {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (player, moves) = match s.split_once(": ") {
+            Some((player, moves)) => (Some(player.trim().to_string()), moves),
+            None => (None, s),
+        };
+        let tokens = moves
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(TurnNotation { player, tokens })
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<TurnNotation>, NotationError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+pub fn to_string(log: &[TurnRecord]) -> String {
+    log.iter()
+        .map(|record| TurnNotation::from(record).to_string() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roll_notation_round_trips() {
+        let roll: RollNotation = "[11]3556".parse().unwrap();
+        assert_eq!(roll.kept, [1, 1]);
+        assert_eq!(roll.live, [3, 5, 5, 6]);
+        assert_eq!(roll.to_string(), "[11]3556");
+
+        let dice = roll.to_roll(DiceFaces::default()).unwrap();
+        assert_eq!(dice.picked_values(), [1, 1]);
+        assert_eq!(RollNotation::from(&dice), roll);
+        assert!("113556".parse::<RollNotation>().unwrap().kept.is_empty());
+    }
+
+    #[test]
+    fn turn_notation_round_trips() {
+        let text = "Ann: r:135264 k:1,5 r:3342 k:333 unpick k:3 pass:2 bank";
+        let turn: TurnNotation = text.parse().unwrap();
+        assert_eq!(turn.player.as_deref(), Some("Ann"));
+        assert_eq!(turn.tokens[0], TurnToken::Roll(vec![1, 3, 5, 2, 6, 4]));
+        assert_eq!(turn.tokens[1], TurnToken::Keep(vec![1, 5]));
+        assert_eq!(turn.tokens[6], TurnToken::Pass(1));
+        assert_eq!(turn.to_string(), text);
+        assert_eq!(
+            parse(&format!("{}\n\nr:2 farkle\n", text)).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn bad_notation_is_rejected() {
+        assert!("[11".parse::<RollNotation>().is_err());
+        assert!("10".parse::<RollNotation>().is_err());
+        for token in ["r:", "k:", "x:12", "hold", "pass:0"] {
+            assert!(token.parse::<TurnToken>().is_err(), "{}", token);
+        }
+    }
+}
@@ -0,0 +1,709 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use itertools::Itertools;
+
+use crate::farkle::{DieValue, Roll, RollType, ScoreComponent, Selection};
+use crate::rules::ScoringRules;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PickOption {
+    pub picks: Vec<usize>,
+    pub values: Vec<DieValue>,
+    pub value: u32,
+    pub dice_left: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Analysis {
+    pub options: Vec<PickOption>,
+    pub farkle_chances: Vec<(usize, f64)>,
+    pub bank_value: u32,
+    pub reroll_value: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Recommendation {
+    Bank {
+        keep: u32,
+        reroll_ev: f64,
+        dice: usize,
+    },
+    Reroll {
+        keep: u32,
+        reroll_ev: f64,
+        dice: usize,
+    },
+}
+
+impl Analysis {
+    pub fn best(&self) -> Option<&PickOption> {
+        self.options.first()
+    }
+
+    pub fn recommendation(&self) -> Option<Recommendation> {
+        self.best().map(|best| {
+            Recommendation::new(
+                self.bank_value,
+                self.reroll_value,
+                dice_to_roll(best.dice_left),
+            )
+        })
+    }
+}
+
+impl Recommendation {
+    pub fn new(keep: u32, reroll_ev: f64, dice: usize) -> Self {
+        if reroll_ev > keep as f64 {
+            Recommendation::Reroll {
+                keep,
+                reroll_ev,
+                dice,
+            }
+        } else {
+            Recommendation::Bank {
+                keep,
+                reroll_ev,
+                dice,
+            }
+        }
+    }
+
+    pub fn for_hand(keep: u32, dice: usize, rules: &dyn ScoringRules) -> Self {
+        let reroll_ev = keep as f64 + expected_gain(dice, keep, rules);
+        Recommendation::new(keep, reroll_ev, dice)
+    }
+
+    pub fn should_roll(&self) -> bool {
+        matches!(self, Recommendation::Reroll { .. })
+    }
+}
+
+impl Display for Recommendation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (keep, reroll_ev, dice) = match *self {
+            Recommendation::Bank {
+                keep,
+                reroll_ev,
+                dice,
+            }
+            | Recommendation::Reroll {
+                keep,
+                reroll_ev,
+                dice,
+            } => (keep, reroll_ev, dice),
+        };
+        let dice = match dice {
+            1 => "1 die".to_string(),
+            n => format!("{} dice", n),
+        };
+        match self {
+            Recommendation::Bank { .. } => write!(
+                f,
+                "Bank (keeping {} beats EV {:.0} of re-rolling {})",
+                keep, reroll_ev, dice
+            ),
+            Recommendation::Reroll { .. } => write!(
+                f,
+                "Reroll (EV {:.0} of re-rolling {} beats keeping {})",
+                reroll_ev, dice, keep
+            ),
+        }
+    }
+}
+
+fn best_score(counts: &[usize], rules: &dyn ScoringRules) -> u32 {
+    let mut best = ScoreComponent::special_roll(counts).map_or(0, |special| special.value());
+    let mut sub = vec![0; counts.len()];
+    loop {
+        let mut face = 0;
+        while face < counts.len() && sub[face] == counts[face] {
+            sub[face] = 0;
+            face += 1;
+        }
+        if face == counts.len() {
+            return best;
+        }
+        sub[face] += 1;
+        if let Ok(selection) = rules.score(&sub) {
+            best = best.max(selection.value());
+        }
+    }
+}
+
+fn for_each_outcome(dice: usize, faces: usize, mut visit: impl FnMut(&[usize], f64)) {
+    fn fill(
+        counts: &mut Vec<usize>,
+        left: usize,
+        weight: f64,
+        faces: usize,
+        visit: &mut dyn FnMut(&[usize], f64),
+    ) {
+        let face = counts.len();
+        if face + 1 == faces {
+            counts.push(left);
+            visit(counts, weight / factorial(left));
+            counts.pop();
+            return;
+        }
+        for count in 0..=left {
+            counts.push(count);
+            fill(
+                counts,
+                left - count,
+                weight / factorial(count),
+                faces,
+                visit,
+            );
+            counts.pop();
+        }
+    }
+
+    let total = factorial(dice) / (faces as f64).powi(dice as i32);
+    fill(
+        &mut Vec::with_capacity(faces),
+        dice,
+        total,
+        faces,
+        &mut visit,
+    );
+}
+
+fn dice_to_roll(dice_left: usize) -> usize {
+    match dice_left {
+        0 => 6,
+        n => n,
+    }
+}
+
+pub fn farkle_chance(dice: usize, rules: &dyn ScoringRules) -> f64 {
+    let mut chance = 0.;
+    for_each_outcome(dice, rules.faces().count, |counts, p| {
+        if best_score(counts, rules) == 0 {
+            chance += p;
+        }
+    });
+    chance
+}
+
+pub fn expected_gain(dice: usize, hand_value: u32, rules: &dyn ScoringRules) -> f64 {
+    let mut expected = 0.;
+    for_each_outcome(dice, rules.faces().count, |counts, p| {
+        expected += match best_score(counts, rules) {
+            0 => -(hand_value as f64) * p,
+            best => best as f64 * p,
+        };
+    });
+    expected
+}
+
+static FARKLE_ROLLS: OnceLock<Vec<[DieValue; 6]>> = OnceLock::new();
+
+fn for_each_roll(dice: usize, mut visit: impl FnMut(&[DieValue], bool)) {
+    let mut roll = Roll::default();
+    let mut values = vec![1; dice];
+    for combination in 0..6usize.pow(dice as u32) {
+        let mut rest = combination;
+        for value in values.iter_mut() {
+            *value = rest % 6 + 1;
+            rest /= 6;
+        }
+        roll.release_all();
+        for die in dice..6 {
+            roll.set_aside(die);
+        }
+        roll.new_roll_from_values(&values)
+            .expect("one value per unpicked die");
+        let (_, roll_type) = roll.determine_type();
+        visit(&values, roll_type == RollType::Farkle);
+    }
+}
+
+fn farkle_rolls() -> &'static [[DieValue; 6]] {
+    FARKLE_ROLLS.get_or_init(|| {
+        let mut rolls = vec![];
+        for_each_roll(6, |values, farkle| {
+            if farkle {
+                rolls.push(core::array::from_fn(|idx| values[idx]));
+            }
+        });
+        rolls
+    })
+}
+
+fn factorial(n: usize) -> f64 {
+    (1..=n).map(|k| k as f64).product()
+}
+
+impl Roll {
+    pub fn count_scoreable_sets(&self) -> usize {
+        let counts = self.count_values();
+        if ScoreComponent::special_roll(&counts).is_some() {
+            return 1;
+        }
+        counts
+            .iter()
+            .enumerate()
+            .map(|(idx, &count)| {
+                let singles = if self.faces().is_single(idx + 1) {
+                    count % 3
+                } else {
+                    0
+                };
+                count / 3 + singles
+            })
+            .sum()
+    }
+
+    pub fn roll_quality_score(&self) -> f32 {
+        let rolled: usize = self.count_values().iter().sum();
+        if rolled == 0 {
+            return 0.;
+        }
+        self.count_scoreable_sets() as f32 / rolled as f32
+    }
+
+    pub fn remaining_count(&self) -> usize {
+        match self.dice().iter().filter(|die| !die.picked()).count() {
+            0 => self.dice().len(),
+            n => n,
+        }
+    }
+
+    pub fn max_possible_score(&self, rules: &dyn ScoringRules) -> u32 {
+        best_score(&self.count_values(), rules)
+    }
+
+    pub fn can_continue(&self, rules: &dyn ScoringRules) -> bool {
+        // Once every die has scored, hot dice lets the player roll all six again
+        if self.is_exhausted() {
+            return rules.hot_dice();
+        }
+        let mut counts = vec![0; rules.faces().count];
+        for value in self.unpicked_values() {
+            counts[value - 1] += 1;
+        }
+        best_score(&counts, rules) > 0
+    }
+
+    pub fn all_valid_selections(&self, rules: &dyn ScoringRules) -> Vec<(Vec<usize>, Selection)> {
+        let pool: Vec<usize> = (0..self.dice().len())
+            .filter(|&idx| !self.dice()[idx].picked() || self.dice()[idx].picked_this_roll())
+            .collect();
+        (1..1usize << pool.len())
+            .filter_map(|mask| {
+                let picks: Vec<usize> = pool
+                    .iter()
+                    .enumerate()
+                    .filter(|&(bit, _)| mask >> bit & 1 == 1)
+                    .map(|(_, &idx)| idx)
+                    .collect();
+                let mut counts = vec![0; rules.faces().count];
+                for &idx in &picks {
+                    counts[self.dice()[idx].value() - 1] += 1;
+                }
+                let sel = rules.score(&counts).ok()?.with_picks(picks.clone());
+                Some((picks, sel))
+            })
+            .collect()
+    }
+
+    pub fn picks_leaving_n_dice(
+        &self,
+        n: usize,
+        rules: &dyn ScoringRules,
+    ) -> Vec<(Vec<usize>, u32)> {
+        let pool = self
+            .dice()
+            .iter()
+            .filter(|die| !die.picked() || die.picked_this_roll())
+            .count();
+        let mut picks: Vec<_> = self
+            .all_valid_selections(rules)
+            .into_iter()
+            .filter(|(picks, _)| pool - picks.len() == n)
+            .map(|(picks, sel)| (picks, sel.value()))
+            .collect();
+        picks.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+        picks
+    }
+
+    pub fn constrain_picks(
+        &self,
+        must_include: &[usize],
+        must_exclude: &[usize],
+        rules: &dyn ScoringRules,
+    ) -> Vec<Vec<usize>> {
+        self.all_valid_selections(rules)
+            .into_iter()
+            .map(|(picks, _)| picks)
+            .filter(|picks| {
+                must_include.iter().all(|idx| picks.contains(idx))
+                    && !must_exclude.iter().any(|idx| picks.contains(idx))
+            })
+            .collect()
+    }
+
+    pub fn picks_including_die(
+        &self,
+        idx: usize,
+        rules: &dyn ScoringRules,
+    ) -> Vec<(Vec<usize>, u32)> {
+        self.all_valid_selections(rules)
+            .into_iter()
+            .filter(|(picks, _)| picks.contains(&idx))
+            .map(|(picks, sel)| (picks, sel.value()))
+            .collect()
+    }
+
+    pub fn optimal_picks_for_remaining_dice(
+        &self,
+        target_remaining: usize,
+        rules: &dyn ScoringRules,
+    ) -> Option<Vec<usize>> {
+        self.picks_leaving_n_dice(target_remaining, rules)
+            .into_iter()
+            .next()
+            .map(|(picks, _)| picks)
+    }
+
+    pub fn generate_all_farkle_rolls() -> Vec<[DieValue; 6]> {
+        farkle_rolls().to_vec()
+    }
+
+    pub fn farkle_exact_probability() -> f64 {
+        farkle_rolls().len() as f64 / 6f64.powi(6)
+    }
+
+    // None for dice counts a roll can never have, rather than a panic or a made-up 0
+    pub fn farkle_exact_probability_for_n(n: usize) -> Option<f64> {
+        match n {
+            6 => Some(Roll::farkle_exact_probability()),
+            1..=5 => {
+                let mut count = 0;
+                for_each_roll(n, |_, farkle| count += usize::from(farkle));
+                Some(count as f64 / 6f64.powi(n as i32))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn farkle_probability(&self, rules: &dyn ScoringRules) -> f64 {
+        farkle_chance(self.remaining_count(), rules)
+    }
+
+    pub fn expected_reroll_value(&self, hand_value: u32, rules: &dyn ScoringRules) -> f64 {
+        expected_gain(self.remaining_count(), hand_value, rules)
+    }
+
+    pub fn analyze(&self, hand_value: u32, rules: &dyn ScoringRules) -> Analysis {
+        let pool = self
+            .dice()
+            .iter()
+            .filter(|die| !die.picked() || die.picked_this_roll())
+            .count();
+        let mut selections: Vec<_> = self
+            .all_valid_selections(rules)
+            .into_iter()
+            .map(|(picks, sel)| (picks, sel.value()))
+            .collect();
+        if let Some(special) = ScoreComponent::special_roll(&self.count_values()) {
+            selections.push(((0..self.dice().len()).collect(), special.value()));
+        }
+        let mut options: Vec<PickOption> = selections
+            .into_iter()
+            .map(|(picks, value)| PickOption {
+                values: picks
+                    .iter()
+                    .map(|&idx| self.dice()[idx].value())
+                    .sorted()
+                    .collect(),
+                dice_left: pool - picks.len(),
+                value,
+                picks,
+            })
+            .collect();
+        options.sort_by(|a, b| {
+            b.value
+                .cmp(&a.value)
+                .then(b.dice_left.cmp(&a.dice_left))
+                .then(a.values.cmp(&b.values))
+        });
+        options.dedup_by(|a, b| a.values == b.values);
+        let farkle_chances = options
+            .iter()
+            .map(|option| option.dice_left)
+            .unique()
+            .sorted()
+            .map(|left| (left, farkle_chance(dice_to_roll(left), rules)))
+            .collect();
+        let best = options.first();
+        let bank_value = hand_value + best.map_or(0, |option| option.value);
+        let reroll_value = best.map_or(0., |option| {
+            bank_value as f64 + expected_gain(dice_to_roll(option.dice_left), bank_value, rules)
+        });
+        Analysis {
+            options,
+            farkle_chances,
+            bank_value,
+            reroll_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::farkle::{DieValue, STRAIGHT_VALUE, TRIPLE_PAIR_VALUE};
+    use crate::rules::Ruleset;
+
+    fn roll_of(values: [DieValue; 6]) -> Roll {
+        let mut roll = Roll::default();
+        for (die, value) in roll.dice_mut().iter_mut().zip(values) {
+            die.set_value(value);
+        }
+        roll
+    }
+
+    #[test]
+    fn scoreable_sets_and_roll_quality() {
+        let sets = |values| roll_of(values).count_scoreable_sets();
+        assert_eq!(sets([1, 1, 1, 5, 5, 5]), 2);
+        assert_eq!(sets([3, 3, 3, 1, 1, 1]), 2);
+        assert_eq!(sets([3, 1, 4, 6, 5, 2]), 1);
+        assert_eq!(sets([2, 3, 4, 6, 6, 2]), 0);
+        assert_eq!(sets([1, 5, 2, 3, 4, 4]), 2);
+
+        let quality = |values| roll_of(values).roll_quality_score();
+        assert_eq!(quality([1, 1, 1, 5, 5, 5]), 2. / 6.);
+        assert_eq!(quality([3, 1, 4, 6, 5, 2]), 1. / 6.);
+        assert_eq!(quality([2, 3, 4, 6, 6, 2]), 0.);
+    }
+
+    #[test]
+    fn picks_by_dice_left() {
+        let rules = Ruleset::default();
+        let roll = roll_of([1, 1, 1, 5, 2, 3]);
+        assert_eq!(
+            roll.picks_leaving_n_dice(2, &rules),
+            [(vec![0, 1, 2, 3], 1050)]
+        );
+        let three_left = roll.picks_leaving_n_dice(3, &rules);
+        assert_eq!(three_left.len(), 4);
+        assert_eq!(three_left[0], (vec![0, 1, 2], 1000));
+        assert!(three_left[1..]
+            .iter()
+            .all(|(picks, value)| { *value == 250 && picks.contains(&3) }));
+        let five_left = roll.picks_leaving_n_dice(5, &rules);
+        let values: Vec<_> = five_left.iter().map(|&(_, value)| value).collect();
+        assert_eq!(values, [100, 100, 100, 50]);
+        assert!(roll.picks_leaving_n_dice(0, &rules).is_empty());
+
+        assert_eq!(
+            roll.optimal_picks_for_remaining_dice(2, &rules),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(
+            roll.optimal_picks_for_remaining_dice(3, &rules),
+            Some(vec![0, 1, 2])
+        );
+        assert_eq!(roll.optimal_picks_for_remaining_dice(0, &rules), None);
+        assert_eq!(roll.optimal_picks_for_remaining_dice(6, &rules), None);
+    }
+
+    fn max_score(values: [DieValue; 6]) -> u32 {
+        roll_of(values).max_possible_score(&Ruleset::default())
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn recommendation_matches_a_hand_computed_ev() {
+        // One die: a 1 (+100) or a 5 (+50) each come up 1 in 6, otherwise the 450 is lost
+        let rules = Ruleset::default();
+        let one_die = Recommendation::for_hand(450, 1, &rules);
+        let Recommendation::Bank {
+            keep,
+            reroll_ev,
+            dice,
+        } = one_die
+        else {
+            panic!("Expected to bank, got {:?}", one_die);
+        };
+        assert_eq!((keep, dice), (450, 1));
+        assert_close(reroll_ev, 450. + 150. / 6. - 450. * 4. / 6.);
+        assert_eq!(
+            one_die.to_string(),
+            "Bank (keeping 450 beats EV 175 of re-rolling 1 die)"
+        );
+
+        // Two dice: 16 of the 36 outcomes farkle, the other 20 add 1800 points between them
+        let two_dice = Recommendation::for_hand(50, 2, &rules);
+        assert!(two_dice.should_roll());
+        let Recommendation::Reroll { reroll_ev, .. } = two_dice else {
+            unreachable!();
+        };
+        assert_close(reroll_ev, 50. + (1800. - 50. * 16.) / 36.);
+        assert_eq!(
+            two_dice.to_string(),
+            "Reroll (EV 78 of re-rolling 2 dice beats keeping 50)"
+        );
+    }
+
+    #[test]
+    fn exact_farkle_probabilities() {
+        // 1080 of the 46656 six-dice rolls have no 1, 5, triple, straight or triple pair
+        assert!((Roll::farkle_exact_probability() - 0.0231).abs() < 0.001);
+        assert_close(Roll::farkle_exact_probability(), 1080. / 46656.);
+        assert!((Roll::farkle_exact_probability_for_n(1).unwrap() - 0.667).abs() < 0.001);
+        assert_eq!(
+            Roll::farkle_exact_probability_for_n(6),
+            Some(Roll::farkle_exact_probability())
+        );
+        for n in 1..=5 {
+            assert_close(
+                Roll::farkle_exact_probability_for_n(n).unwrap(),
+                farkle_chance(n, &Ruleset::default()),
+            );
+        }
+        assert_eq!(Roll::farkle_exact_probability_for_n(0), None);
+        assert_eq!(Roll::farkle_exact_probability_for_n(7), None);
+        assert_close(
+            farkle_chance(6, &Ruleset::default()),
+            Roll::farkle_exact_probability(),
+        );
+
+        let triple_pair = |roll: &[DieValue; 6]| {
+            let mut counts = [0; 6];
+            roll.iter().for_each(|&value| counts[value - 1] += 1);
+            counts.iter().filter(|&&count| count == 2).count() == 3
+        };
+        assert!(!Roll::generate_all_farkle_rolls().iter().any(triple_pair));
+    }
+
+    #[test]
+    fn max_score_of_six_of_a_kind() {
+        assert_eq!(max_score([2; 6]), 800);
+        assert_eq!(max_score([1; 6]), 4000);
+        assert_eq!(max_score([5; 6]), 2000);
+    }
+
+    #[test]
+    fn max_score_of_special_rolls() {
+        assert_eq!(max_score([3, 1, 4, 6, 5, 2]), STRAIGHT_VALUE);
+        assert_eq!(max_score([3, 3, 4, 4, 6, 6]), TRIPLE_PAIR_VALUE);
+    }
+
+    #[test]
+    fn max_score_takes_every_scoring_die() {
+        assert_eq!(max_score([1, 1, 1, 5, 2, 3]), 1050);
+        assert_eq!(max_score([2, 3, 4, 6, 6, 2]), 0);
+    }
+
+    #[test]
+    fn continuing_needs_a_scoring_die_left() {
+        let rules = Ruleset::default();
+        let mut roll = roll_of([1, 5, 2, 3, 4, 4]);
+        assert!(roll.can_continue(&rules));
+        roll.toggle_die(0);
+        assert!(roll.can_continue(&rules));
+        roll.toggle_die(1);
+        assert!(!roll.can_continue(&rules));
+
+        let mut roll = roll_of([1, 2, 2, 2, 3, 4]);
+        roll.toggle_die(0);
+        assert!(roll.can_continue(&rules));
+        assert!(!roll_of([2, 3, 4, 6, 6, 2]).can_continue(&rules));
+    }
+
+    #[test]
+    fn continuing_after_every_die_scored_needs_hot_dice() {
+        let mut roll = roll_of([1, 1, 1, 5, 5, 5]);
+        for die in 0..6 {
+            roll.toggle_die(die);
+        }
+        assert!(roll.is_exhausted());
+        assert!(roll.can_continue(&Ruleset::default()));
+        let no_hot_dice = Ruleset {
+            hot_dice: false,
+            ..Ruleset::default()
+        };
+        assert!(!roll.can_continue(&no_hot_dice));
+    }
+
+    #[test]
+    fn picks_around_a_chosen_die() {
+        let rules = Ruleset::default();
+        let roll = roll_of([1, 5, 5, 2, 3, 5]);
+        let with_one = roll.picks_including_die(0, &rules);
+        assert_eq!(with_one.len(), 8);
+        for (picks, value) in &with_one {
+            assert!(picks.contains(&0));
+            assert!(*value > 0);
+            let mut check = roll_of([1, 5, 5, 2, 3, 5]);
+            for &idx in picks {
+                check.toggle_die(idx);
+            }
+            let selection = check.construct_selection_with(&rules);
+            assert_eq!(selection.map(|sel| sel.value()), Ok(*value));
+        }
+        assert!(with_one.contains(&(vec![0, 1, 2, 5], 600)));
+        assert!(roll.picks_including_die(3, &rules).is_empty());
+
+        let constrained = roll.constrain_picks(&[0], &[1], &rules);
+        assert_eq!(
+            constrained,
+            [vec![0], vec![0, 2], vec![0, 5], vec![0, 2, 5]]
+        );
+        assert!(roll.constrain_picks(&[0, 3], &[], &rules).is_empty());
+        assert_eq!(roll.constrain_picks(&[], &[], &rules).len(), 15);
+    }
+
+    #[test]
+    fn analysis_puts_the_best_pick_first() {
+        let rules = Ruleset::default();
+        let analysis = roll_of([1, 1, 3, 5, 5, 6]).analyze(200, &rules);
+        let best = analysis.best().unwrap();
+        assert_eq!(best.values, [1, 1, 5, 5]);
+        assert_eq!((best.value, best.dice_left), (300, 2));
+        assert_eq!(analysis.bank_value, 500);
+        assert_eq!(analysis.options.last().unwrap().values, [5]);
+        let left: Vec<_> = analysis
+            .farkle_chances
+            .iter()
+            .map(|&(left, _)| left)
+            .collect();
+        assert_eq!(left, [2, 3, 4, 5]);
+        assert_eq!(analysis.farkle_chances[0].1, farkle_chance(2, &rules));
+
+        let straight = roll_of([3, 1, 4, 6, 5, 2]).analyze(0, &rules);
+        let best = straight.best().unwrap();
+        assert_eq!((best.value, best.dice_left), (STRAIGHT_VALUE, 0));
+        assert_eq!(straight.farkle_chances[0], (0, farkle_chance(6, &rules)));
+        assert!(straight.reroll_value > straight.bank_value as f64);
+
+        let farkle = roll_of([2, 2, 3, 4, 6, 6]).analyze(0, &rules);
+        assert!(farkle.best().is_none() && farkle.farkle_chances.is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::farkle::DieValue;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum RoundOrder {
+    #[default]
+    Fixed,
+    Rotate,
+    Shuffle,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum TurnOrder {
+    #[default]
+    Sequential,
+    Random,
+    Handicap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerList {
+    order: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RollOff {
+    rounds: Vec<Vec<(usize, DieValue)>>,
+    order: Vec<usize>,
+}
+
+impl RollOff {
+    pub fn run(players: usize, faces: usize, rng: &mut impl Rng) -> Self {
+        let mut roll_off = RollOff::default();
+        roll_off.order = roll_off.resolve((0..players).collect(), faces, rng);
+        roll_off
+    }
+
+    fn resolve(&mut self, group: Vec<usize>, faces: usize, rng: &mut impl Rng) -> Vec<usize> {
+        if group.len() < 2 {
+            return group;
+        }
+        let mut rolls: Vec<_> = group
+            .into_iter()
+            .map(|player| (player, rng.gen_range(1..=faces)))
+            .collect();
+        self.rounds.push(rolls.clone());
+        rolls.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+
+        let mut order = Vec::with_capacity(rolls.len());
+        for (_, tied) in &rolls.into_iter().group_by(|&(_, value)| value) {
+            let tied = tied.map(|(player, _)| player).collect();
+            order.extend(self.resolve(tied, faces, rng));
+        }
+        order
+    }
+
+    pub fn rounds(&self) -> &[Vec<(usize, DieValue)>] {
+        &self.rounds
+    }
+
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+impl RoundOrder {
+    pub fn reorder(&self, seats: &mut [usize], seed: u64, round: u32) {
+        match self {
+            RoundOrder::Fixed => (),
+            RoundOrder::Rotate => seats.rotate_left(1),
+            RoundOrder::Shuffle => {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(round as u64));
+                seats.shuffle(&mut rng);
+            }
+        }
+    }
+}
+
+impl Display for RoundOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundOrder::Fixed => write!(f, "fixed"),
+            RoundOrder::Rotate => write!(f, "rotate"),
+            RoundOrder::Shuffle => write!(f, "shuffle"),
+        }
+    }
+}
+
+impl FromStr for RoundOrder {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(RoundOrder::Fixed),
+            "rotate" => Ok(RoundOrder::Rotate),
+            "shuffle" => Ok(RoundOrder::Shuffle),
+            _ => Err("Expected fixed, rotate or shuffle"),
+        }
+    }
+}
+
+impl PlayerList {
+    pub fn new(players: usize) -> Self {
+        PlayerList {
+            order: (0..players).collect(),
+        }
+    }
+
+    pub fn rotate_start(&mut self, starting_player: usize) {
+        if !self.order.is_empty() {
+            let len = self.order.len();
+            self.order.rotate_left(starting_player % len);
+        }
+    }
+
+    pub fn shuffle_order(&mut self, rng: &mut impl Rng) {
+        self.order.shuffle(rng);
+    }
+
+    pub fn weakest_first(&mut self, handicaps: &[u32]) {
+        self.order
+            .sort_by_key(|&idx| std::cmp::Reverse(handicaps.get(idx).copied().unwrap_or(0)));
+    }
+
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn into_order(self) -> Vec<usize> {
+        self.order
+    }
+}
+
+impl TurnOrder {
+    pub fn arrange(&self, players: usize, handicaps: &[u32], rng: &mut impl Rng) -> PlayerList {
+        let mut list = PlayerList::new(players);
+        match self {
+            TurnOrder::Sequential => (),
+            TurnOrder::Random => list.shuffle_order(rng),
+            TurnOrder::Handicap => list.weakest_first(handicaps),
+        }
+        list
+    }
+}
+
+impl Display for TurnOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TurnOrder::Sequential => write!(f, "sequential"),
+            TurnOrder::Random => write!(f, "random"),
+            TurnOrder::Handicap => write!(f, "handicap"),
+        }
+    }
+}
+
+impl FromStr for TurnOrder {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sequential" => Ok(TurnOrder::Sequential),
+            "random" => Ok(TurnOrder::Random),
+            "handicap" => Ok(TurnOrder::Handicap),
+            _ => Err("Expected sequential, random or handicap"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reordered(order: RoundOrder, round: u32) -> Vec<usize> {
+        let mut seats = vec![0, 1, 2, 3];
+        order.reorder(&mut seats, 9, round);
+        seats
+    }
+
+    #[test]
+    fn round_orders_move_the_seats() {
+        assert_eq!(reordered(RoundOrder::Fixed, 1), [0, 1, 2, 3]);
+        assert_eq!(reordered(RoundOrder::Rotate, 1), [1, 2, 3, 0]);
+        let shuffled = reordered(RoundOrder::Shuffle, 2);
+        assert_eq!(reordered(RoundOrder::Shuffle, 2), shuffled);
+        assert_eq!(
+            shuffled.iter().copied().sorted().collect_vec(),
+            [0, 1, 2, 3]
+        );
+        assert!((0..20).any(|round| reordered(RoundOrder::Shuffle, round) != shuffled));
+    }
+
+    #[test]
+    fn round_orders_parse_their_names() {
+        for order in [RoundOrder::Fixed, RoundOrder::Rotate, RoundOrder::Shuffle] {
+            assert_eq!(order.to_string().parse(), Ok(order));
+        }
+        assert!("random".parse::<RoundOrder>().is_err());
+    }
+
+    fn arrange(order: TurnOrder, seed: u64) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        order
+            .arrange(6, &[0, 300, 0, 900, 100, 0], &mut rng)
+            .into_order()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_order() {
+        let shuffled = arrange(TurnOrder::Random, 4);
+        assert_eq!(arrange(TurnOrder::Random, 4), shuffled);
+        assert_eq!(
+            shuffled.iter().copied().sorted().collect_vec(),
+            [0, 1, 2, 3, 4, 5]
+        );
+        assert!((0..20).any(|seed| arrange(TurnOrder::Random, seed) != shuffled));
+
+        let reshuffle = || {
+            let mut seats = [0, 1, 2, 3];
+            RoundOrder::Shuffle.reorder(&mut seats, 9, 2);
+            seats
+        };
+        assert_eq!(reshuffle(), reshuffle());
+    }
+
+    #[test]
+    fn turn_orders_arrange_the_players() {
+        assert_eq!(arrange(TurnOrder::Sequential, 4), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(arrange(TurnOrder::Handicap, 4), [3, 1, 4, 0, 2, 5]);
+    }
+
+    #[test]
+    fn rotating_the_start_wraps_the_others() {
+        let mut list = PlayerList::new(5);
+        list.rotate_start(2);
+        assert_eq!(list.order(), [2, 3, 4, 0, 1]);
+        list.rotate_start(7);
+        assert_eq!(list.order(), [4, 0, 1, 2, 3]);
+
+        let mut empty = PlayerList::new(0);
+        empty.rotate_start(2);
+        assert!(empty.order().is_empty());
+    }
+}
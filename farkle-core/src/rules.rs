@@ -0,0 +1,576 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, num::NonZeroU32, str::FromStr};
+
+use crate::farkle::{score_counts, DieValue, ScoreComponent, Selection, FIVE_VALUE, ONE_VALUE};
+
+const POT_CONTRIBUTION: u32 = 500;
+const STANDARD_FACE_COUNT: usize = 6;
+
+pub trait ScoringRules {
+    fn score(&self, counts: &[usize]) -> Result<Selection, &'static str>;
+
+    fn faces(&self) -> DiceFaces {
+        DiceFaces::default()
+    }
+
+    fn hot_dice(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardRules;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PotRule {
+    pub contribution: u32,
+    pub trigger: fn(&[usize]) -> bool,
+}
+
+pub type SpecialRollHook = fn(&[usize]) -> Option<SpecialEvent>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SpecialEvent {
+    Bonus(u32, &'static str),
+    Penalty(u32, &'static str),
+    Message(&'static str),
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Rounding {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum FourKindRule {
+    #[default]
+    Scale,
+    Flat(u32),
+    DoubleTriple,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DiceFaces {
+    pub count: usize,
+    pub singles: Vec<(DieValue, u32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ruleset {
+    pub pot: Option<PotRule>,
+    pub four_of_a_kind: FourKindRule,
+    pub faces: DiceFaces,
+    pub hidden_scores: bool,
+    pub on_special_roll: Option<SpecialRollHook>,
+    pub bank_rounding: Option<u32>,
+    pub rounding: Rounding,
+    pub hot_dice: bool,
+    pub max_rolls_per_turn: Option<NonZeroU32>,
+    pub sudden_death: bool,
+    pub ones_set_bonus: bool,
+    pub fives_set_bonus: bool,
+    pub zilch_wipe: bool,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            pot: None,
+            four_of_a_kind: FourKindRule::default(),
+            faces: DiceFaces::default(),
+            hidden_scores: false,
+            on_special_roll: None,
+            bank_rounding: None,
+            rounding: Rounding::default(),
+            hot_dice: true,
+            max_rolls_per_turn: None,
+            sudden_death: false,
+            ones_set_bonus: true,
+            fives_set_bonus: true,
+            zilch_wipe: false,
+        }
+    }
+}
+
+impl PotRule {
+    pub fn three_ones(counts: &[usize]) -> bool {
+        counts[0] >= 3
+    }
+
+    pub fn with_contribution(contribution: u32) -> Self {
+        PotRule {
+            contribution,
+            ..Default::default()
+        }
+    }
+}
+
+impl SpecialEvent {
+    // Hooks are saved by name so that transcripts can replay them
+    pub const HOOKS: &'static [(&'static str, SpecialRollHook)] =
+        &[("evensteven", SpecialEvent::even_steven)];
+
+    pub fn hook_named(name: &str) -> Option<SpecialRollHook> {
+        SpecialEvent::HOOKS
+            .iter()
+            .find(|&&(hook_name, _)| hook_name == name)
+            .map(|&(_, hook)| hook)
+    }
+
+    pub fn hook_name(hook: SpecialRollHook) -> Option<&'static str> {
+        SpecialEvent::HOOKS
+            .iter()
+            .find(|&&(_, known)| std::ptr::fn_addr_eq(known, hook))
+            .map(|&(name, _)| name)
+    }
+
+    pub fn even_steven(counts: &[usize]) -> Option<SpecialEvent> {
+        let rolled: usize = counts.iter().sum();
+        let odd: usize = counts.iter().step_by(2).sum();
+        if rolled >= 3 && odd == 0 {
+            Some(SpecialEvent::Penalty(
+                100,
+                "Even Steven! Nothing but even numbers.",
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for SpecialEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecialEvent::Bonus(points, msg) => write!(f, "{} (+{} points)", msg, points),
+            SpecialEvent::Penalty(points, msg) => write!(f, "{} (-{} points)", msg, points),
+            SpecialEvent::Message(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Rounding {
+    pub fn apply(&self, points: u32, increment: u32) -> u32 {
+        if increment == 0 {
+            return points;
+        }
+        let down = points / increment * increment;
+        let rest = points - down;
+        match self {
+            Rounding::Down => down,
+            Rounding::Up if rest > 0 => down + increment,
+            Rounding::Nearest if rest * 2 >= increment => down + increment,
+            _ => down,
+        }
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rounding::Nearest => write!(f, "nearest"),
+            Rounding::Up => write!(f, "up"),
+            Rounding::Down => write!(f, "down"),
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Rounding::Nearest),
+            "up" => Ok(Rounding::Up),
+            "down" => Ok(Rounding::Down),
+            _ => Err("Expected nearest, up or down"),
+        }
+    }
+}
+
+impl Display for FourKindRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FourKindRule::Scale => write!(f, "scale"),
+            FourKindRule::Flat(points) => write!(f, "{}", points),
+            FourKindRule::DoubleTriple => write!(f, "double"),
+        }
+    }
+}
+
+impl FromStr for FourKindRule {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scale" => Ok(FourKindRule::Scale),
+            "double" => Ok(FourKindRule::DoubleTriple),
+            _ => s
+                .parse()
+                .map(FourKindRule::Flat)
+                .map_err(|_| "Expected scale, double or a point value"),
+        }
+    }
+}
+
+impl FourKindRule {
+    pub fn component(&self, face: DieValue, base: u32) -> ScoreComponent {
+        match *self {
+            FourKindRule::Scale => ScoreComponent::Set {
+                face,
+                count: 4,
+                base,
+            },
+            FourKindRule::Flat(points) => ScoreComponent::FourOfAKind { face, points },
+            FourKindRule::DoubleTriple => ScoreComponent::FourOfAKind {
+                face,
+                points: base * 2,
+            },
+        }
+    }
+}
+
+impl DiceFaces {
+    pub fn d8() -> Self {
+        DiceFaces {
+            count: 8,
+            singles: vec![(1, ONE_VALUE), (8, FIVE_VALUE)],
+        }
+    }
+
+    pub fn single_points(&self, face: DieValue) -> Option<u32> {
+        self.singles
+            .iter()
+            .find(|(single, _)| *single == face)
+            .map(|(_, points)| *points)
+    }
+
+    pub fn is_single(&self, face: DieValue) -> bool {
+        self.single_points(face).is_some()
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == DiceFaces::default()
+    }
+}
+
+impl Display for DiceFaces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.count)?;
+        for (face, points) in &self.singles {
+            write!(f, " {}:{}", face, points)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DiceFaces {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let count: usize = words
+            .next()
+            .and_then(|word| word.parse().ok())
+            .ok_or("Expected a face count")?;
+        if count < 2 {
+            return Err("Dice need at least 2 faces");
+        }
+        let mut singles = vec![];
+        for word in words {
+            let (face, points) = word.split_once(':').ok_or("Expected face:points")?;
+            let face: DieValue = face.parse().map_err(|_| "Bad single face")?;
+            let points = points.parse().map_err(|_| "Bad single points")?;
+            if face == 0 || face > count {
+                return Err("Single face out of range");
+            }
+            singles.push((face, points));
+        }
+        Ok(DiceFaces { count, singles })
+    }
+}
+
+impl Default for DiceFaces {
+    fn default() -> Self {
+        DiceFaces {
+            count: STANDARD_FACE_COUNT,
+            singles: vec![(1, ONE_VALUE), (5, FIVE_VALUE)],
+        }
+    }
+}
+
+impl Default for PotRule {
+    fn default() -> Self {
+        PotRule {
+            contribution: POT_CONTRIBUTION,
+            trigger: PotRule::three_ones,
+        }
+    }
+}
+
+impl ScoringRules for StandardRules {
+    fn score(&self, counts: &[usize]) -> Result<Selection, &'static str> {
+        score_counts(counts, FourKindRule::Scale, &DiceFaces::default(), &[])
+    }
+}
+
+impl Ruleset {
+    pub const PRESETS: &'static [&'static str] = &[
+        "standard", "hasbro", "doubling", "pot", "blind", "d8", "hardones", "zilch",
+    ];
+
+    pub fn preset(name: &str) -> Option<Ruleset> {
+        let rules = match name {
+            "standard" => Ruleset::default(),
+            "hasbro" => Ruleset {
+                four_of_a_kind: FourKindRule::Flat(1000),
+                ..Default::default()
+            },
+            "doubling" => Ruleset {
+                four_of_a_kind: FourKindRule::DoubleTriple,
+                ..Default::default()
+            },
+            "pot" => Ruleset {
+                pot: Some(PotRule::default()),
+                ..Default::default()
+            },
+            "blind" => Ruleset {
+                hidden_scores: true,
+                ..Default::default()
+            },
+            "d8" => Ruleset {
+                faces: DiceFaces::d8(),
+                ..Default::default()
+            },
+            "hardones" => Ruleset {
+                ones_set_bonus: false,
+                ..Default::default()
+            },
+            "zilch" => Ruleset {
+                zilch_wipe: true,
+                ..Default::default()
+            },
+            _ => return None,
+        };
+        Some(rules)
+    }
+
+    // The 1s option covers the best single-scoring face and the 5s option the other one
+    pub fn singles_only(&self) -> Vec<DieValue> {
+        self.faces
+            .singles
+            .iter()
+            .zip([self.ones_set_bonus, self.fives_set_bonus])
+            .filter(|&(_, bonus)| !bonus)
+            .map(|(&(face, _), _)| face)
+            .collect()
+    }
+
+    pub fn round_bank(&self, points: u32) -> u32 {
+        match self.bank_rounding {
+            Some(increment) => self.rounding.apply(points, increment),
+            None => points,
+        }
+    }
+}
+
+impl ScoringRules for Ruleset {
+    fn score(&self, counts: &[usize]) -> Result<Selection, &'static str> {
+        score_counts(
+            counts,
+            self.four_of_a_kind,
+            &self.faces,
+            &self.singles_only(),
+        )
+    }
+
+    fn faces(&self) -> DiceFaces {
+        self.faces.clone()
+    }
+
+    fn hot_dice(&self) -> bool {
+        self.hot_dice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn four_kind_rules(rule: FourKindRule) -> Ruleset {
+        Ruleset {
+            four_of_a_kind: rule,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rounding_goes_the_configured_way() {
+        assert_eq!(Rounding::Nearest.apply(450, 100), 500);
+        assert_eq!(Rounding::Up.apply(450, 100), 500);
+        assert_eq!(Rounding::Down.apply(450, 100), 400);
+        assert_eq!(Rounding::Nearest.apply(440, 100), 400);
+        assert_eq!(Rounding::Up.apply(410, 100), 500);
+        for rounding in [Rounding::Nearest, Rounding::Up, Rounding::Down] {
+            assert_eq!(rounding.apply(450, 50), 450);
+            assert_eq!(rounding.apply(450, 0), 450);
+            assert_eq!(rounding.to_string().parse(), Ok(rounding));
+        }
+    }
+
+    fn counts(faces: usize, values: &[DieValue]) -> Vec<usize> {
+        let mut counts = vec![0; faces];
+        for &value in values {
+            counts[value - 1] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn singles_only_follows_the_set_bonus_options() {
+        let hard = Ruleset::preset("hardones").unwrap();
+        let score = |rules: &Ruleset, values| rules.score(&counts(6, values)).unwrap().value();
+        assert_eq!(Ruleset::default().singles_only(), Vec::<DieValue>::new());
+        assert_eq!(hard.singles_only(), [1]);
+        assert_eq!(score(&Ruleset::default(), &[1, 1, 1]), 1000);
+        assert_eq!(score(&hard, &[1, 1, 1]), 300);
+        assert_eq!(score(&Ruleset::default(), &[1; 6]), 4000);
+        assert_eq!(score(&hard, &[1; 6]), 600);
+        assert_eq!(score(&hard, &[5, 5, 5]), 500);
+    }
+
+    #[test]
+    fn singles_only_uses_the_faces_of_the_dice() {
+        let d8 = Ruleset {
+            ones_set_bonus: false,
+            fives_set_bonus: false,
+            ..Ruleset::preset("d8").unwrap()
+        };
+        assert_eq!(d8.singles_only(), [1, 8]);
+        let score = |values| d8.score(&counts(8, values)).unwrap().value();
+        assert_eq!(score(&[1, 1, 1]), 300);
+        assert_eq!(score(&[8, 8, 8]), 150);
+        assert_eq!(score(&[5, 5, 5]), 500);
+    }
+
+    #[test]
+    fn banks_are_exact_without_rounding() {
+        assert_eq!(Ruleset::default().round_bank(450), 450);
+    }
+
+    #[test]
+    fn presets_are_distinct_and_valid() {
+        let presets: Vec<_> = Ruleset::PRESETS
+            .iter()
+            .map(|name| format!("{:?}", Ruleset::preset(name).unwrap()))
+            .collect();
+        assert!(presets.iter().all_unique());
+        for name in Ruleset::PRESETS {
+            let rules = Ruleset::preset(name).unwrap();
+            assert_eq!(rules.faces.to_string().parse(), Ok(rules.faces.clone()));
+            for face in 1..=rules.faces.count {
+                let mut counts = vec![0; rules.faces.count];
+                counts[face - 1] = 3;
+                assert!(
+                    rules.score(&counts).unwrap().value() > 0,
+                    "{} {}",
+                    name,
+                    face
+                );
+            }
+            for &(face, points) in &rules.faces.singles {
+                let mut counts = vec![0; rules.faces.count];
+                counts[face - 1] = 1;
+                assert_eq!(rules.score(&counts).unwrap().value(), points);
+            }
+        }
+        assert_eq!(
+            Ruleset::preset("standard").unwrap().faces,
+            DiceFaces::default()
+        );
+        assert!(Ruleset::preset("nonsense").is_none());
+    }
+
+    #[test]
+    fn four_of_a_kind_scales_by_default() {
+        let selection = Ruleset::default().score(&[0, 0, 4, 0, 0, 0]).unwrap();
+        assert_eq!(selection.value(), 600);
+        assert_eq!(
+            selection.components().collect::<Vec<_>>(),
+            [&ScoreComponent::Set {
+                face: 3,
+                count: 4,
+                base: 300
+            }]
+        );
+    }
+
+    #[test]
+    fn flat_four_of_a_kind_ignores_the_face() {
+        let rules = four_kind_rules(FourKindRule::Flat(1000));
+        assert_eq!(rules.score(&[0, 4, 0, 0, 0, 0]).unwrap().value(), 1000);
+        assert_eq!(rules.score(&[4, 0, 0, 0, 0, 0]).unwrap().value(), 1000);
+    }
+
+    #[test]
+    fn double_triple_doubles_the_set_value() {
+        let rules = four_kind_rules(FourKindRule::DoubleTriple);
+        let selection = rules.score(&[0, 0, 0, 4, 0, 0]).unwrap();
+        assert_eq!(selection.value(), 800);
+        assert_eq!(
+            selection.components().collect::<Vec<_>>(),
+            [&ScoreComponent::FourOfAKind {
+                face: 4,
+                points: 800
+            }]
+        );
+        assert_eq!(rules.score(&[4, 0, 0, 0, 0, 0]).unwrap().value(), 2000);
+    }
+
+    #[test]
+    fn four_kind_rule_leaves_other_sets_alone() {
+        for rule in [
+            FourKindRule::Scale,
+            FourKindRule::Flat(1000),
+            FourKindRule::DoubleTriple,
+        ] {
+            let rules = four_kind_rules(rule);
+            assert_eq!(rules.score(&[0, 0, 3, 0, 0, 0]).unwrap().value(), 300);
+            assert_eq!(rules.score(&[0, 0, 5, 0, 0, 0]).unwrap().value(), 900);
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn even_steven_needs_three_even_dice() {
+        assert_eq!(
+            SpecialEvent::even_steven(&counts(6, &[2, 4, 6, 2])),
+            Some(SpecialEvent::Penalty(
+                100,
+                "Even Steven! Nothing but even numbers."
+            ))
+        );
+        assert_eq!(SpecialEvent::even_steven(&counts(6, &[2, 4])), None);
+        assert_eq!(SpecialEvent::even_steven(&counts(6, &[2, 4, 6, 5])), None);
+        let hook = SpecialEvent::hook_named("evensteven").unwrap();
+        assert_eq!(SpecialEvent::hook_name(hook), Some("evensteven"));
+        assert!(SpecialEvent::hook_named("oddtodd").is_none());
+    }
+}
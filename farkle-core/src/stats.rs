@@ -0,0 +1,418 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Display;
+
+use crate::farkle::RollType;
+
+const SPECIAL_ROLLS: [RollType; 2] = [RollType::Straight, RollType::TriplePair];
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerStats {
+    games: u32,
+    wins: u32,
+    total_score: u32,
+    turns: u32,
+    banked: u32,
+    banked_dice: u32,
+    farkles: u32,
+    farkle_losses: u32,
+    forfeits: u32,
+    wipes: u32,
+    wiped_points: u32,
+    best_turn: u32,
+    straights: u32,
+    triple_pairs: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RollStats {
+    total_rolls: u32,
+    farkles: u32,
+    straights: u32,
+    triple_pairs: u32,
+    simple_rolls: u32,
+    wipes: u32,
+    highest_roll_value: u32,
+    total_value: u32,
+}
+
+impl RollStats {
+    pub fn record(&mut self, roll_type: &RollType, selection_value: u32) -> &mut Self {
+        self.total_rolls += 1;
+        match roll_type {
+            RollType::Farkle => self.farkles += 1,
+            RollType::Straight => self.straights += 1,
+            RollType::TriplePair => self.triple_pairs += 1,
+            RollType::Simple => self.simple_rolls += 1,
+        }
+        self.highest_roll_value = self.highest_roll_value.max(selection_value);
+        self.total_value += selection_value;
+        self
+    }
+
+    pub fn record_wipe(&mut self) -> &mut Self {
+        self.wipes += 1;
+        self
+    }
+
+    pub fn total_rolls(&self) -> u32 {
+        self.total_rolls
+    }
+
+    pub fn wipes(&self) -> u32 {
+        self.wipes
+    }
+
+    pub fn count(&self, roll_type: RollType) -> u32 {
+        match roll_type {
+            RollType::Farkle => self.farkles,
+            RollType::Straight => self.straights,
+            RollType::TriplePair => self.triple_pairs,
+            RollType::Simple => self.simple_rolls,
+        }
+    }
+
+    pub fn highest_roll_value(&self) -> u32 {
+        self.highest_roll_value
+    }
+
+    pub fn farkle_rate(&self) -> f64 {
+        self.per_roll(self.farkles)
+    }
+
+    pub fn expected_value_per_roll(&self) -> f64 {
+        self.per_roll(self.total_value)
+    }
+
+    fn per_roll(&self, total: u32) -> f64 {
+        if self.total_rolls == 0 {
+            0.
+        } else {
+            total as f64 / self.total_rolls as f64
+        }
+    }
+}
+
+impl PlayerStats {
+    pub fn record_roll(&mut self, roll_type: RollType) {
+        match roll_type {
+            RollType::Farkle => self.farkles += 1,
+            RollType::Straight => self.straights += 1,
+            RollType::TriplePair => self.triple_pairs += 1,
+            RollType::Simple => (),
+        }
+    }
+
+    pub fn record_turn(&mut self, banked: u32) {
+        self.turns += 1;
+        self.banked += banked;
+        self.best_turn = self.best_turn.max(banked);
+    }
+
+    pub fn record_banked_dice(&mut self, dice: usize) {
+        self.banked_dice += dice as u32;
+    }
+
+    pub fn record_farkle_loss(&mut self, lost: u32) {
+        self.farkle_losses += lost;
+    }
+
+    pub fn record_forfeit(&mut self) {
+        self.forfeits += 1;
+    }
+
+    pub fn record_wipe(&mut self, lost: u32) {
+        self.wipes += 1;
+        self.wiped_points += lost;
+    }
+
+    pub fn record_game(&mut self, score: u32, won: bool) {
+        self.games += 1;
+        self.total_score += score;
+        if won {
+            self.wins += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.wins
+    }
+
+    pub fn total_score(&self) -> u32 {
+        self.total_score
+    }
+
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    pub fn avg_score_per_turn(&self) -> f64 {
+        if self.turns == 0 {
+            0.
+        } else {
+            self.banked as f64 / self.turns as f64
+        }
+    }
+
+    pub fn points_per_die(&self) -> f64 {
+        if self.banked_dice == 0 {
+            0.
+        } else {
+            self.banked as f64 / self.banked_dice as f64
+        }
+    }
+
+    pub fn farkles(&self) -> u32 {
+        self.farkles
+    }
+
+    pub fn farkle_losses(&self) -> u32 {
+        self.farkle_losses
+    }
+
+    pub fn forfeits(&self) -> u32 {
+        self.forfeits
+    }
+
+    pub fn wipes(&self) -> u32 {
+        self.wipes
+    }
+
+    pub fn wiped_points(&self) -> u32 {
+        self.wiped_points
+    }
+
+    pub fn best_turn(&self) -> u32 {
+        self.best_turn
+    }
+
+    pub fn roll_count(&self, roll_type: RollType) -> u32 {
+        match roll_type {
+            RollType::Farkle => self.farkles,
+            RollType::Straight => self.straights,
+            RollType::TriplePair => self.triple_pairs,
+            RollType::Simple => 0,
+        }
+    }
+
+    pub fn favorite_roll(&self) -> Option<(RollType, u32)> {
+        SPECIAL_ROLLS
+            .iter()
+            .map(|&roll_type| (roll_type, self.roll_count(roll_type)))
+            .filter(|(_, count)| *count > 0)
+            .max_by_key(|(_, count)| *count)
+    }
+
+    fn per_game(&self, total: u32) -> f64 {
+        if self.games == 0 {
+            0.
+        } else {
+            total as f64 / self.games as f64
+        }
+    }
+
+    pub fn to_report(&self, player_name: &str) -> String {
+        format!("=== {} ===\n{}", player_name, self)
+    }
+
+    pub fn csv_header() -> &'static str {
+        "name,games,wins,total_score,farkles,best_turn,straights,triple_pairs"
+    }
+
+    pub fn to_csv_row(&self, name: &str) -> String {
+        let name = if name.contains([',', '"', '\n']) {
+            format!("\"{}\"", name.replace('"', "\"\""))
+        } else {
+            name.to_string()
+        };
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            name,
+            self.games,
+            self.wins,
+            self.total_score,
+            self.farkles,
+            self.best_turn,
+            self.straights,
+            self.triple_pairs
+        )
+    }
+}
+
+impl Display for PlayerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Games: {} | Wins: {} ({:.0}%)",
+            self.games,
+            self.wins,
+            self.per_game(self.wins) * 100.
+        )?;
+        writeln!(
+            f,
+            "Total Score: {} | Avg: {:.0}/game",
+            self.total_score,
+            self.per_game(self.total_score)
+        )?;
+        writeln!(
+            f,
+            "Farkles: {} ({:.1}/game, {} points lost) | Forfeits: {} | Best Turn: {}",
+            self.farkles,
+            self.per_game(self.farkles),
+            self.farkle_losses,
+            self.forfeits,
+            self.best_turn
+        )?;
+        if self.wipes > 0 {
+            writeln!(
+                f,
+                "Score wipes: {} ({} points lost)",
+                self.wipes, self.wiped_points
+            )?;
+        }
+        writeln!(f, "Points per banked die: {:.0}", self.points_per_die())?;
+        match self.favorite_roll() {
+            Some((roll_type, count)) => {
+                write!(f, "Favorite Roll: {:?} ({} times)", roll_type, count)
+            }
+            None => write!(f, "Favorite Roll: None"),
+        }
+    }
+}
+
+impl Display for RollStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Rolls: {} | Farkles: {} ({:.1}%)",
+            self.total_rolls,
+            self.farkles,
+            self.farkle_rate() * 100.
+        )?;
+        writeln!(
+            f,
+            "Simple: {} | Straights: {} | Triple pairs: {}",
+            self.simple_rolls, self.straights, self.triple_pairs
+        )?;
+        if self.wipes > 0 {
+            writeln!(f, "Score wipes: {}", self.wipes)?;
+        }
+        write!(
+            f,
+            "Highest roll: {} | Avg: {:.0}/roll",
+            self.highest_roll_value,
+            self.expected_value_per_roll()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_games() -> PlayerStats {
+        let mut stats = PlayerStats::default();
+        stats.record_game(1500, true);
+        stats.record_game(500, false);
+        stats.record_game(1000, false);
+        stats.record_roll(RollType::Farkle);
+        stats.record_roll(RollType::Farkle);
+        stats.record_roll(RollType::Straight);
+        stats.record_farkle_loss(350);
+        stats.record_turn(700);
+        stats.record_turn(0);
+        stats.record_banked_dice(3);
+        stats
+    }
+
+    #[test]
+    fn reports_round_the_rates() {
+        assert_eq!(
+            three_games().to_report("Ann"),
+            "=== Ann ===\n\
+             Games: 3 | Wins: 1 (33%)\n\
+             Total Score: 3000 | Avg: 1000/game\n\
+             Farkles: 2 (0.7/game, 350 points lost) | Forfeits: 0 | Best Turn: 700\n\
+             Points per banked die: 233\n\
+             Favorite Roll: Straight (1 times)"
+        );
+        let mut stats = three_games();
+        stats.record_game(0, true);
+        stats.record_wipe(400);
+        let report = stats.to_report("Bob");
+        assert!(report.contains("Wins: 2 (50%)"));
+        assert!(report.contains("Farkles: 2 (0.5/game"));
+        assert!(report.contains("Score wipes: 1 (400 points lost)"));
+        stats.record_game(0, true);
+        assert!(stats.to_report("Bob").contains("Wins: 3 (60%)"));
+        stats.record_game(0, true);
+        assert!(stats.to_report("Bob").contains("Wins: 4 (67%)"));
+        assert!(PlayerStats::default()
+            .to_report("Cat")
+            .contains("Wins: 0 (0%)"));
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let header = PlayerStats::csv_header();
+        let row = three_games().to_csv_row("Ann");
+        assert_eq!(row, "Ann,3,1,3000,2,700,1,0");
+        assert_eq!(header.split(',').count(), row.split(',').count());
+        assert_eq!(
+            PlayerStats::default().to_csv_row("Smith, \"Ann\""),
+            "\"Smith, \"\"Ann\"\"\",0,0,0,0,0,0,0"
+        );
+    }
+
+    #[test]
+    fn roll_stats_count_each_roll() {
+        let mut stats = RollStats::default();
+        stats
+            .record(&RollType::Farkle, 0)
+            .record(&RollType::Simple, 300)
+            .record(&RollType::Straight, 1500)
+            .record(&RollType::Farkle, 0)
+            .record(&RollType::TriplePair, 750)
+            .record(&RollType::Simple, 150)
+            .record_wipe();
+        assert_eq!((stats.total_rolls(), stats.wipes()), (6, 1));
+        assert_eq!(stats.count(RollType::Farkle), 2);
+        assert_eq!(stats.count(RollType::Simple), 2);
+        assert_eq!(stats.count(RollType::Straight), 1);
+        assert_eq!(stats.count(RollType::TriplePair), 1);
+        assert_eq!(stats.highest_roll_value(), 1500);
+        assert!((stats.farkle_rate() - 1. / 3.).abs() < 1e-9);
+        assert!((stats.expected_value_per_roll() - 450.).abs() < 1e-9);
+        assert_eq!(
+            stats.to_string(),
+            "Rolls: 6 | Farkles: 2 (33.3%)\n\
+             Simple: 2 | Straights: 1 | Triple pairs: 1\n\
+             Score wipes: 1\n\
+             Highest roll: 1500 | Avg: 450/roll"
+        );
+    }
+
+    #[test]
+    fn roll_stats_start_empty() {
+        let stats = RollStats::default();
+        assert_eq!(stats.total_rolls(), 0);
+        assert_eq!(stats.farkle_rate(), 0.);
+        assert_eq!(stats.expected_value_per_roll(), 0.);
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub name: String,
+    pub members: Vec<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum TeamScoring {
+    #[default]
+    Shared,
+    Separate,
+}
+
+impl Team {
+    pub fn new(name: &str, members: Vec<usize>) -> Self {
+        Team {
+            name: name.to_string(),
+            members,
+        }
+    }
+
+    pub fn pairs(players: usize) -> Vec<Team> {
+        (0..players)
+            .chunks(2)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, members)| Team::new(&format!("Team {}", idx + 1), members.collect()))
+            .collect()
+    }
+
+    pub fn contains(&self, player: usize) -> bool {
+        self.members.contains(&player)
+    }
+}
+
+impl Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let members = self.members.iter().map(|player| player + 1).join(",");
+        write!(f, "{}:{}", self.name, members)
+    }
+}
+
+impl FromStr for Team {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, members) = s
+            .rsplit_once(':')
+            .ok_or("Expected a team name, a colon and player numbers")?;
+        if name.trim().is_empty() {
+            return Err("The team needs a name");
+        }
+        let members = members
+            .split(',')
+            .map(|player| match player.trim().parse::<usize>() {
+                Ok(player) if player > 0 => Ok(player - 1),
+                _ => Err("Expected player numbers starting from 1"),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Team::new(name.trim(), members))
+    }
+}
+
+impl Display for TeamScoring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TeamScoring::Shared => write!(f, "shared"),
+            TeamScoring::Separate => write!(f, "separate"),
+        }
+    }
+}
+
+impl FromStr for TeamScoring {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shared" => Ok(TeamScoring::Shared),
+            "separate" => Ok(TeamScoring::Separate),
+            _ => Err("Expected shared or separate"),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use rand::Rng;
+
+use crate::bot::{simulate_n_turns, BotStrategy, Difficulty, GreedyBot};
+use crate::farkle::Hand;
+use crate::rules::ScoringRules;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TournamentConfig {
+    pub games: usize,
+    pub turns: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TunedThreshold {
+    pub bank_threshold: u32,
+    pub win_rate: f64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        TournamentConfig {
+            games: 2000,
+            turns: 10,
+        }
+    }
+}
+
+// Both bots take their turns in order, so neither sees a different run of dice
+fn play_match(
+    first: &dyn BotStrategy,
+    second: &dyn BotStrategy,
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> (u32, u32) {
+    let mut scores = (0, 0);
+    for _ in 0..config.turns {
+        for (bot, score) in [(first, &mut scores.0), (second, &mut scores.1)] {
+            *score += simulate_n_turns(1, bot, &Hand::default(), rules, rng)[0].banked;
+        }
+    }
+    scores
+}
+
+pub fn head_to_head(
+    first: &dyn BotStrategy,
+    second: &dyn BotStrategy,
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> f64 {
+    let mut points = 0.;
+    for game in 0..config.games {
+        // Alternate who opens so the second player is never always behind
+        let (a, b) = if game % 2 == 0 {
+            play_match(first, second, config, rules, rng)
+        } else {
+            let (b, a) = play_match(second, first, config, rules, rng);
+            (a, b)
+        };
+        points += match a.cmp(&b) {
+            std::cmp::Ordering::Greater => 1.,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.,
+        };
+    }
+    points / config.games.max(1) as f64
+}
+
+pub fn round_robin(
+    strategies: &[&dyn BotStrategy],
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> Vec<f64> {
+    let mut win_rates = vec![0.; strategies.len()];
+    for i in 0..strategies.len() {
+        for j in i + 1..strategies.len() {
+            let rate = head_to_head(strategies[i], strategies[j], config, rules, rng);
+            win_rates[i] += rate;
+            win_rates[j] += 1. - rate;
+        }
+    }
+    let opponents = strategies.len().saturating_sub(1).max(1) as f64;
+    win_rates.iter().map(|wins| wins / opponents).collect()
+}
+
+pub fn tune_threshold(
+    candidates: impl IntoIterator<Item = u32>,
+    target_win_rate: f64,
+    reference: &dyn BotStrategy,
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> Option<TunedThreshold> {
+    candidates
+        .into_iter()
+        .map(|bank_threshold| {
+            let bot = GreedyBot { bank_threshold };
+            TunedThreshold {
+                bank_threshold,
+                win_rate: head_to_head(&bot, reference, config, rules, rng),
+            }
+        })
+        .min_by(|a, b| {
+            let a = (a.win_rate - target_win_rate).abs();
+            let b = (b.win_rate - target_win_rate).abs();
+            a.total_cmp(&b)
+        })
+}
+
+pub fn tune_difficulties(
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> Vec<(Difficulty, Option<TunedThreshold>)> {
+    let reference = GreedyBot::default();
+    Difficulty::ALL
+        .iter()
+        .map(|&difficulty| {
+            let tuned = tune_threshold(
+                (50..=2000).step_by(50),
+                difficulty.target_win_rate(),
+                &reference,
+                config,
+                rules,
+                rng,
+            );
+            (difficulty, tuned)
+        })
+        .collect()
+}
+
+pub fn rank_difficulties(
+    config: TournamentConfig,
+    rules: &dyn ScoringRules,
+    rng: &mut impl Rng,
+) -> Vec<(Difficulty, f64)> {
+    let bots = Difficulty::ALL.map(|difficulty| difficulty.strategy());
+    let strategies: Vec<&dyn BotStrategy> = bots.iter().map(|bot| bot as _).collect();
+    Difficulty::ALL
+        .into_iter()
+        .zip(round_robin(&strategies, config, rules, rng))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::rules::StandardRules;
+
+    const CONFIG: TournamentConfig = TournamentConfig {
+        games: 400,
+        turns: 10,
+    };
+
+    #[test]
+    fn harness_is_seeded() {
+        let run = || {
+            let mut rng = StdRng::seed_from_u64(7);
+            let easy = Difficulty::Easy.strategy();
+            head_to_head(
+                &easy,
+                &GreedyBot::default(),
+                CONFIG,
+                &StandardRules,
+                &mut rng,
+            )
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn difficulties_rank_in_order() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let ranked = rank_difficulties(CONFIG, &StandardRules, &mut rng);
+        let rates: Vec<_> = ranked.iter().map(|&(_, rate)| rate).collect();
+        assert_eq!(ranked.len(), Difficulty::ALL.len());
+        assert!(
+            rates.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            ranked
+        );
+        assert!((rates.iter().sum::<f64>() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tuning_picks_the_closest_threshold() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let reference = GreedyBot::default();
+        let tuned = tune_threshold(
+            [50, 350, 3000],
+            Difficulty::Hard.target_win_rate(),
+            &reference,
+            CONFIG,
+            &StandardRules,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(tuned.bank_threshold, 350);
+        assert!((tuned.win_rate - 0.5).abs() < 0.1, "{:?}", tuned);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rsfarkle::farkle::GameState;
+use rsfarkle::game::{Game, GameConfig};
+use rsfarkle::notation;
+use rsfarkle::rules::Ruleset;
+
+fn config(seed: u64) -> GameConfig {
+    GameConfig {
+        player_names: vec!["Ann".to_string(), "Bob".to_string(), "Cat".to_string()],
+        turns: 5,
+        seed: Some(seed),
+        ..Default::default()
+    }
+}
+
+fn play_randomly(config: GameConfig, seed: u64) -> Game {
+    let mut game = Game::new(config);
+    let mut rng = StdRng::seed_from_u64(seed);
+    while !game.is_over() {
+        while game.state() != GameState::TurnEnded {
+            let moves = game.legal_moves();
+            let _ = game.apply(moves[rng.gen_range(0..moves.len())]);
+        }
+        game.next_turn();
+    }
+    game
+}
+
+fn scores(game: &Game) -> Vec<u32> {
+    game.players().iter().map(|player| player.score()).collect()
+}
+
+#[test]
+fn seeded_games_replay_from_their_transcripts() {
+    for seed in 0..20 {
+        let game = play_randomly(config(seed), seed);
+        let (replayed, summary) = Game::from_transcript(&game.transcript()).unwrap();
+        assert_eq!(scores(&replayed), scores(&game));
+        assert_eq!(replayed.winners(), game.winners());
+        assert!(summary.standings().eq(game.summary().standings()));
+        assert_eq!(summary.turns(), 5);
+    }
+}
+
+#[test]
+fn a_seed_fixes_the_whole_game() {
+    let first = play_randomly(config(7), 1);
+    let second = play_randomly(config(7), 1);
+    assert_eq!(first.transcript(), second.transcript());
+}
+
+#[test]
+fn recorded_games_round_trip_through_notation() {
+    for (seed, preset) in (0..30).zip(["standard", "zilch", "d8"].iter().cycle()) {
+        let config = GameConfig {
+            rules: Ruleset::preset(preset).unwrap(),
+            ..config(seed)
+        };
+        let game = play_randomly(config.clone(), seed);
+        let text = notation::to_string(game.turn_log());
+        let turns = notation::parse(&text).unwrap();
+        let rewritten: String = turns.iter().map(|turn| turn.to_string() + "\n").collect();
+        assert_eq!(rewritten, text);
+
+        let rebuilt = Game::from_notation(config, &text).unwrap();
+        assert_eq!(rebuilt.turn_log(), game.turn_log());
+        assert_eq!(scores(&rebuilt), scores(&game));
+    }
+}
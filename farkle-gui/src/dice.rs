@@ -0,0 +1,245 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Display;
+use std::path::Path;
+
+use eframe::egui::{self, Color32, RichText, Stroke, Ui, Vec2};
+use eframe::epaint::{ColorImage, TextureHandle};
+use image::DynamicImage;
+use rsfarkle::farkle::Die;
+
+#[cfg(feature = "spritesheet")]
+use crate::sprites::SpriteSheet;
+
+pub const DEFAULT_DIE_SIZE: f32 = 32.;
+const MAX_SPRITE_SIZE: u32 = 256;
+
+pub struct DieRenderer {
+    dice: [Option<(TextureHandle, Vec2)>; 6],
+    die_size: f32,
+}
+
+pub enum RenderState {
+    InGame(bool),
+    Splash,
+    Editable,
+}
+
+pub enum DieEdit {
+    CycleFace,
+    ToggleKept,
+}
+
+pub struct DieOverlay {
+    pub color: Color32,
+    pub label: Option<String>,
+    pub border_width: f32,
+}
+
+#[derive(Debug)]
+pub enum SpriteSetError {
+    Image(String, image::ImageError),
+    MismatchedSizes,
+}
+
+impl Display for SpriteSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpriteSetError::Image(file, e) => write!(f, "Failed to load {}: {}", file, e),
+            SpriteSetError::MismatchedSizes => {
+                write!(f, "All die sprites must have the same dimensions")
+            }
+        }
+    }
+}
+
+macro_rules! get_die_sprites {
+    ($dice:expr, $ui:expr, $( $idx:expr ),*) => {
+        $(
+        let sprite = include_bytes!(concat!("dice/", $idx, ".png"));
+        let sprite = load_image_from_bytes(sprite, format!("Die {}", $idx), $ui);
+        $dice[$idx - 1] = Some(sprite);
+        )*
+    };
+}
+
+// Based on code from
+// https://github.com/emilk/egui/blob/0.16.0/eframe/examples/image.rs
+pub fn load_image_from_bytes(
+    image_data: &[u8],
+    name: String,
+    ui: &mut Ui,
+) -> (TextureHandle, Vec2) {
+    let image = image::load_from_memory(image_data).expect("Failed to load image");
+    upload_image(image, name, ui)
+}
+
+fn upload_image(image: DynamicImage, name: String, ui: &mut Ui) -> (TextureHandle, Vec2) {
+    let image = if image.width().max(image.height()) > MAX_SPRITE_SIZE {
+        image.thumbnail(MAX_SPRITE_SIZE, MAX_SPRITE_SIZE)
+    } else {
+        image
+    };
+    let image_buffer = image.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    let pixels = image_buffer.into_vec();
+    let image = ColorImage::from_rgba_unmultiplied(size, &pixels);
+
+    // Allocate a texture:
+    let texture = ui.ctx().load_texture(name, image, Default::default());
+    let size = Vec2::new(size[0] as f32, size[1] as f32);
+    (texture, size)
+}
+
+impl Default for DieRenderer {
+    fn default() -> Self {
+        DieRenderer {
+            dice: Default::default(),
+            die_size: DEFAULT_DIE_SIZE,
+        }
+    }
+}
+
+impl DieRenderer {
+    #[cfg(feature = "spritesheet")]
+    pub fn from_sprite_sheet(sheet: SpriteSheet) -> Self {
+        let mut renderer = DieRenderer::default();
+        for (idx, die) in renderer
+            .dice
+            .iter_mut()
+            .enumerate()
+            .take(sheet.sprite_count())
+        {
+            *die = Some(sheet.get_sprite(idx));
+        }
+        renderer
+    }
+
+    pub fn set_die_size(&mut self, size: f32) {
+        self.die_size = size;
+    }
+
+    pub fn init(&mut self, ui: &mut Ui) -> Result<(), image::ImageError> {
+        get_die_sprites!(self.dice, ui, 1, 2, 3, 4, 5, 6);
+        Ok(())
+    }
+
+    pub fn init_from_dir(&mut self, dir: &Path, ui: &mut Ui) -> Result<(), SpriteSetError> {
+        let mut images = Vec::with_capacity(self.dice.len());
+        for idx in 1..=self.dice.len() {
+            let file = format!("{}.png", idx);
+            let image = image::open(dir.join(&file)).map_err(|e| SpriteSetError::Image(file, e))?;
+            images.push(image);
+        }
+        let size = (images[0].width(), images[0].height());
+        if images
+            .iter()
+            .any(|image| (image.width(), image.height()) != size)
+        {
+            return Err(SpriteSetError::MismatchedSizes);
+        }
+        for (idx, image) in images.into_iter().enumerate() {
+            let name = format!("Custom die {}", idx + 1);
+            self.dice[idx] = Some(upload_image(image, name, ui));
+        }
+        Ok(())
+    }
+
+    pub fn needs_init(&self) -> bool {
+        self.dice[0].is_none()
+    }
+
+    pub fn draw_die(&self, die: &Die, state: RenderState, ui: &mut Ui) -> bool {
+        self.draw_die_with_overlay(die, state, None, ui)
+    }
+
+    pub fn draw_die_with_overlay(
+        &self,
+        die: &Die,
+        state: RenderState,
+        overlay: Option<DieOverlay>,
+        ui: &mut Ui,
+    ) -> bool {
+        self.draw(die, state, overlay, ui).clicked()
+    }
+
+    pub fn draw_editable_die(
+        &self,
+        die: &Die,
+        overlay: Option<DieOverlay>,
+        ui: &mut Ui,
+    ) -> Option<DieEdit> {
+        let response = self
+            .draw(die, RenderState::Editable, overlay, ui)
+            .on_hover_text("Click to change the face, right-click to keep or release");
+        if response.clicked() {
+            Some(DieEdit::CycleFace)
+        } else if response.secondary_clicked() {
+            Some(DieEdit::ToggleKept)
+        } else {
+            None
+        }
+    }
+
+    fn draw(
+        &self,
+        die: &Die,
+        state: RenderState,
+        overlay: Option<DieOverlay>,
+        ui: &mut Ui,
+    ) -> egui::Response {
+        let sprite = self.dice.get(die.value() - 1).and_then(Option::as_ref);
+        ui.vertical(|ui| {
+            let mut button = match sprite {
+                Some((texture, native)) => {
+                    let size = *native * (self.die_size / native.max_elem());
+                    egui::Button::opt_image_and_text(
+                        Some(egui::Image::from_texture(texture).fit_to_exact_size(size)),
+                        None,
+                    )
+                }
+                None => {
+                    egui::Button::new(die.value().to_string()).min_size(Vec2::splat(self.die_size))
+                }
+            };
+            if let Some(overlay) = &overlay {
+                button = button.stroke(Stroke::new(overlay.border_width, overlay.color));
+            }
+            let response = ui.add(button);
+            if let Some(DieOverlay {
+                color,
+                label: Some(label),
+                ..
+            }) = &overlay
+            {
+                ui.label(RichText::new(label).color(*color));
+            } else if let RenderState::InGame(pickable) = state {
+                if die.picked() {
+                    if die.picked_this_roll() {
+                        ui.label("^");
+                    } else {
+                        ui.label("X");
+                    }
+                } else if pickable {
+                    ui.label("?");
+                }
+            } else if let (RenderState::Editable, true) = (state, die.picked()) {
+                ui.label("X");
+            }
+            response
+        })
+        .inner
+    }
+}
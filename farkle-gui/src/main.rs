@@ -0,0 +1,1698 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+// Based on code in https://github.com/emilk/eframe_template
+
+pub mod dice;
+#[cfg(feature = "spritesheet")]
+pub mod sprites;
+
+use dice::{DieEdit, DieOverlay, DieRenderer, RenderState, DEFAULT_DIE_SIZE};
+
+use eframe::egui::{Color32, Context, Ui};
+use eframe::{egui, Frame};
+
+use itertools::Itertools;
+
+use std::{num::NonZeroU32, path::PathBuf};
+
+use rsfarkle::farkle::*;
+use rsfarkle::game::*;
+use rsfarkle::history::GameHistory;
+use rsfarkle::order::{RoundOrder, TurnOrder};
+use rsfarkle::rules::*;
+use rsfarkle::team::{Team, TeamScoring};
+
+#[forbid(unsafe_code)]
+#[derive(Debug, PartialEq)]
+enum AppAction {
+    StartGame,
+    WatchReplay,
+    ExitApp,
+}
+
+const ROLL_OFF_ANIMATION_SECS: f64 = 1.;
+const SCORE_FLASH_SECS: f64 = 1.;
+const SCORE_FLASH_RISE: f32 = 20.;
+const KEPT_DIE_SCALE: f32 = 0.6;
+const COMPACT_MARGIN: f32 = 4.;
+const COMPACT_WINDOW_SIZE: egui::Vec2 = egui::vec2(480., 240.);
+const DEFAULT_REPLAY_DELAY: f64 = 1.;
+const DEFAULT_ROLL_LIMIT: u32 = 4;
+const ROLL_HISTORY_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy)]
+struct ScoreFlash {
+    player: usize,
+    points: u32,
+    started: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RollOffView {
+    round: usize,
+    round_started: f64,
+}
+
+#[derive(Debug)]
+struct ReplayView {
+    playback: Playback,
+    paused: bool,
+    last_step: f64,
+    last_move: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum MenuPage {
+    Main,
+    RulesReference,
+    ActiveRules,
+    ConfirmAbandon,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+enum TurnEndPhase {
+    #[default]
+    Review,
+    Ready,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Farkle {
+    #[serde(skip)]
+    game: Game,
+    #[serde(skip)]
+    roll: Roll,
+    #[serde(skip)]
+    analysis_roll: Roll,
+    #[serde(skip)]
+    analysis_hand: u32,
+    #[serde(skip)]
+    roll_state: Option<RollType>,
+    #[serde(skip)]
+    bad_selection: Option<String>,
+
+    #[serde(skip)]
+    turn_end: TurnEndPhase,
+    #[serde(skip)]
+    banked_hand: Vec<Selection>,
+
+    #[serde(skip)]
+    farkle_lost: Option<u32>,
+    #[serde(skip)]
+    score_wiped: Option<(usize, u32)>,
+    #[serde(skip)]
+    pot_claimed: Option<u32>,
+    #[serde(skip)]
+    special_event: Option<SpecialEvent>,
+    #[serde(skip)]
+    score_flashes: Vec<ScoreFlash>,
+    #[serde(skip)]
+    confirm_forfeit: bool,
+    #[serde(skip)]
+    missed_points: Option<(u32, u32)>,
+    #[serde(skip)]
+    coached_roll: Option<usize>,
+    #[serde(skip)]
+    game_in_progress: bool,
+    #[serde(skip)]
+    roll_off_view: Option<RollOffView>,
+    #[serde(skip)]
+    replay_view: Option<ReplayView>,
+    #[serde(skip)]
+    replay_error: Option<String>,
+    #[serde(skip)]
+    menu: Option<MenuPage>,
+    #[serde(skip)]
+    menu_status: Option<String>,
+    #[serde(skip)]
+    export_status: Option<String>,
+
+    #[serde(skip)]
+    die_sprites: DieRenderer,
+    #[serde(skip)]
+    history: GameHistory,
+    #[serde(skip)]
+    history_error: Option<String>,
+
+    player_names: Vec<String>,
+    #[serde(default)]
+    handicaps: Vec<u32>,
+    player_count: usize,
+    turn_count: usize,
+    #[serde(default)]
+    review_turns: bool,
+    #[serde(default)]
+    shared_pot: bool,
+    #[serde(default)]
+    highlight_best: bool,
+    #[serde(default)]
+    coach_selections: bool,
+    #[serde(default)]
+    suggest_bank: bool,
+    #[serde(default = "default_die_size")]
+    die_size: f32,
+    #[serde(default)]
+    roll_for_order: bool,
+    #[serde(default)]
+    hidden_scores: bool,
+    #[serde(default)]
+    no_hot_dice: bool,
+    #[serde(default)]
+    roll_limit: Option<u32>,
+    #[serde(default)]
+    sudden_death: bool,
+    #[serde(default)]
+    zilch_wipe: bool,
+    #[serde(default)]
+    turn_order: TurnOrder,
+    #[serde(default)]
+    teams_of_two: bool,
+    #[serde(default = "default_preset")]
+    preset: String,
+    #[serde(default)]
+    custom_dice_dir: Option<PathBuf>,
+    #[serde(default)]
+    compact: bool,
+    #[serde(default = "default_replay_delay")]
+    replay_delay: f64,
+    #[serde(skip)]
+    sprite_error: Option<String>,
+}
+
+fn default_preset() -> String {
+    Ruleset::PRESETS[0].to_string()
+}
+
+fn default_die_size() -> f32 {
+    DEFAULT_DIE_SIZE
+}
+
+fn default_replay_delay() -> f64 {
+    DEFAULT_REPLAY_DELAY
+}
+
+impl Default for Farkle {
+    fn default() -> Self {
+        Farkle {
+            game: Game::default(),
+            farkle_lost: None,
+            score_wiped: None,
+            pot_claimed: None,
+            special_event: None,
+            score_flashes: vec![],
+            confirm_forfeit: false,
+            missed_points: None,
+            coached_roll: None,
+            game_in_progress: false,
+            player_names: vec![],
+            handicaps: vec![],
+            player_count: 1,
+            turn_count: 5,
+            roll: Roll::default(),
+            analysis_roll: Roll::default(),
+            analysis_hand: 0,
+            roll_state: None,
+            die_sprites: DieRenderer::default(),
+            history: GameHistory::default(),
+            history_error: None,
+            bad_selection: None,
+            turn_end: TurnEndPhase::default(),
+            banked_hand: vec![],
+            review_turns: false,
+            shared_pot: false,
+            highlight_best: false,
+            coach_selections: false,
+            suggest_bank: false,
+            die_size: DEFAULT_DIE_SIZE,
+            roll_for_order: false,
+            hidden_scores: false,
+            no_hot_dice: false,
+            roll_limit: None,
+            sudden_death: false,
+            zilch_wipe: false,
+            turn_order: TurnOrder::Sequential,
+            teams_of_two: false,
+            preset: default_preset(),
+            custom_dice_dir: None,
+            compact: false,
+            replay_delay: DEFAULT_REPLAY_DELAY,
+            sprite_error: None,
+            roll_off_view: None,
+            replay_view: None,
+            replay_error: None,
+            menu: None,
+            menu_status: None,
+            export_status: None,
+        }
+    }
+}
+
+#[cfg(feature = "turn-timer")]
+fn turn_time_hint(player: &Player) -> Option<String> {
+    player
+        .average_turn_time()
+        .map(|average| format!("avg {}s/turn", average.as_secs()))
+}
+
+#[cfg(not(feature = "turn-timer"))]
+fn turn_time_hint(_player: &Player) -> Option<String> {
+    None
+}
+
+fn best_selection(roll: &Roll, rules: &dyn ScoringRules) -> [bool; 6] {
+    let available: Vec<bool> = roll
+        .dice()
+        .iter()
+        .map(|die| !die.picked() || die.picked_this_roll())
+        .collect();
+    let mut best = (0, [false; 6]);
+    for mask in 1..1 << available.len() {
+        let chosen: [bool; 6] = core::array::from_fn(|i| mask >> i & 1 == 1);
+        if chosen.iter().zip(&available).any(|(&c, &a)| c && !a) {
+            continue;
+        }
+        let mut counts = vec![0; roll.faces().count];
+        for (die, _) in roll.dice().iter().zip(chosen).filter(|(_, c)| *c) {
+            counts[die.value() - 1] += 1;
+        }
+        if let Ok(selection) = rules.score(&counts) {
+            if selection.value() > best.0 {
+                best = (selection.value(), chosen);
+            }
+        }
+    }
+    best.1
+}
+
+impl Farkle {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app: Farkle = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        app.save_history();
+        app
+    }
+
+    fn save_history(&mut self) {
+        let Some(path) = GameHistory::default_path() else {
+            return;
+        };
+        self.history_error = self
+            .history
+            .save(&path)
+            .err()
+            .map(|e| format!("Failed to save session history: {}", e));
+    }
+
+    fn get_current_player(&self) -> &Player {
+        self.game.current_player()
+    }
+
+    fn get_input(name: &str, key: egui::Key, ctx: &Context, ui: &mut Ui) -> bool {
+        ui.button(name).clicked() || (ui.is_enabled() && ctx.input(|i| i.key_released(key)))
+    }
+
+    fn draw_kept_dice(&mut self, ui: &mut Ui) {
+        let kept = self.get_current_player().kept_values_this_turn();
+        if kept.is_empty() {
+            return;
+        }
+        self.die_sprites
+            .set_die_size(self.die_size * KEPT_DIE_SCALE);
+        ui.horizontal(|ui| {
+            ui.set_enabled(false);
+            ui.visuals_mut().button_frame = false;
+            ui.label("Kept:");
+            for value in kept {
+                self.die_sprites
+                    .draw_die(&Die::new_with_value(value), RenderState::Splash, ui);
+            }
+        });
+    }
+
+    fn draw_dice(&mut self, ui: &mut Ui) {
+        if self.game_in_progress {
+            self.draw_kept_dice(ui);
+        }
+        self.die_sprites.set_die_size(self.die_size);
+        let roll = if self.game_in_progress {
+            self.game.roll()
+        } else {
+            &self.roll
+        };
+        let pickable = if self.game_in_progress {
+            roll.determine_pickable(None)
+        } else {
+            [false; 6]
+        };
+        let state = self.game.state();
+        let best = if self.game_in_progress && self.highlight_best && state == GameState::Picking {
+            best_selection(roll, &self.game.config().rules)
+        } else {
+            [false; 6]
+        };
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            ui.visuals_mut().button_frame = false;
+            for (idx, ((die, can_pick), best)) in
+                roll.dice().iter().zip(pickable).zip(best).enumerate()
+            {
+                let overlay = best.then(|| DieOverlay {
+                    color: Color32::GOLD,
+                    label: Some("★".into()),
+                    border_width: 3.0,
+                });
+                if self.die_sprites.draw_die_with_overlay(
+                    die,
+                    if self.game_in_progress {
+                        RenderState::InGame(can_pick && state != GameState::FirstRoll)
+                    } else {
+                        RenderState::Splash
+                    },
+                    overlay,
+                    ui,
+                ) {
+                    clicked = Some(idx);
+                }
+            }
+        });
+        if self.game_in_progress && self.replay_view.is_none() && state != GameState::FirstRoll {
+            if let Some(idx) = clicked {
+                let _ = self.game.apply(GameAction::ToggleDie(idx));
+            }
+        }
+    }
+
+    fn settings(&mut self, ui: &mut Ui) -> Option<AppAction> {
+        ui.label("Number of turns");
+        ui.add(egui::Slider::new(&mut self.turn_count, 1..=20usize));
+
+        ui.label("Number of players");
+        ui.add(egui::Slider::new(&mut self.player_count, 1..=10usize));
+        if self.player_count > self.player_names.len() {
+            self.player_names
+                .resize_with(self.player_count, String::new);
+        }
+        self.handicaps.resize(self.player_names.len(), 0);
+        for (name, handicap) in self
+            .player_names
+            .iter_mut()
+            .zip(&mut self.handicaps)
+            .take(self.player_count)
+        {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(name);
+                ui.add(egui::DragValue::new(handicap).speed(50).prefix("+"))
+                    .on_hover_text("Starting score");
+            });
+        }
+        egui::ComboBox::from_label("Rules")
+            .selected_text(&self.preset)
+            .show_ui(ui, |ui| {
+                for &preset in Ruleset::PRESETS {
+                    ui.selectable_value(&mut self.preset, preset.to_string(), preset);
+                }
+            });
+        ui.checkbox(&mut self.review_turns, "Review turn before proceeding");
+        ui.checkbox(&mut self.shared_pot, "Shared pot");
+        ui.checkbox(&mut self.highlight_best, "Highlight best selection");
+        ui.checkbox(&mut self.coach_selections, "Warn about missed points");
+        ui.checkbox(&mut self.suggest_bank, "Suggest whether to bank");
+        ui.horizontal(|ui| {
+            ui.label("Turn order");
+            ui.radio_value(&mut self.turn_order, TurnOrder::Sequential, "Sequential");
+            ui.radio_value(&mut self.turn_order, TurnOrder::Random, "Random");
+            ui.radio_value(&mut self.turn_order, TurnOrder::Handicap, "Weakest first")
+                .on_hover_text("Players with the biggest handicap go first");
+        });
+        ui.checkbox(&mut self.roll_for_order, "Roll for turn order");
+        ui.checkbox(&mut self.hidden_scores, "Hide scores until the end");
+        ui.checkbox(&mut self.no_hot_dice, "No hot dice")
+            .on_hover_text("Bank once all six dice have scored instead of rolling them again");
+        ui.checkbox(&mut self.sudden_death, "Break ties with sudden death");
+        ui.checkbox(&mut self.zilch_wipe, "Three 1s wipe your score (Zilch)");
+        ui.checkbox(&mut self.teams_of_two, "Play in teams of two")
+            .on_hover_text("Players 1 & 2, 3 & 4 and so on share a score and can pass dice");
+        ui.horizontal(|ui| {
+            let mut limited = self.roll_limit.is_some();
+            ui.checkbox(&mut limited, "Limit rolls per turn");
+            self.roll_limit = limited.then(|| self.roll_limit.unwrap_or(DEFAULT_ROLL_LIMIT));
+            if let Some(limit) = &mut self.roll_limit {
+                ui.add(egui::DragValue::new(limit).clamp_range(1..=20));
+            }
+        });
+        ui.checkbox(&mut self.compact, "Compact mode (F2)");
+
+        ui.label("Die size");
+        ui.add(egui::Slider::new(&mut self.die_size, 16.0..=128.0));
+        if ui.button("Custom dice folder…").clicked() {
+            if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                self.custom_dice_dir = Some(dir);
+                self.load_die_sprites(ui);
+            }
+        }
+        if self.custom_dice_dir.is_some() && ui.button("Use built-in dice").clicked() {
+            self.custom_dice_dir = None;
+            self.load_die_sprites(ui);
+        }
+        if let Some(err) = &self.sprite_error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        if ui.button("New Game").clicked() {
+            return Some(AppAction::StartGame);
+        }
+        ui.horizontal(|ui| {
+            ui.label("Replay speed");
+            ui.add(
+                egui::Slider::new(&mut self.replay_delay, 0.1..=3.0)
+                    .suffix(" s/move")
+                    .logarithmic(true),
+            );
+        });
+        if ui.button("Watch replay…").clicked() {
+            return Some(AppAction::WatchReplay);
+        }
+        if let Some(err) = &self.replay_error {
+            ui.colored_label(Color32::RED, err);
+        }
+        if ui.button("Quit").clicked() {
+            return Some(AppAction::ExitApp);
+        }
+        None
+    }
+
+    fn load_die_sprites(&mut self, ui: &mut Ui) {
+        self.sprite_error = None;
+        if let Some(dir) = &self.custom_dice_dir {
+            match self.die_sprites.init_from_dir(dir, ui) {
+                Ok(()) => return,
+                Err(e) => self.sprite_error = Some(format!("{}. Using built-in dice.", e)),
+            }
+        }
+        #[cfg(feature = "spritesheet")]
+        if let Ok(sheet) = sprites::SpriteSheet::load(include_bytes!("dice/sheet.png"), 6, 1, ui) {
+            self.die_sprites = DieRenderer::from_sprite_sheet(sheet);
+            return;
+        }
+        self.die_sprites
+            .init(ui)
+            .expect("Failed to load die sprites");
+    }
+
+    fn splash(&mut self, ui: &mut Ui) {
+        ui.heading("Farkle");
+        ui.label("Set up game parameters and click 'New Game' to play.");
+        if self.die_sprites.needs_init() {
+            self.load_die_sprites(ui);
+
+            for (idx, die) in self.roll.dice_mut().iter_mut().enumerate() {
+                die.set_value(idx + 1);
+            }
+        }
+        self.draw_dice(ui);
+        self.analysis_panel(ui);
+        self.session_history(ui);
+    }
+
+    fn analysis_panel(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Analyze").show(ui, |ui| {
+            let rules = Ruleset::preset(&self.preset).unwrap_or_default();
+            if *self.analysis_roll.faces() != rules.faces {
+                self.analysis_roll = Roll::with_faces(rules.faces.clone());
+            }
+            let analysis = self.analysis_roll.analyze(self.analysis_hand, &rules);
+            let best = analysis.best().map_or(vec![], |best| best.picks.clone());
+
+            self.die_sprites.set_die_size(self.die_size);
+            let mut edit = None;
+            ui.horizontal(|ui| {
+                ui.visuals_mut().button_frame = false;
+                for (idx, die) in self.analysis_roll.dice().iter().enumerate() {
+                    let overlay = best.contains(&idx).then(|| DieOverlay {
+                        color: Color32::GOLD,
+                        label: Some("★".into()),
+                        border_width: 3.0,
+                    });
+                    if let Some(action) = self.die_sprites.draw_editable_die(die, overlay, ui) {
+                        edit = Some((idx, action));
+                    }
+                }
+            });
+            match edit {
+                Some((idx, DieEdit::CycleFace)) => {
+                    let die = &mut self.analysis_roll.dice_mut()[idx];
+                    die.set_value(die.value() % rules.faces.count + 1);
+                }
+                Some((idx, DieEdit::ToggleKept)) if self.analysis_roll.dice()[idx].picked() => {
+                    self.analysis_roll.release(idx)
+                }
+                Some((idx, DieEdit::ToggleKept)) => self.analysis_roll.set_aside(idx),
+                None => (),
+            }
+            ui.horizontal(|ui| {
+                ui.label("Points in hand");
+                ui.add(egui::DragValue::new(&mut self.analysis_hand).speed(50));
+            });
+
+            if analysis.options.is_empty() {
+                ui.label("Farkle! None of the dice score.");
+                return;
+            }
+            egui::Grid::new("analysis_selections")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Selection");
+                    ui.label("Points");
+                    ui.label("Dice left");
+                    ui.end_row();
+                    for option in &analysis.options {
+                        ui.label(option.values.iter().join(" "));
+                        ui.label(option.value.to_string());
+                        ui.label(option.dice_left.to_string());
+                        ui.end_row();
+                    }
+                });
+            for (left, chance) in &analysis.farkle_chances {
+                let dice = match left {
+                    0 => "6 fresh dice".to_string(),
+                    1 => "1 die".to_string(),
+                    n => format!("{} dice", n),
+                };
+                ui.label(format!(
+                    "Farkle chance rolling {}: {:.1}%",
+                    dice,
+                    chance * 100.
+                ));
+            }
+            if let Some(recommendation) = analysis.recommendation() {
+                ui.label(format!("Recommendation: {}", recommendation));
+            }
+        });
+    }
+
+    fn session_history(&self, ui: &mut Ui) {
+        if let Some(err) = &self.history_error {
+            ui.colored_label(Color32::RED, err);
+        }
+        if self.history.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.heading("Session History");
+        egui::Grid::new("session_history")
+            .striped(true)
+            .show(ui, |ui| {
+                for (idx, game) in self.history.games().enumerate() {
+                    ui.label(format!("Game {} ({} turns)", idx + 1, game.turns()));
+                    ui.label(
+                        game.standings()
+                            .map(|(name, score)| format!("{} {}", name, score))
+                            .join(", "),
+                    );
+                    ui.end_row();
+                }
+            });
+        if let Some(winner) = self.history.session_winner() {
+            ui.label(format!("Session leader: {}", winner));
+        }
+    }
+
+    fn show_selections(&self, ui: &mut Ui) {
+        ui.heading("Selections");
+        let selections = if self.game.state() == GameState::TurnEnded
+            && self.turn_end == TurnEndPhase::Review
+            && !self.banked_hand.is_empty()
+        {
+            self.banked_hand.iter()
+        } else {
+            self.get_current_player().selections()
+        };
+        if selections.len() == 0 {
+            ui.label("(None so far)");
+        } else {
+            let width = ui.available_width() / 2.;
+            egui::Grid::new("selections_table")
+                .min_col_width(width)
+                .striped(true)
+                .show(ui, |ui| {
+                    let mut total = 0;
+                    for sel in selections {
+                        ui.label(sel.to_string()).on_hover_text(sel.explain());
+                        ui.label(sel.value().to_string());
+                        ui.end_row();
+
+                        total += sel.value();
+                    }
+                    ui.label("Total");
+                    ui.label(total.to_string());
+                    ui.end_row();
+                });
+        }
+    }
+
+    fn find_missed_points(&self) -> Option<(u32, u32)> {
+        let roll = self.game.roll();
+        let rules = &self.game.config().rules;
+        let taken = roll.construct_selection_with(rules).ok()?.value();
+        let picked = roll.current_roll_picks().len();
+        let best = roll
+            .all_valid_selections(rules)
+            .into_iter()
+            .filter(|(picks, _)| picks.len() <= picked)
+            .map(|(_, selection)| selection.value())
+            .max()?;
+        (best > taken).then_some((taken, best))
+    }
+
+    fn scores_hidden(&self) -> bool {
+        self.game.config().rules.hidden_scores
+            && !(self.game.is_final_turn() && self.game.state() == GameState::TurnEnded)
+    }
+
+    fn show_leaderboard(&self, ui: &mut Ui) {
+        ui.heading("Leaderboard");
+        if self.scores_hidden() {
+            for player in self.game.players() {
+                ui.label(format!("{}: ?", player.name()));
+            }
+            return;
+        }
+        let current = self.game.current_player_index();
+        let now = ui.input(|i| i.time);
+        let width = ui.available_width() / 2.;
+        egui::Grid::new("leaderboard")
+            .min_col_width(width)
+            .show(ui, |ui| {
+                for standing in self.game.live_standings() {
+                    let idx = standing.player;
+                    let player = &self.game.players()[idx];
+                    let flashes = self
+                        .score_flashes
+                        .iter()
+                        .filter(|flash| flash.player == idx);
+                    let mut name = if standing.behind == 0 && standing.score > 0 {
+                        egui::RichText::new(format!("★ {}", player.name()))
+                    } else {
+                        egui::RichText::new(player.name())
+                    };
+                    if idx == current {
+                        name = name.strong();
+                    }
+                    let mut score = egui::RichText::new(standing.score.to_string());
+                    if player.has_uncommitted_points() {
+                        score = score.italics();
+                    }
+                    if let Some(latest) = flashes.clone().next_back() {
+                        let fade = 1. - ((now - latest.started) / SCORE_FLASH_SECS) as f32;
+                        let color = if latest.points == 0 {
+                            Color32::RED
+                        } else {
+                            Color32::GREEN
+                        }
+                        .gamma_multiply(0.4 * fade);
+                        name = name.background_color(color);
+                        score = score.background_color(color);
+                    }
+                    let response = ui.label(name);
+                    if let Some(hint) = turn_time_hint(player) {
+                        response.on_hover_text(hint);
+                    }
+                    let mut rect = ui
+                        .horizontal(|ui| {
+                            let mut response = ui.label(score);
+                            if player.has_uncommitted_points() {
+                                response = response.on_hover_text(format!(
+                                    "Includes {} points in hand",
+                                    player.hand_value()
+                                ));
+                            }
+                            let rect = response.rect;
+                            if standing.behind > 0 {
+                                ui.weak(format!("−{}", standing.behind))
+                                    .on_hover_text("Points behind the leader")
+                                    .rect
+                            } else {
+                                rect
+                            }
+                        })
+                        .inner;
+                    let remaining = self.game.turns_remaining(idx);
+                    if remaining > 0 {
+                        let projected = player.projected_final_score(remaining);
+                        rect = ui
+                            .weak(format!("→ {}", projected))
+                            .on_hover_text("Projected final score")
+                            .rect;
+                    }
+                    for flash in flashes {
+                        let progress = ((now - flash.started) / SCORE_FLASH_SECS) as f32;
+                        let color = if flash.points == 0 {
+                            Color32::RED
+                        } else {
+                            Color32::GREEN
+                        };
+                        ui.painter().text(
+                            rect.right_center() + egui::vec2(8., -SCORE_FLASH_RISE * progress),
+                            egui::Align2::LEFT_CENTER,
+                            format!("+{}", flash.points),
+                            egui::FontId::proportional(14.),
+                            color.gamma_multiply(1. - progress),
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+        if !self.game.teams().is_empty() {
+            egui::Grid::new("team_leaderboard")
+                .min_col_width(width)
+                .show(ui, |ui| {
+                    for (team, score) in self.game.team_standings() {
+                        ui.label(&self.game.teams()[team].name);
+                        ui.label(score.to_string());
+                        ui.end_row();
+                    }
+                });
+        }
+        if !self.score_flashes.is_empty() {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn end_turn(&mut self) {
+        self.turn_end = if self.review_turns {
+            TurnEndPhase::Review
+        } else {
+            TurnEndPhase::Ready
+        };
+    }
+
+    fn roll_off_screen(&mut self, ctx: &Context, ui: &mut Ui) {
+        let (Some(mut view), Some(roll_off)) = (self.roll_off_view, self.game.roll_off()) else {
+            self.roll_off_view = None;
+            return;
+        };
+        let names = &self.game.config().player_names;
+        let faces = self.game.config().rules.faces.count;
+        let now = ctx.input(|i| i.time);
+        let elapsed = now - view.round_started;
+        let settled = elapsed >= ROLL_OFF_ANIMATION_SECS;
+
+        ui.heading("Rolling for turn order");
+        for (round, rolls) in roll_off.rounds().iter().enumerate().take(view.round + 1) {
+            if round > 0 {
+                let tied = rolls.iter().map(|&(player, _)| &names[player]).join(", ");
+                ui.label(format!("Tie between {}! Rolling again.", tied));
+            }
+            egui::Grid::new(("roll_off", round)).show(ui, |ui| {
+                for (n, &(player, value)) in rolls.iter().enumerate() {
+                    let value = if round < view.round || settled {
+                        value
+                    } else {
+                        (elapsed * 12.) as usize * (n + 1) % faces + 1
+                    };
+                    ui.label(&names[player]);
+                    self.die_sprites
+                        .draw_die(&Die::new_with_value(value), RenderState::Splash, ui);
+                    ui.end_row();
+                }
+            });
+        }
+
+        let last_round = view.round + 1 == roll_off.rounds().len();
+        if !settled {
+            ctx.request_repaint();
+        } else if !last_round {
+            view.round += 1;
+            view.round_started = now;
+            ctx.request_repaint();
+        } else {
+            let order = self.game.players().iter().map(Player::name).join(", ");
+            ui.label(format!("Turn order: {}", order));
+            if Self::get_input("Start game", egui::Key::Enter, ctx, ui) {
+                self.roll_off_view = None;
+                return;
+            }
+        }
+        if !(settled && last_round) && ui.button("Skip").clicked() {
+            self.roll_off_view = None;
+            return;
+        }
+        self.roll_off_view = Some(view);
+    }
+
+    fn return_to_splash(&mut self) {
+        self.game_in_progress = false;
+
+        self.game = Game::default();
+        self.roll_state = None;
+        self.farkle_lost = None;
+        self.score_wiped = None;
+        self.pot_claimed = None;
+        self.special_event = None;
+        self.score_flashes.clear();
+        self.confirm_forfeit = false;
+        self.missed_points = None;
+        self.coached_roll = None;
+        self.bad_selection = None;
+        self.banked_hand.clear();
+        self.roll_off_view = None;
+        self.replay_view = None;
+        self.menu = None;
+        self.menu_status = None;
+        self.export_status = None;
+        for (idx, die) in self.roll.dice_mut().iter_mut().enumerate() {
+            die.set_value(idx + 1);
+        }
+    }
+
+    fn save_game(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("farkle.txt")
+            .save_file()
+        else {
+            return;
+        };
+        self.menu_status = Some(match std::fs::write(&path, self.game.transcript()) {
+            Ok(()) => format!("Saved to {}", path.display()),
+            Err(e) => format!("Failed to save game: {}", e),
+        });
+    }
+
+    fn export_game_log_as_text(&mut self, ui: &mut Ui) {
+        if ui.button("Export Game Log").clicked() {
+            let now = chrono::Local::now();
+            let Some(path) = rfd::FileDialog::new()
+                .set_file_name(format!("farkle_game_{}.txt", now.format("%Y%m%d_%H%M%S")))
+                .save_file()
+            else {
+                return;
+            };
+            let log = format!(
+                "Date: {}\n{}",
+                now.format("%F %T"),
+                self.game.format_as_text()
+            );
+            self.export_status = Some(match std::fs::write(&path, log) {
+                Ok(()) => format!("Exported to {}", path.display()),
+                Err(e) => format!("Failed to export game log: {}", e),
+            });
+        }
+        if let Some(status) = &self.export_status {
+            ui.label(status);
+        }
+    }
+
+    fn rules_reference(ui: &mut Ui) {
+        ui.label(format!("Single 1: {} points", ONE_VALUE));
+        ui.label(format!("Single 5: {} points", FIVE_VALUE));
+        ui.label(format!(
+            "Three of a kind: face value × {} (three 1s score {})",
+            SET_SCALE_VALUE,
+            ONE_VALUE * 10
+        ));
+        ui.label("Each extra die in a set adds the three of a kind value again");
+        ui.label(format!("Straight (1 to 6): {} points", STRAIGHT_VALUE));
+        ui.label(format!("Three pairs: {} points", TRIPLE_PAIR_VALUE));
+        ui.label("A roll with nothing to score is a farkle and loses the hand");
+        ui.label("Scoring with all six dice lets you roll all six again");
+    }
+
+    fn active_rules(&self, ui: &mut Ui) {
+        let config = self.game.config();
+        let rules = &config.rules;
+        egui::Grid::new("active_rules")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Players");
+                ui.label(config.player_names.join(", "));
+                ui.end_row();
+                ui.label("Turns");
+                ui.label(config.turns.to_string());
+                ui.end_row();
+                ui.label("Dice faces");
+                ui.label(rules.faces.to_string());
+                ui.end_row();
+                ui.label("Four of a kind");
+                ui.label(match rules.four_of_a_kind {
+                    FourKindRule::Scale => "Doubles the set".to_string(),
+                    FourKindRule::Flat(points) => format!("Flat {} points", points),
+                    FourKindRule::DoubleTriple => "Twice three of a kind".to_string(),
+                });
+                ui.end_row();
+                ui.label("Shared pot");
+                ui.label(match &rules.pot {
+                    Some(pot) => format!("{} points per bank", pot.contribution),
+                    None => "Off".to_string(),
+                });
+                ui.end_row();
+                ui.label("Bank rounding");
+                ui.label(match rules.bank_rounding {
+                    Some(increment) => format!("{} ({})", increment, rules.rounding),
+                    None => "Off".to_string(),
+                });
+                ui.end_row();
+                ui.label("Hot dice");
+                ui.label(if rules.hot_dice { "On" } else { "Off" });
+                ui.end_row();
+                ui.label("Roll limit");
+                ui.label(match rules.max_rolls_per_turn {
+                    Some(limit) => format!("{} per turn", limit),
+                    None => "Off".to_string(),
+                });
+                ui.end_row();
+                ui.label("Sets of 1s and 5s");
+                ui.label(match rules.singles_only().as_slice() {
+                    [] => "Score as sets".to_string(),
+                    faces => format!("{} only score as singles", faces.iter().join(" and ")),
+                });
+                ui.end_row();
+                ui.label("Three 1s");
+                ui.label(if rules.zilch_wipe {
+                    "Wipe your score"
+                } else {
+                    "Score 1000"
+                });
+                ui.end_row();
+                ui.label("Sudden death");
+                ui.label(if rules.sudden_death { "On" } else { "Off" });
+                ui.end_row();
+                ui.label("Hidden scores");
+                ui.label(if rules.hidden_scores { "On" } else { "Off" });
+                ui.end_row();
+                ui.label("Roll for turn order");
+                ui.label(if config.roll_off { "On" } else { "Off" });
+                ui.end_row();
+                ui.label("Turn order");
+                ui.label(config.turn_order.to_string());
+                ui.end_row();
+                ui.label("Teams");
+                ui.label(if config.teams.is_empty() {
+                    "Off".to_string()
+                } else {
+                    format!(
+                        "{} ({} scoring)",
+                        config.teams.iter().map(|team| &team.name).join(" vs "),
+                        config.team_scoring
+                    )
+                });
+                ui.end_row();
+            });
+    }
+
+    fn pause_menu(&mut self, ctx: &Context) -> Option<AppAction> {
+        let page = self.menu?;
+        let mut action = None;
+        egui::Window::new("Menu")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+            .show(ctx, |ui| match page {
+                MenuPage::Main => {
+                    if ui.button("Resume").clicked() {
+                        self.menu = None;
+                        self.menu_status = None;
+                    }
+                    if ui.button("Save game").clicked() {
+                        self.save_game();
+                    }
+                    if ui.button("Rules reference").clicked() {
+                        self.menu = Some(MenuPage::RulesReference);
+                    }
+                    if ui.button("Settings").clicked() {
+                        self.menu = Some(MenuPage::ActiveRules);
+                    }
+                    if ui.button("Abandon game").clicked() {
+                        self.menu = Some(MenuPage::ConfirmAbandon);
+                    }
+                    if ui.button("Quit").clicked() {
+                        action = Some(AppAction::ExitApp);
+                    }
+                    if let Some(status) = &self.menu_status {
+                        ui.label(status);
+                    }
+                }
+                MenuPage::RulesReference | MenuPage::ActiveRules => {
+                    if page == MenuPage::RulesReference {
+                        Self::rules_reference(ui);
+                    } else {
+                        self.active_rules(ui);
+                    }
+                    if ui.button("Back").clicked() {
+                        self.menu = Some(MenuPage::Main);
+                    }
+                }
+                MenuPage::ConfirmAbandon => {
+                    ui.label("Abandon the current game? All progress will be lost.");
+                    ui.horizontal(|ui| {
+                        if ui.button("Abandon").clicked() {
+                            self.return_to_splash();
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.menu = Some(MenuPage::Main);
+                        }
+                    });
+                }
+            });
+        action
+    }
+
+    fn handle_events(&mut self, now: f64) {
+        let player = self.game.current_player_index();
+        for event in self.game.drain_events() {
+            match event {
+                GameEvent::PotClaimed(pot) => self.pot_claimed = Some(pot),
+                GameEvent::Special(special) => self.special_event = Some(special),
+                GameEvent::Banked(points) => self.score_flashes.push(ScoreFlash {
+                    player,
+                    points,
+                    started: now,
+                }),
+                GameEvent::Farkled { lost } => {
+                    self.farkle_lost = Some(lost);
+                    self.score_flashes.push(ScoreFlash {
+                        player,
+                        points: 0,
+                        started: now,
+                    });
+                }
+                GameEvent::ScoreWiped { player, lost } => {
+                    self.score_wiped = Some((player, lost));
+                    self.score_flashes.push(ScoreFlash {
+                        player,
+                        points: 0,
+                        started: now,
+                    });
+                }
+                _ => (),
+            }
+        }
+        self.score_flashes
+            .retain(|flash| now - flash.started < SCORE_FLASH_SECS);
+    }
+
+    fn start_replay(&mut self, now: f64) {
+        let Some(path) = rfd::FileDialog::new().pick_file() else {
+            return;
+        };
+        let started = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|moves| Playback::start(&moves).map_err(|e| e.to_string()));
+        match started {
+            Ok((game, playback)) => {
+                self.return_to_splash();
+                self.game = game;
+                self.game_in_progress = true;
+                self.replay_error = None;
+                self.replay_view = Some(ReplayView {
+                    playback,
+                    paused: false,
+                    last_step: now,
+                    last_move: None,
+                    error: None,
+                });
+            }
+            Err(e) => self.replay_error = Some(format!("Failed to load replay: {}", e)),
+        }
+    }
+
+    fn replay_screen(&mut self, ctx: &Context, ui: &mut Ui) {
+        let now = ctx.input(|i| i.time);
+        let Some(view) = &mut self.replay_view else {
+            return;
+        };
+        let running = !view.paused && view.error.is_none() && !view.playback.is_finished();
+        if running {
+            let waited = now - view.last_step;
+            if waited >= self.replay_delay {
+                match view.playback.step(&mut self.game) {
+                    Ok(line) => view.last_move = line.map(str::to_lowercase),
+                    Err(e) => view.error = Some(e.to_string()),
+                }
+                view.last_step = now;
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(self.replay_delay));
+            } else {
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                    self.replay_delay - waited,
+                ));
+            }
+        }
+
+        let (done, total) = view.playback.progress();
+        ui.label(format!(
+            "Replay: {}'s turn {} of {}. Move {} of {}",
+            self.game.current_player().name(),
+            self.game.current_turn(),
+            self.game.total_turns(),
+            done,
+            total
+        ));
+        if let Some(mov) = &view.last_move {
+            ui.label(format!("Last move: {}", mov));
+        }
+        if let Some(err) = &view.error {
+            ui.colored_label(Color32::RED, err);
+        } else if view.playback.is_finished() {
+            ui.label("End of replay");
+        }
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            let finished = view.error.is_some() || view.playback.is_finished();
+            ui.add_enabled_ui(!finished, |ui| {
+                let label = if view.paused { "Resume" } else { "Pause" };
+                if Self::get_input(label, egui::Key::Space, ctx, ui) {
+                    view.paused = !view.paused;
+                    view.last_step = now;
+                }
+                if view.paused && ui.button("Step").clicked() {
+                    match view.playback.step(&mut self.game) {
+                        Ok(line) => view.last_move = line.map(str::to_lowercase),
+                        Err(e) => view.error = Some(e.to_string()),
+                    }
+                }
+                if ui.button("Skip to end").clicked() {
+                    if let Err(e) = view.playback.finish(&mut self.game) {
+                        view.error = Some(e.to_string());
+                    }
+                    view.last_move = None;
+                }
+            });
+            close = ui.button("Close replay").clicked();
+        });
+        if close {
+            self.return_to_splash();
+            return;
+        }
+
+        self.handle_events(now);
+        self.draw_dice(ui);
+        if self.compact {
+            self.compact_status(ui);
+        } else {
+            self.show_selections(ui);
+            self.show_leaderboard(ui);
+        }
+    }
+
+    fn roll_history_strip(&self, ui: &mut Ui) {
+        let Some(summary) = self.game.current_turn_summary() else {
+            return;
+        };
+        let earlier = summary.roll_history.len().saturating_sub(1);
+        if earlier == 0 {
+            return;
+        }
+        ui.horizontal(|ui| {
+            for recap in &summary.roll_history[earlier.saturating_sub(ROLL_HISTORY_LEN)..earlier] {
+                ui.group(|ui| {
+                    let mut kept = recap.kept.clone();
+                    for value in &recap.values {
+                        let text = egui::RichText::new(value.to_string()).monospace();
+                        match kept.iter().position(|k| k == value) {
+                            Some(idx) => {
+                                kept.remove(idx);
+                                ui.label(text.strong().color(Color32::GOLD));
+                            }
+                            None => {
+                                ui.label(text.weak());
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    fn game_view(&mut self, ctx: &Context, ui: &mut Ui) {
+        let score = if self.scores_hidden() {
+            "?".to_string()
+        } else {
+            self.get_current_player().score().to_string()
+        };
+        ui.label(format!(
+            "{}'s turn {} of {}. Score: {}",
+            self.get_current_player().name(),
+            self.game.current_turn(),
+            self.game.total_turns(),
+            score
+        ));
+        if let Some(overtime) = self.game.overtime_round() {
+            ui.label(format!("Sudden death round {}", overtime));
+        }
+        if self.game.rolls_this_turn() > 0 {
+            ui.label(format!("Roll {}", self.game.rolls_this_turn()));
+        }
+        if let Some(pot) = self.game.pot() {
+            ui.label(format!("Pot: {}", pot));
+        }
+        let now = ctx.input(|i| i.time);
+        self.handle_events(now);
+        if let Some(pot) = self.pot_claimed {
+            ui.label(format!("Claimed the pot of {} points!", pot));
+        }
+        if let Some(special) = self.special_event {
+            ui.label(special.to_string());
+        }
+
+        if let Some(roll) = self.roll_state {
+            match roll {
+                RollType::Farkle => ui.label("Farkle!"),
+                RollType::TriplePair => ui.label("Triple pair!"),
+                RollType::Straight => ui.label("Straight!"),
+                _ => ui.label(""),
+            };
+        }
+
+        self.roll_history_strip(ui);
+        self.draw_dice(ui);
+
+        match self.game.state() {
+            _ if self.game.roll_limit_reached() && self.game.state().can_bank() => {
+                ui.label(GameError::RollLimitReached.to_string());
+            }
+            GameState::Rolling | GameState::HotDice
+                if !self.game.roll().can_continue(&self.game.config().rules) =>
+            {
+                ui.label(if self.game.roll().is_exhausted() {
+                    GameError::NoDiceLeft.to_string()
+                } else {
+                    "No more scoring dice in this roll. Bank or roll again.".to_string()
+                });
+            }
+            GameState::HotDice => {
+                ui.label("Hot dice! Roll all six again or bank.");
+            }
+            _ => (),
+        }
+        if let (Some(recommendation), true) = (self.game.recommend(), self.suggest_bank) {
+            ui.label(format!("Recommendation: {}", recommendation));
+        }
+
+        if let Some(err) = self.bad_selection.as_ref() {
+            ui.label(err);
+            if !ui.button("OK").clicked() {
+                return;
+            }
+        }
+        self.bad_selection = None;
+
+        if let Some(lost) = self.farkle_lost {
+            egui::Frame::none()
+                .fill(Color32::from_rgb(140, 20, 20))
+                .inner_margin(12.)
+                .rounding(6.)
+                .show(ui, |ui| {
+                    ui.colored_label(Color32::WHITE, "Farkle!");
+                    ui.colored_label(Color32::WHITE, format!("You lose {} unbanked points", lost));
+                });
+            if Self::get_input("OK", egui::Key::Enter, ctx, ui) {
+                self.farkle_lost = None;
+            }
+            return;
+        }
+
+        if let Some((player, lost)) = self.score_wiped {
+            let name = self.game.players()[player].name();
+            egui::Frame::none()
+                .fill(Color32::from_rgb(90, 0, 90))
+                .inner_margin(16.)
+                .rounding(6.)
+                .show(ui, |ui| {
+                    ui.heading(
+                        egui::RichText::new("THREE 1s!")
+                            .color(Color32::WHITE)
+                            .strong(),
+                    );
+                    ui.colored_label(
+                        Color32::WHITE,
+                        format!("{}'s score of {} is wiped out", name, lost),
+                    );
+                });
+            if Self::get_input("OK", egui::Key::Enter, ctx, ui) {
+                self.score_wiped = None;
+            }
+            return;
+        }
+
+        if self.game.state() == GameState::TurnEnded {
+            if self.turn_end == TurnEndPhase::Review {
+                let outcome = self
+                    .game
+                    .turn_log()
+                    .last()
+                    .and_then(|record| record.outcome);
+                if self.roll_state == Some(RollType::Farkle) {
+                    ui.label("Your hand was lost.");
+                } else if let Some(TurnOutcome::Wiped(lost)) = outcome {
+                    ui.label(format!("Your score of {} was wiped out.", lost));
+                } else if outcome == Some(TurnOutcome::Forfeited) {
+                    ui.label("You gave up the turn.");
+                } else {
+                    let banked = match self.game.turn_log().last() {
+                        Some(TurnRecord {
+                            outcome: Some(TurnOutcome::Banked(points)),
+                            ..
+                        }) => *points,
+                        _ => self.banked_hand.iter().map(Selection::value).sum(),
+                    };
+                    ui.label(format!("Banked {} points.", banked));
+                }
+                if Self::get_input("Done reviewing", egui::Key::Enter, ctx, ui) {
+                    self.turn_end = TurnEndPhase::Ready;
+                    self.banked_hand.clear();
+                }
+            } else {
+                if let Some(summary) = self.game.last_turn_summary() {
+                    ui.label(summary.to_string());
+                }
+                if self.game.is_final_turn() {
+                    ui.label("Game Over");
+                    if self.game.winners().is_empty() {
+                        ui.label("No points scored — no winner");
+                    }
+                    egui::CollapsingHeader::new("Roll stats").show(ui, |ui| {
+                        ui.label(self.game.roll_stats().to_string());
+                    });
+                    self.export_game_log_as_text(ui);
+                    if Self::get_input("OK", egui::Key::Enter, ctx, ui) {
+                        self.history.push(self.game.summary());
+                        self.save_history();
+                        self.return_to_splash();
+                    }
+                } else if Self::get_input("Proceed to next turn", egui::Key::Enter, ctx, ui) {
+                    self.game.next_turn();
+                    self.roll_state = None;
+                    self.pot_claimed = None;
+                    self.special_event = None;
+                }
+            }
+        } else {
+            let mut mov = None;
+
+            type Mapping = (&'static str, egui::Key, GameAction, fn(&Game) -> bool);
+            const MOVES: [Mapping; 3] = [
+                ("Roll", egui::Key::R, GameAction::Roll, Game::can_reroll),
+                (
+                    "Confirm Selection",
+                    egui::Key::C,
+                    GameAction::ConfirmSelection,
+                    |game| game.state() == GameState::Picking,
+                ),
+                ("Bank", egui::Key::B, GameAction::Bank, |game| {
+                    game.state().can_bank()
+                }),
+            ];
+
+            ui.horizontal(|ui| {
+                for (name, key, mt, state_check) in MOVES {
+                    if state_check(&self.game) && Self::get_input(name, key, ctx, ui) {
+                        mov = Some(mt);
+                    }
+                }
+            });
+
+            if let (Some((taken, best)), GameState::Picking) =
+                (self.missed_points, self.game.state())
+            {
+                ui.label(format!(
+                    "You could take {} instead of {} — confirm anyway?",
+                    best, taken
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Confirm").clicked() {
+                        mov = Some(GameAction::ConfirmSelection);
+                    }
+                    if ui.button("Adjust").clicked() {
+                        self.missed_points = None;
+                    }
+                });
+            }
+
+            if self.confirm_forfeit {
+                ui.label(format!(
+                    "Give up this turn and lose {} points in hand?",
+                    self.get_current_player().hand_value()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Give up").clicked() {
+                        mov = Some(GameAction::ForfeitTurn);
+                    }
+                    if ui.button("Keep playing").clicked() {
+                        self.confirm_forfeit = false;
+                    }
+                });
+            } else if ui.small_button("Give up turn").clicked() {
+                self.confirm_forfeit = true;
+            }
+
+            if self.game.state().can_bank() {
+                ui.horizontal(|ui| {
+                    for action in self.game.legal_moves() {
+                        let GameAction::PassToTeammate(teammate) = action else {
+                            continue;
+                        };
+                        let name = self.game.players()[teammate].name();
+                        if ui.button(format!("Pass to {}", name)).clicked() {
+                            mov = Some(GameAction::PassToTeammate(teammate));
+                        }
+                    }
+                });
+            }
+
+            if let Some(mov) = mov {
+                match mov {
+                    GameAction::Roll => {
+                        self.missed_points = None;
+                        let hand = self.get_current_player().selections().cloned().collect();
+                        if let Ok(GameEvent::Rolled { roll_type, .. }) =
+                            self.game.apply(GameAction::Roll)
+                        {
+                            match roll_type {
+                                RollType::Farkle => {
+                                    self.banked_hand = hand;
+                                    self.end_turn();
+                                    self.roll_state = Some(roll_type);
+                                }
+                                _ if self.game.state() == GameState::TurnEnded => self.end_turn(),
+                                RollType::Straight | RollType::TriplePair => {
+                                    self.roll_state = Some(roll_type);
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                    GameAction::Bank => {
+                        self.banked_hand =
+                            self.get_current_player().selections().cloned().collect();
+                        let _ = self.game.apply(GameAction::Bank);
+                        self.end_turn();
+                    }
+                    GameAction::ForfeitTurn => {
+                        self.confirm_forfeit = false;
+                        if self.game.apply(GameAction::ForfeitTurn).is_ok() {
+                            self.end_turn();
+                        }
+                    }
+                    GameAction::PassToTeammate(teammate) => {
+                        self.missed_points = None;
+                        if let Err(e) = self.game.apply(GameAction::PassToTeammate(teammate)) {
+                            self.bad_selection = Some(e.to_string());
+                        }
+                    }
+                    GameAction::ConfirmSelection => {
+                        let roll_count = self.game.roll_count();
+                        let missed = self
+                            .coach_selections
+                            .then(|| self.find_missed_points())
+                            .flatten();
+                        match missed {
+                            Some((taken, best)) if self.coached_roll != Some(roll_count) => {
+                                self.coached_roll = Some(roll_count);
+                                self.missed_points = missed;
+                                self.game.record_missed_points(taken, best);
+                            }
+                            _ => {
+                                self.missed_points = None;
+                                if let Err(e) = self.game.apply(GameAction::ConfirmSelection) {
+                                    self.bad_selection = Some(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                    _ => panic!("Unreachable state"),
+                }
+            }
+        }
+
+        if self.compact {
+            self.compact_status(ui);
+        } else {
+            self.show_selections(ui);
+            self.show_leaderboard(ui);
+        }
+    }
+
+    fn compact_status(&self, ui: &mut Ui) {
+        let standings = if self.scores_hidden() {
+            self.game.players().iter().map(Player::name).join(" · ")
+        } else {
+            self.game
+                .live_standings()
+                .iter()
+                .map(|standing| {
+                    let name = self.game.players()[standing.player].name();
+                    format!("{} {}", name, standing.score)
+                })
+                .join(" · ")
+        };
+        ui.label(format!(
+            "Hand: {} | {}",
+            self.get_current_player().hand_value(),
+            standings
+        ));
+    }
+}
+
+impl eframe::App for Farkle {
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+        if self.game_in_progress && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.menu = match self.menu {
+                Some(_) => None,
+                None => Some(MenuPage::Main),
+            };
+            self.menu_status = None;
+        }
+        let was_compact = self.compact;
+        if ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+            self.compact = !self.compact;
+        }
+        let menu_open = self.menu.is_some();
+        let compact = self.compact && self.game_in_progress;
+        egui::SidePanel::left("control_panel").show_animated(ctx, !compact, |ui| {
+            ui.set_enabled(!menu_open);
+            if let Some(action) = self.settings(ui) {
+                match action {
+                    AppAction::StartGame => {
+                        let player_names = self
+                            .player_names
+                            .iter()
+                            .enumerate()
+                            .take(self.player_count)
+                            .map(|(i, name)| {
+                                if name.is_empty() {
+                                    format!("Player {}", i + 1)
+                                } else {
+                                    name.clone()
+                                }
+                            })
+                            .collect();
+
+                        let preset = Ruleset::preset(&self.preset).unwrap_or_default();
+                        self.game = Game::new(GameConfig {
+                            player_names,
+                            turns: self.turn_count as u32,
+                            seed: None,
+                            rules: Ruleset {
+                                pot: self.shared_pot.then(PotRule::default).or(preset.pot),
+                                hidden_scores: self.hidden_scores || preset.hidden_scores,
+                                hot_dice: preset.hot_dice && !self.no_hot_dice,
+                                max_rolls_per_turn: self
+                                    .roll_limit
+                                    .and_then(NonZeroU32::new)
+                                    .or(preset.max_rolls_per_turn),
+                                sudden_death: self.sudden_death || preset.sudden_death,
+                                zilch_wipe: self.zilch_wipe || preset.zilch_wipe,
+                                ..preset
+                            },
+                            roll_off: self.roll_for_order,
+                            roll_seed: None,
+                            handicaps: self.handicaps.clone(),
+                            round_order: RoundOrder::Fixed,
+                            turn_order: self.turn_order,
+                            teams: if self.teams_of_two && self.player_count > 2 {
+                                Team::pairs(self.player_count)
+                            } else {
+                                vec![]
+                            },
+                            team_scoring: TeamScoring::Shared,
+                        });
+                        self.game_in_progress = true;
+                        self.roll_off_view = self.game.roll_off().map(|_| RollOffView {
+                            round: 0,
+                            round_started: ctx.input(|i| i.time),
+                        });
+                    }
+                    AppAction::WatchReplay => self.start_replay(ctx.input(|i| i.time)),
+                    AppAction::ExitApp => frame.close(),
+                }
+            }
+        });
+        let mut central = egui::Frame::central_panel(&ctx.style());
+        if compact {
+            central = central.inner_margin(COMPACT_MARGIN);
+        }
+        egui::CentralPanel::default()
+            .frame(central)
+            .show(ctx, |ui| {
+                ui.set_enabled(!menu_open);
+                if self.game_in_progress && !compact && ui.button("Menu").clicked() {
+                    self.menu = Some(MenuPage::Main);
+                }
+                if self.replay_view.is_some() {
+                    self.replay_screen(ctx, ui)
+                } else if self.game_in_progress && self.roll_off_view.is_some() {
+                    self.roll_off_screen(ctx, ui)
+                } else if self.game_in_progress {
+                    self.game_view(ctx, ui)
+                } else {
+                    self.splash(ui);
+                }
+            });
+        if let Some(AppAction::ExitApp) = self.pause_menu(ctx) {
+            frame.close();
+        }
+        if self.compact != was_compact {
+            frame.set_always_on_top(self.compact);
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
+}
+
+fn main() -> eframe::Result<()> {
+    let compact = std::env::args().any(|arg| arg == "--compact");
+    let native_options = if compact {
+        eframe::NativeOptions {
+            initial_window_size: Some(COMPACT_WINDOW_SIZE),
+            always_on_top: true,
+            ..Default::default()
+        }
+    } else {
+        eframe::NativeOptions::default()
+    };
+    eframe::run_native(
+        "Farkle",
+        native_options,
+        Box::new(move |cc| {
+            let mut app = Farkle::new(cc);
+            app.compact |= compact;
+            Box::new(app)
+        }),
+    )
+}
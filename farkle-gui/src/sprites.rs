@@ -0,0 +1,55 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use eframe::egui::{Ui, Vec2};
+use eframe::epaint::{ColorImage, TextureHandle};
+
+pub struct SpriteSheet {
+    sprites: Vec<(TextureHandle, Vec2)>,
+}
+
+impl SpriteSheet {
+    pub fn load(
+        bytes: &[u8],
+        cols: usize,
+        rows: usize,
+        ui: &mut Ui,
+    ) -> Result<Self, image::ImageError> {
+        let sheet = image::load_from_memory(bytes)?;
+        let width = sheet.width() / cols as u32;
+        let height = sheet.height() / rows as u32;
+        let mut sprites = Vec::with_capacity(cols * rows);
+        for row in 0..rows as u32 {
+            for col in 0..cols as u32 {
+                let sprite = sheet
+                    .crop_imm(col * width, row * height, width, height)
+                    .to_rgba8();
+                let size = [width as usize, height as usize];
+                let image = ColorImage::from_rgba_unmultiplied(size, sprite.as_raw());
+                let name = format!("Sprite {}", sprites.len());
+                let texture = ui.ctx().load_texture(name, image, Default::default());
+                sprites.push((texture, Vec2::new(width as f32, height as f32)));
+            }
+        }
+        Ok(SpriteSheet { sprites })
+    }
+
+    pub fn sprite_count(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn get_sprite(&self, index: usize) -> (TextureHandle, Vec2) {
+        self.sprites[index].clone()
+    }
+}
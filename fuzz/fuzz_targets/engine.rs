@@ -0,0 +1,58 @@
+// Copyright (C) 2023 Arc676/Alessandro Vinciguerra <alesvinciguerra@gmail.com>
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation (version 3)
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsfarkle::farkle::{DieValue, GameAction, GameState};
+use rsfarkle::game::{Game, GameConfig};
+
+fn action(byte: u8) -> GameAction {
+    let arg = byte / 8;
+    match byte % 8 {
+        0 => GameAction::Roll,
+        1 => GameAction::ToggleDie(arg as usize % 8),
+        2 => GameAction::TakeValue(arg as DieValue % 8),
+        3 => GameAction::ConfirmSelection,
+        4 => GameAction::UndoSelection,
+        5 => GameAction::Bank,
+        6 => GameAction::ForfeitTurn,
+        7 => GameAction::PassToTeammate(arg as usize % 8),
+        _ => unreachable!(),
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 9 {
+        return;
+    }
+    let (seed, moves) = data.split_at(8);
+    let mut game = Game::new(GameConfig {
+        player_names: vec!["A".to_string(), "B".to_string()],
+        turns: 2,
+        seed: Some(u64::from_le_bytes(seed.try_into().unwrap())),
+        ..Default::default()
+    });
+    for &byte in moves {
+        if game.is_over() {
+            break;
+        }
+        if game.state() == GameState::TurnEnded {
+            game.next_turn();
+            continue;
+        }
+        let _ = game.apply(action(byte));
+        game.assert_invariants();
+    }
+});